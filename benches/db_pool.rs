@@ -0,0 +1,151 @@
+// 同時リクエスト時のスループットを、共有コネクションプールと
+// 以前のMutexで直列化した実装とで比較するベンチマーク
+// CONCURRENCY個のタスクから同時にTaskRepositoryTrait::find_from_user_idを呼び出す
+//
+// DATABASE_URL=... cargo bench --bench db_pool
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use dotenv::dotenv;
+use entity::user;
+use gakusai2024_backend::{
+    domain::{
+        repository::{task::TaskRepositoryTrait, user::UserRepositoryTrait},
+        task::{Task, TaskStatus},
+        user::User,
+    },
+    infrastructure::db::{task::TaskPersistence, user::UserPersistence, DbConfig},
+};
+use sea_orm::{DatabaseConnection, EntityTrait};
+use time::OffsetDateTime;
+use tokio::{runtime::Runtime, sync::Mutex};
+use uuid::Uuid;
+
+const CONCURRENCY: usize = 32;
+const TASKS_PER_USER: usize = 50;
+
+async fn seed(conn: Arc<DatabaseConnection>, tasks: &TaskPersistence, user_id: &str) -> Vec<Uuid> {
+    let now = OffsetDateTime::now_utc();
+    UserPersistence::new(conn)
+        .insert(User {
+            id: user_id.to_string(),
+            username: "Bench User".to_string(),
+            email: "bench@example.com".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .await
+        .unwrap();
+
+    let mut ids = Vec::with_capacity(TASKS_PER_USER);
+    for i in 0..TASKS_PER_USER {
+        let id = tasks
+            .insert(
+                Task {
+                    id: Uuid::new_v4(),
+                    title: format!("bench_task_{}", i),
+                    description: "bench".to_string(),
+                    user_id: user_id.to_string(),
+                    due_date: now,
+                    priority: 1,
+                    weight: 1,
+                    created_at: now,
+                    updated_at: now,
+                    deleted_at: None,
+                    status: TaskStatus::Todo,
+                    completed_at: None,
+                    parent_id: None,
+                    recurrence: None,
+                    version: 1,
+                },
+                vec![],
+            )
+            .await
+            .unwrap();
+        ids.push(id);
+    }
+    ids
+}
+
+async fn cleanup(
+    conn: &DatabaseConnection,
+    tasks: &TaskPersistence,
+    ids: Vec<Uuid>,
+    user_id: &str,
+) {
+    for id in ids {
+        tasks.purge(id, vec![]).await.unwrap();
+    }
+    // ユーザーの削除はリポジトリに無いため直接消す
+    user::Entity::delete_by_id(user_id.to_string())
+        .exec(conn)
+        .await
+        .unwrap();
+}
+
+fn bench_concurrent_reads(c: &mut Criterion) {
+    dotenv().ok();
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set; skipping db_pool benchmark");
+        return;
+    };
+
+    let rt = Runtime::new().unwrap();
+    let mut config = DbConfig::new(url);
+    config.max_connections = CONCURRENCY as u32;
+    let conn = rt.block_on(config.connect()).unwrap();
+
+    let user_id = format!("bench_user_{}", Uuid::new_v4());
+    let tasks = Arc::new(TaskPersistence::new(conn.clone()));
+    let ids = rt.block_on(seed(conn.clone(), &tasks, &user_id));
+
+    // 以前の実装と同様に、リポジトリをMutexで共有して1件ずつ処理する
+    let locked = Arc::new(Mutex::new(TaskPersistence::new(conn.clone())));
+
+    let mut group = c.benchmark_group("concurrent_requests");
+
+    group.bench_function("mutex", |b| {
+        b.to_async(&rt).iter(|| {
+            let handles: Vec<_> = (0..CONCURRENCY)
+                .map(|_| {
+                    let locked = locked.clone();
+                    let user_id = user_id.clone();
+                    tokio::spawn(async move {
+                        let tasks = locked.lock().await;
+                        tasks.find_from_user_id(user_id).await.unwrap()
+                    })
+                })
+                .collect();
+            async move {
+                for handle in handles {
+                    handle.await.unwrap();
+                }
+            }
+        })
+    });
+
+    group.bench_function("pool", |b| {
+        b.to_async(&rt).iter(|| {
+            let handles: Vec<_> = (0..CONCURRENCY)
+                .map(|_| {
+                    let tasks = tasks.clone();
+                    let user_id = user_id.clone();
+                    tokio::spawn(async move { tasks.find_from_user_id(user_id).await.unwrap() })
+                })
+                .collect();
+            async move {
+                for handle in handles {
+                    handle.await.unwrap();
+                }
+            }
+        })
+    });
+
+    group.finish();
+
+    rt.block_on(cleanup(&conn, &tasks, ids, &user_id));
+}
+
+criterion_group!(benches, bench_concurrent_reads);
+criterion_main!(benches);
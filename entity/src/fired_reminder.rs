@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+// 送信済みのリマインド。再起動後に同じ通知を重複して送らないために記録する
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "fired_reminders")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub task_id: Uuid,
+    // 送信した時点のタスクの期限
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub due_date: TimeDateTimeWithTimeZone,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub offset_minutes: i32,
+    pub fired_at: TimeDateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::task::Entity",
+        from = "Column::TaskId",
+        to = "super::task::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Task,
+}
+
+impl Related<super::task::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Task.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,3 +1,10 @@
+pub mod fired_reminder;
 pub mod hello;
+pub mod reminder_offset;
+pub mod tag;
 pub mod task;
+pub mod task_dependency;
+pub mod task_history;
+pub mod task_share;
+pub mod task_tag;
 pub mod user;
@@ -17,6 +17,27 @@ pub struct Model {
     pub weight: i32,
     pub created_at: TimeDateTimeWithTimeZone,
     pub updated_at: TimeDateTimeWithTimeZone,
+    pub deleted_at: Option<TimeDateTimeWithTimeZone>,
+    pub status: TaskStatus,
+    pub completed_at: Option<TimeDateTimeWithTimeZone>,
+    pub parent_id: Option<Uuid>,
+    // RRULE形式の繰り返しルール
+    pub recurrence: Option<String>,
+    // 楽観的排他制御のためのバージョン。更新のたびに1増える
+    pub version: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum TaskStatus {
+    #[sea_orm(string_value = "todo")]
+    Todo,
+    #[sea_orm(string_value = "in_progress")]
+    InProgress,
+    #[sea_orm(string_value = "done")]
+    Done,
+    #[sea_orm(string_value = "cancelled")]
+    Cancelled,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -29,6 +50,38 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::ParentId",
+        to = "Column::Id",
+        on_update = "Cascade",
+        on_delete = "NoAction"
+    )]
+    Parent,
+}
+
+// 子タスクから親タスクを辿る
+pub struct ParentLink;
+
+impl Linked for ParentLink {
+    type FromEntity = Entity;
+    type ToEntity = Entity;
+
+    fn link(&self) -> Vec<RelationDef> {
+        vec![Relation::Parent.def()]
+    }
+}
+
+// 親タスクから子タスクを辿る
+pub struct ChildrenLink;
+
+impl Linked for ChildrenLink {
+    type FromEntity = Entity;
+    type ToEntity = Entity;
+
+    fn link(&self) -> Vec<RelationDef> {
+        vec![Relation::Parent.def().rev()]
+    }
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -39,7 +92,7 @@ impl Related<super::user::Entity> for Entity {
 
 #[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
-    async fn before_save<C>(self, db: &C, insert: bool) -> Result<Self, DbErr>
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
     where
         C: ConnectionTrait,
     {
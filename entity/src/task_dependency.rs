@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+// task_idのタスクはdepends_on_idのタスクが完了するまで完了できない
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "task_dependencies")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub task_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub depends_on_id: Uuid,
+    pub created_at: TimeDateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::task::Entity",
+        from = "Column::TaskId",
+        to = "super::task::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Task,
+    #[sea_orm(
+        belongs_to = "super::task::Entity",
+        from = "Column::DependsOnId",
+        to = "super::task::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    DependsOn,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
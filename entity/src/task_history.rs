@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "task_histories")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub task_id: Uuid,
+    // タスクの所有者。タスクを完全に削除した後も閲覧できるユーザーの判断に使う
+    pub owner_id: String,
+    // 変更したユーザー
+    pub actor_id: String,
+    pub action: TaskHistoryAction,
+    // フィールドごとの変更前後の値
+    pub changes: Json,
+    pub created_at: TimeDateTimeWithTimeZone,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum TaskHistoryAction {
+    #[sea_orm(string_value = "created")]
+    Created,
+    #[sea_orm(string_value = "updated")]
+    Updated,
+    #[sea_orm(string_value = "deleted")]
+    Deleted,
+    #[sea_orm(string_value = "restored")]
+    Restored,
+    #[sea_orm(string_value = "purged")]
+    Purged,
+}
+
+// task_idは完全に削除されたタスクを指すこともあるため、外部キーを持たない
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,9 +1,25 @@
 pub use sea_orm_migration::prelude::*;
 
+use sqlite::WithSqlite;
+
 mod m20240915_062259_create_hello_table;
 mod m20241004_014513_create_task_table;
 mod m20241004_030615_create_user_table;
 mod m20241008_232913_update_task_table;
+mod m20261018_013021_add_deleted_at_to_task_table;
+mod m20261018_042517_add_status_to_task_table;
+mod m20261018_061204_add_task_list_indexes;
+mod m20261018_083412_create_task_shares_table;
+mod m20261018_101547_add_parent_id_to_task_table;
+mod m20261018_123805_create_task_dependencies_table;
+mod m20261018_141126_create_tags_table;
+mod m20261018_141532_create_task_tags_table;
+mod m20261018_160214_add_recurrence_to_task_table;
+mod m20261018_173205_create_reminder_offsets_table;
+mod m20261018_173418_create_fired_reminders_table;
+mod m20261018_190527_create_task_histories_table;
+mod m20261018_204311_add_version_to_task_table;
+mod sqlite;
 
 pub struct Migrator;
 
@@ -14,7 +30,26 @@ impl MigratorTrait for Migrator {
             Box::new(m20240915_062259_create_hello_table::Migration),
             Box::new(m20241004_014513_create_task_table::Migration),
             Box::new(m20241004_030615_create_user_table::Migration),
-            Box::new(m20241008_232913_update_task_table::Migration),
+            Box::new(WithSqlite::new(
+                m20241008_232913_update_task_table::Migration,
+                sqlite::m20241008_232913_update_task_table::Migration,
+            )),
+            Box::new(m20261018_013021_add_deleted_at_to_task_table::Migration),
+            Box::new(m20261018_042517_add_status_to_task_table::Migration),
+            Box::new(m20261018_061204_add_task_list_indexes::Migration),
+            Box::new(m20261018_083412_create_task_shares_table::Migration),
+            Box::new(WithSqlite::new(
+                m20261018_101547_add_parent_id_to_task_table::Migration,
+                sqlite::m20261018_101547_add_parent_id_to_task_table::Migration,
+            )),
+            Box::new(m20261018_123805_create_task_dependencies_table::Migration),
+            Box::new(m20261018_141126_create_tags_table::Migration),
+            Box::new(m20261018_141532_create_task_tags_table::Migration),
+            Box::new(m20261018_160214_add_recurrence_to_task_table::Migration),
+            Box::new(m20261018_173205_create_reminder_offsets_table::Migration),
+            Box::new(m20261018_173418_create_fired_reminders_table::Migration),
+            Box::new(m20261018_190527_create_task_histories_table::Migration),
+            Box::new(m20261018_204311_add_version_to_task_table::Migration),
         ]
     }
 }
@@ -0,0 +1,50 @@
+use entity::task::{Column, Entity};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-tasks-user_id-created_at")
+                    .table(Entity)
+                    .col(Column::UserId)
+                    .col(Column::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-tasks-user_id-due_date")
+                    .table(Entity)
+                    .col(Column::UserId)
+                    .col(Column::DueDate)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-tasks-user_id-due_date")
+                    .table(Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-tasks-user_id-created_at")
+                    .table(Entity)
+                    .to_owned(),
+            )
+            .await
+    }
+}
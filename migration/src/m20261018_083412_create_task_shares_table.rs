@@ -0,0 +1,51 @@
+use entity::task::{Column as TaskColumn, Entity as Task};
+use entity::task_share::{Column, Entity};
+use entity::user::{Column as UserColumn, Entity as User};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Entity)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Column::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(Column::UserId).string().not_null())
+                    .col(
+                        ColumnDef::new(Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(Index::create().col(Column::TaskId).col(Column::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_TaskShare_TaskId")
+                            .from(Entity, Column::TaskId)
+                            .to(Task, TaskColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_TaskShare_UserId")
+                            .from(Entity, Column::UserId)
+                            .to(User, UserColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,66 @@
+use entity::task::{Column, Entity};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entity)
+                    .add_column(ColumnDef::new(Column::ParentId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                sea_query::ForeignKey::create()
+                    .name("FK_Task_ParentId")
+                    .from(Entity, Column::ParentId)
+                    .to(Entity, Column::Id)
+                    .on_delete(ForeignKeyAction::NoAction)
+                    .on_update(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-tasks-parent_id")
+                    .table(Entity)
+                    .col(Column::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-tasks-parent_id")
+                    .table(Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_foreign_key(
+                sea_query::ForeignKey::drop()
+                    .name("FK_Task_ParentId")
+                    .table(Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entity)
+                    .drop_column(Column::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,61 @@
+use entity::task::{Column as TaskColumn, Entity as Task};
+use entity::task_dependency::{Column, Entity};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Entity)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Column::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(Column::DependsOnId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(Index::create().col(Column::TaskId).col(Column::DependsOnId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_TaskDependency_TaskId")
+                            .from(Entity, Column::TaskId)
+                            .to(Task, TaskColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_TaskDependency_DependsOnId")
+                            .from(Entity, Column::DependsOnId)
+                            .to(Task, TaskColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .check(Expr::col(Column::TaskId).ne(Expr::col(Column::DependsOnId)))
+                    .to_owned(),
+            )
+            .await?;
+        // 依存先タスクからの検索・カスケード削除用のインデックス
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task_dependencies-depends_on_id")
+                    .table(Entity)
+                    .col(Column::DependsOnId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Entity).to_owned())
+            .await
+    }
+}
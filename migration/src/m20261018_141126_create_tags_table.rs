@@ -0,0 +1,59 @@
+use entity::tag::{Column, Entity};
+use entity::user::{Column as UserColumn, Entity as User};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Entity)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Column::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(Column::UserId).string().not_null())
+                    .col(ColumnDef::new(Column::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Column::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_Tag_UserId")
+                            .from(Entity, Column::UserId)
+                            .to(User, UserColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // 同じユーザーが同じ名前のタグを作れないようにする
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-tags-user_id-name")
+                    .table(Entity)
+                    .col(Column::UserId)
+                    .col(Column::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,61 @@
+use entity::tag::{Column as TagColumn, Entity as Tag};
+use entity::task::{Column as TaskColumn, Entity as Task};
+use entity::task_tag::{Column, Entity};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Entity)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Column::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(Column::TagId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(Index::create().col(Column::TaskId).col(Column::TagId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_TaskTag_TaskId")
+                            .from(Entity, Column::TaskId)
+                            .to(Task, TaskColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_TaskTag_TagId")
+                            .from(Entity, Column::TagId)
+                            .to(Tag, TagColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // タグで絞り込むときのためのインデックス
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task_tags-tag_id")
+                    .table(Entity)
+                    .col(Column::TagId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Entity).to_owned())
+            .await
+    }
+}
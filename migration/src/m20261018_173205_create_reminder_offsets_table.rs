@@ -0,0 +1,46 @@
+use entity::reminder_offset::{Column, Entity};
+use entity::user::{Column as UserColumn, Entity as User};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Entity)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Column::UserId).string().not_null())
+                    .col(ColumnDef::new(Column::OffsetMinutes).integer().not_null())
+                    .col(
+                        ColumnDef::new(Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(Column::UserId)
+                            .col(Column::OffsetMinutes),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_ReminderOffset_UserId")
+                            .from(Entity, Column::UserId)
+                            .to(User, UserColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Entity).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,53 @@
+use entity::fired_reminder::{Column, Entity};
+use entity::task::{Column as TaskColumn, Entity as Task};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Entity)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Column::TaskId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Column::DueDate)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Column::OffsetMinutes).integer().not_null())
+                    .col(
+                        ColumnDef::new(Column::FiredAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    // 期限が変わった場合は同じオフセットでも改めて通知する
+                    .primary_key(
+                        Index::create()
+                            .col(Column::TaskId)
+                            .col(Column::DueDate)
+                            .col(Column::OffsetMinutes),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_FiredReminder_TaskId")
+                            .from(Entity, Column::TaskId)
+                            .to(Task, TaskColumn::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Entity).to_owned())
+            .await
+    }
+}
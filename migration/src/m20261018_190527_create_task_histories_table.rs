@@ -0,0 +1,48 @@
+use entity::task_history::{Column, Entity};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Entity)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Column::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(Column::TaskId).uuid().not_null())
+                    .col(ColumnDef::new(Column::OwnerId).string().not_null())
+                    .col(ColumnDef::new(Column::ActorId).string().not_null())
+                    .col(ColumnDef::new(Column::Action).string_len(16).not_null())
+                    .col(ColumnDef::new(Column::Changes).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(Column::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    // タスクを完全に削除しても監査のために履歴は残すため、外部キーは張らない
+                    .to_owned(),
+            )
+            .await?;
+        // タスクごとに時系列で取得するためのインデックス
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-task_histories-task_id-created_at")
+                    .table(Entity)
+                    .col(Column::TaskId)
+                    .col(Column::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Entity).to_owned())
+            .await
+    }
+}
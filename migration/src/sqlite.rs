@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::DbBackend;
+
+pub mod m20241008_232913_update_task_table;
+pub mod m20261018_101547_add_parent_id_to_task_table;
+
+// SQLiteは既存のテーブルに外部キーを追加・削除できない。
+// 適用済みのマイグレーションは変更せず、SQLiteの場合だけ同じバージョンとして別の手順を実行する
+pub struct WithSqlite<M, S> {
+    migration: M,
+    sqlite: S,
+}
+
+impl<M, S> WithSqlite<M, S> {
+    pub fn new(migration: M, sqlite: S) -> Self {
+        Self { migration, sqlite }
+    }
+}
+
+impl<M: MigrationName, S> MigrationName for WithSqlite<M, S> {
+    fn name(&self) -> &str {
+        self.migration.name()
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: MigrationTrait, S: MigrationTrait> MigrationTrait for WithSqlite<M, S> {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DbBackend::Sqlite {
+            self.sqlite.up(manager).await
+        } else {
+            self.migration.up(manager).await
+        }
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DbBackend::Sqlite {
+            self.sqlite.down(manager).await
+        } else {
+            self.migration.down(manager).await
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use entity::task::{Column as TaskColumn, Entity as Task};
+use entity::user::{Column as UserColumn, Entity as User};
+use sea_orm_migration::prelude::*;
+
+// SQLiteではtasksを作り直して外部キーを追加・削除する
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        recreate_task_table(manager, true).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        recreate_task_table(manager, false).await
+    }
+}
+
+// この時点のtasksの列
+const TASK_COLUMNS: [TaskColumn; 9] = [
+    TaskColumn::Id,
+    TaskColumn::Title,
+    TaskColumn::Description,
+    TaskColumn::DueDate,
+    TaskColumn::Priority,
+    TaskColumn::Weight,
+    TaskColumn::CreatedAt,
+    TaskColumn::UpdatedAt,
+    TaskColumn::UserId,
+];
+
+// この時点ではtasksを参照するテーブルは無いため、新しいテーブルにデータを移して置き換える
+async fn recreate_task_table(
+    manager: &SchemaManager<'_>,
+    with_foreign_key: bool,
+) -> Result<(), DbErr> {
+    let new_table = Alias::new("tasks_new");
+    let mut table = Table::create();
+    table
+        .table(new_table.clone())
+        .col(
+            ColumnDef::new(TaskColumn::Id)
+                .uuid()
+                .not_null()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(TaskColumn::Title).string().not_null())
+        .col(ColumnDef::new(TaskColumn::Description).string().not_null())
+        .col(
+            ColumnDef::new(TaskColumn::DueDate)
+                .timestamp_with_time_zone()
+                .not_null(),
+        )
+        .col(ColumnDef::new(TaskColumn::Priority).integer().not_null())
+        .col(ColumnDef::new(TaskColumn::Weight).integer().not_null())
+        .col(
+            ColumnDef::new(TaskColumn::CreatedAt)
+                .timestamp_with_time_zone()
+                .not_null(),
+        )
+        .col(
+            ColumnDef::new(TaskColumn::UpdatedAt)
+                .timestamp_with_time_zone()
+                .not_null(),
+        )
+        .col(ColumnDef::new(TaskColumn::UserId).string().not_null());
+    if with_foreign_key {
+        table.foreign_key(
+            sea_query::ForeignKey::create()
+                .name("FK_User_Id")
+                .from(new_table.clone(), TaskColumn::UserId)
+                .to(User, UserColumn::Id)
+                .on_delete(ForeignKeyAction::Restrict)
+                .on_update(ForeignKeyAction::Cascade),
+        );
+    }
+    manager.create_table(table).await?;
+    // 列の順序に依存しないように、列を指定して移す
+    let copy = Query::insert()
+        .into_table(new_table.clone())
+        .columns(TASK_COLUMNS)
+        .select_from(Query::select().columns(TASK_COLUMNS).from(Task).to_owned())
+        .map_err(|err| DbErr::Migration(err.to_string()))?
+        .to_owned();
+    manager.exec_stmt(copy).await?;
+    manager
+        .drop_table(Table::drop().table(Task).to_owned())
+        .await?;
+    manager
+        .rename_table(Table::rename().table(new_table, Task).to_owned())
+        .await
+}
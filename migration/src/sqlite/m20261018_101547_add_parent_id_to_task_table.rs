@@ -0,0 +1,50 @@
+use entity::task::{Column, Entity};
+use sea_orm_migration::prelude::*;
+
+// SQLiteでは外部キーを列の定義に含めて追加する
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entity)
+                    .add_column(ColumnDef::new(Column::ParentId).uuid().null().extra(
+                        r#"REFERENCES "tasks" ("id") ON DELETE NO ACTION ON UPDATE CASCADE"#,
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-tasks-parent_id")
+                    .table(Entity)
+                    .col(Column::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-tasks-parent_id")
+                    .table(Entity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Entity)
+                    .drop_column(Column::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
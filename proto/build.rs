@@ -0,0 +1,15 @@
+// RPCを追加した場合はここにprotoファイルを追記する
+const PROTOS: [&str; 5] = [
+    "api/hello.proto",
+    "api/user.proto",
+    "api/task.proto",
+    "api/tag.proto",
+    "api/reminder.proto",
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // protocをシステムにインストールしなくてもビルドできるよう、同梱のバイナリを使う
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_build::configure().compile_protos(&PROTOS, &["."])?;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+// AUTH_SECRETで署名したトークンを発行する
+//
+// cargo run --bin issue_token -- [--admin] <user_id> [有効期間(時間)]
+// --adminを付けると管理者のroleを持つトークンを発行する
+use std::{env, process, time::Duration};
+
+use dotenv::dotenv;
+use gakusai2024_backend::interface::auth::{issue_token_with_role, Role};
+
+const DEFAULT_EXPIRES_IN_HOURS: u64 = 24;
+
+fn main() {
+    dotenv().ok();
+    let mut args = env::args().skip(1).peekable();
+    let role = if args.next_if(|arg| arg == "--admin").is_some() {
+        Role::Admin
+    } else {
+        Role::User
+    };
+    let Some(user_id) = args.next().filter(|id| !id.is_empty()) else {
+        eprintln!("usage: issue_token [--admin] <user_id> [expires_in_hours]");
+        process::exit(2);
+    };
+    let hours = match args.next() {
+        Some(hours) => hours.parse().unwrap_or_else(|_| {
+            eprintln!("expires_in_hours must be a positive integer");
+            process::exit(2);
+        }),
+        None => DEFAULT_EXPIRES_IN_HOURS,
+    };
+    let secret = env::var("AUTH_SECRET").expect("AUTH_SECRET must be set");
+
+    let token = issue_token_with_role(
+        secret.as_bytes(),
+        &user_id,
+        role,
+        Duration::from_secs(hours * 60 * 60),
+    )
+    .expect("failed to sign token");
+    println!("{}", token);
+}
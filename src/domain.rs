@@ -1,3 +1,15 @@
 pub mod hello;
+pub mod notifier;
+pub mod planner;
+pub mod recurrence;
+pub mod reminder;
 pub mod repository;
+pub mod tag;
 pub mod task;
+pub mod task_dependency;
+pub mod task_event;
+pub mod task_history;
+pub mod task_query;
+pub mod task_tree;
+pub mod urgency;
+pub mod user;
@@ -0,0 +1,11 @@
+use std::future::Future;
+
+use mockall::automock;
+
+use crate::{domain::reminder::Reminder, error::CustomError};
+
+// リマインドの送信先。実装を差し替えることでメールやプッシュ通知などに対応する
+#[automock]
+pub trait Notifier {
+    fn notify(&self, reminder: Reminder) -> impl Future<Output = Result<(), CustomError>> + Send;
+}
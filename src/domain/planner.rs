@@ -0,0 +1,257 @@
+use std::cmp::Reverse;
+
+use time::{Date, Duration};
+use uuid::Uuid;
+
+use crate::domain::task::{Task, TaskStatusExt};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlanOptions {
+    pub start_date: Date,
+    // 1日に作業できる時間
+    pub hours_per_day: u32,
+    // 計画する日数
+    pub days: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanEntry {
+    pub task_id: Uuid,
+    pub hours: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanDay {
+    pub date: Date,
+    pub entries: Vec<PlanEntry>,
+}
+
+impl PlanDay {
+    pub fn scheduled_hours(&self) -> u32 {
+        self.entries.iter().map(|e| e.hours).sum()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Plan {
+    pub days: Vec<PlanDay>,
+    // 期限までに終わらないタスク
+    pub late_task_ids: Vec<Uuid>,
+    // 計画期間内に割り当てきれなかったタスク
+    pub unscheduled_task_ids: Vec<Uuid>,
+}
+
+// weightを作業時間(時間)とみなし、期限が早い順、同じ期限なら優先度(priority)が高い順に
+// 1日の作業時間を埋めていく。1日に収まらないタスクは翌日以降に分割する
+pub fn generate_plan(tasks: &[Task], options: PlanOptions) -> Plan {
+    let mut queue: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| !t.status.is_closed() && t.weight > 0)
+        .collect();
+    queue.sort_by_key(|t| (t.due_date, Reverse(t.priority), t.created_at, t.id));
+
+    let mut days: Vec<PlanDay> = (0..options.days)
+        .map(|i| PlanDay {
+            date: options.start_date + Duration::days(i as i64),
+            entries: Vec::new(),
+        })
+        .collect();
+    let mut late_task_ids = Vec::new();
+    let mut unscheduled_task_ids = Vec::new();
+
+    let mut day_index = 0;
+    let mut free_hours = options.hours_per_day;
+    for task in queue {
+        let mut remaining = task.weight as u32;
+        while remaining > 0 && day_index < days.len() {
+            if free_hours == 0 {
+                day_index += 1;
+                free_hours = options.hours_per_day;
+                continue;
+            }
+            let hours = remaining.min(free_hours);
+            days[day_index].entries.push(PlanEntry {
+                task_id: task.id,
+                hours,
+            });
+            remaining -= hours;
+            free_hours -= hours;
+        }
+
+        if remaining > 0 {
+            unscheduled_task_ids.push(task.id);
+        } else if days[day_index].date > task.due_date.date() {
+            late_task_ids.push(task.id);
+        }
+    }
+
+    Plan {
+        days,
+        late_task_ids,
+        unscheduled_task_ids,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Month, OffsetDateTime, Time};
+    use uuid::uuid;
+
+    use super::*;
+    use crate::domain::task::{test_task, TaskStatus};
+
+    const TASK1: Uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+    const TASK2: Uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+    const TASK3: Uuid = uuid!("00000000-0000-0000-0000-ffff00000003");
+
+    // 計画開始日(2024-10-01)からoffset日後
+    fn day(offset: i64) -> Date {
+        Date::from_calendar_date(2024, Month::October, 1).unwrap() + Duration::days(offset)
+    }
+
+    fn create_test_task(id: Uuid, due_date: Date, priority: i32, weight: i32) -> Task {
+        let created_at = day(0).with_time(Time::MIDNIGHT).assume_utc();
+        Task {
+            id,
+            due_date: due_date.with_time(Time::MIDNIGHT).assume_utc(),
+            priority,
+            weight,
+            created_at,
+            updated_at: created_at,
+            ..test_task()
+        }
+    }
+
+    fn options(hours_per_day: u32, days: u32) -> PlanOptions {
+        PlanOptions {
+            start_date: day(0),
+            hours_per_day,
+            days,
+        }
+    }
+
+    fn entries(plan: &Plan) -> Vec<Vec<(Uuid, u32)>> {
+        plan.days
+            .iter()
+            .map(|d| d.entries.iter().map(|e| (e.task_id, e.hours)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_orders_by_due_date_and_priority() {
+        let tasks = vec![
+            create_test_task(TASK1, day(2), 1, 2),
+            create_test_task(TASK2, day(1), 1, 2),
+            create_test_task(TASK3, day(2), 5, 2),
+        ];
+
+        let plan = generate_plan(&tasks, options(4, 2));
+
+        assert_eq!(
+            entries(&plan),
+            vec![vec![(TASK2, 2), (TASK3, 2)], vec![(TASK1, 2)]]
+        );
+        assert_eq!(plan.days[0].date, day(0));
+        assert_eq!(plan.days[1].date, day(1));
+        assert_eq!(plan.days[0].scheduled_hours(), 4);
+        assert!(plan.late_task_ids.is_empty());
+        assert!(plan.unscheduled_task_ids.is_empty());
+    }
+
+    #[test]
+    fn test_plan_splits_task_across_days() {
+        let tasks = vec![create_test_task(TASK1, day(4), 1, 5)];
+
+        let plan = generate_plan(&tasks, options(2, 3));
+
+        assert_eq!(
+            entries(&plan),
+            vec![vec![(TASK1, 2)], vec![(TASK1, 2)], vec![(TASK1, 1)]]
+        );
+        assert!(plan.late_task_ids.is_empty());
+    }
+
+    #[test]
+    fn test_plan_skips_closed_tasks() {
+        let mut done = create_test_task(TASK1, day(0), 5, 3);
+        done.status = TaskStatus::Done;
+        let tasks = vec![done, create_test_task(TASK2, day(1), 1, 1)];
+
+        let plan = generate_plan(&tasks, options(8, 1));
+
+        assert_eq!(entries(&plan), vec![vec![(TASK2, 1)]]);
+    }
+
+    #[test]
+    fn test_plan_over_committed() {
+        // 2日で6時間しか無いのに合計8時間のタスクがある
+        let tasks = vec![
+            create_test_task(TASK1, day(0), 1, 4),
+            create_test_task(TASK2, day(1), 1, 4),
+        ];
+
+        let plan = generate_plan(&tasks, options(3, 2));
+
+        assert_eq!(
+            entries(&plan),
+            vec![vec![(TASK1, 3)], vec![(TASK1, 1), (TASK2, 2)]]
+        );
+        assert_eq!(plan.late_task_ids, vec![TASK1]);
+        assert_eq!(plan.unscheduled_task_ids, vec![TASK2]);
+    }
+
+    #[test]
+    fn test_plan_infeasible_due_dates() {
+        // 期限が開始日より前のタスクと、期限までに作業時間が足りないタスク
+        let tasks = vec![
+            create_test_task(TASK1, day(-1), 1, 1),
+            create_test_task(TASK2, day(0), 1, 3),
+            create_test_task(TASK3, day(9), 1, 1),
+        ];
+
+        let plan = generate_plan(&tasks, options(2, 5));
+
+        assert_eq!(
+            entries(&plan),
+            vec![
+                vec![(TASK1, 1), (TASK2, 1)],
+                vec![(TASK2, 2)],
+                vec![(TASK3, 1)],
+                vec![],
+                vec![],
+            ]
+        );
+        assert_eq!(plan.late_task_ids, vec![TASK1, TASK2]);
+        assert!(plan.unscheduled_task_ids.is_empty());
+    }
+
+    #[test]
+    fn test_plan_is_deterministic() {
+        let tasks = vec![
+            create_test_task(TASK2, day(1), 3, 2),
+            create_test_task(TASK1, day(1), 3, 2),
+        ];
+        let mut reversed = tasks.clone();
+        reversed.reverse();
+
+        assert_eq!(
+            generate_plan(&tasks, options(3, 2)),
+            generate_plan(&reversed, options(3, 2))
+        );
+        assert_eq!(
+            entries(&generate_plan(&tasks, options(3, 2)))[0],
+            vec![(TASK1, 2), (TASK2, 1)]
+        );
+    }
+
+    #[test]
+    fn test_plan_uses_date_of_due_date() {
+        // 期限日の途中までに終われば遅延とはしない
+        let mut task = create_test_task(TASK1, day(0), 1, 1);
+        task.due_date = OffsetDateTime::new_utc(day(0), Time::from_hms(9, 0, 0).unwrap());
+
+        let plan = generate_plan(&[task], options(8, 1));
+
+        assert!(plan.late_task_ids.is_empty());
+    }
+}
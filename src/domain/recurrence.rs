@@ -0,0 +1,469 @@
+use std::{fmt, str::FromStr};
+
+use time::{Date, Duration, Month, OffsetDateTime, Weekday};
+
+// RFC 5545のRRULEのうち、FREQ(DAILY/WEEKLY/MONTHLY)、INTERVAL、BYDAY、BYMONTHDAY、UNTIL、COUNTに対応する
+// 例: "FREQ=WEEKLY;BYDAY=MO,TH;COUNT=10"
+// 次の発生日時が表せる範囲を超えないように、INTERVALの上限を設ける
+pub const MAX_INTERVAL: u32 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecurrenceEnd {
+    Never,
+    // この日付までの発生に限る
+    Until(Date),
+    // 残りの発生回数(現在のタスクを含む)
+    Count(u32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    // 月曜始まりで並べた曜日
+    pub by_weekday: Vec<Weekday>,
+    // MONTHLYで発生する日。省略時は最初のdueの日を次のルールに引き継ぐ
+    pub by_month_day: Option<u8>,
+    pub end: RecurrenceEnd,
+}
+
+impl RecurrenceRule {
+    // dueの次の発生日時と、次のタスクに引き継ぐルールを返す。
+    // 繰り返しが終わっている、または次の発生日時が表せない場合はNone
+    pub fn next(&self, due: OffsetDateTime) -> Option<(OffsetDateTime, RecurrenceRule)> {
+        let end = match self.end {
+            RecurrenceEnd::Count(count) if count <= 1 => return None,
+            RecurrenceEnd::Count(count) => RecurrenceEnd::Count(count - 1),
+            end => end,
+        };
+        let next_due = match self.frequency {
+            Frequency::Daily => self.next_daily(due)?,
+            Frequency::Weekly => self.next_weekly(due)?,
+            Frequency::Monthly => next_monthly(due, self.interval, self.month_day(due))?,
+        };
+        if let RecurrenceEnd::Until(until) = end {
+            if next_due.date() > until {
+                return None;
+            }
+        }
+        Some((
+            next_due,
+            RecurrenceRule {
+                by_month_day: match self.frequency {
+                    Frequency::Monthly => Some(self.month_day(due)),
+                    _ => None,
+                },
+                end,
+                ..self.clone()
+            },
+        ))
+    }
+
+    fn month_day(&self, due: OffsetDateTime) -> u8 {
+        self.by_month_day.unwrap_or_else(|| due.day())
+    }
+
+    fn next_daily(&self, due: OffsetDateTime) -> Option<OffsetDateTime> {
+        let step = Duration::days(i64::from(self.interval));
+        if self.by_weekday.is_empty() {
+            return due.checked_add(step);
+        }
+        // 曜日の指定に合う日が1週間分の候補に無ければ繰り返さない
+        for k in 1..=7 {
+            let candidate = due.checked_add(step.checked_mul(k)?)?;
+            if self.by_weekday.contains(&candidate.weekday()) {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    fn next_weekly(&self, due: OffsetDateTime) -> Option<OffsetDateTime> {
+        if self.by_weekday.is_empty() {
+            return due.checked_add(Duration::weeks(i64::from(self.interval)));
+        }
+        let current = due.weekday().number_days_from_monday() as i64;
+        // 同じ週の残りの曜日、無ければinterval週後の最初の曜日
+        match self
+            .by_weekday
+            .iter()
+            .map(|w| w.number_days_from_monday() as i64)
+            .find(|w| *w > current)
+        {
+            Some(w) => due.checked_add(Duration::days(w - current)),
+            None => {
+                let first = self.by_weekday[0].number_days_from_monday() as i64;
+                due.checked_add(Duration::days(
+                    7 * i64::from(self.interval) - current + first,
+                ))
+            }
+        }
+    }
+}
+
+// 月末を超える日付はその月の末日にする。dayは元の日を保つため、末日に丸めた後も戻る(1/31 -> 2/28 -> 3/31)
+fn next_monthly(due: OffsetDateTime, interval: u32, day: u8) -> Option<OffsetDateTime> {
+    let months =
+        (due.year() * 12 + (due.month() as i32 - 1)).checked_add(i32::try_from(interval).ok()?)?;
+    let year = months.div_euclid(12);
+    let month = Month::try_from((months.rem_euclid(12) + 1) as u8).ok()?;
+    let day = day.min(month.length(year));
+    let date = Date::from_calendar_date(year, month, day).ok()?;
+    Some(due.replace_date(date))
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_weekday = Vec::new();
+        let mut by_month_day = None;
+        let mut until = None;
+        let mut count = None;
+
+        for part in s.trim().trim_start_matches("RRULE:").split(';') {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid part '{}'", part))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(format!("unsupported FREQ '{}'", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|n| (1..=MAX_INTERVAL).contains(n))
+                        .ok_or_else(|| {
+                            format!("INTERVAL must be an integer from 1 to {}", MAX_INTERVAL)
+                        })?
+                }
+                "BYDAY" => {
+                    by_weekday = value
+                        .split(',')
+                        .map(parse_weekday)
+                        .collect::<Result<Vec<_>, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = Some(
+                        value
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|n| (1..=31).contains(n))
+                            .ok_or_else(|| {
+                                "BYMONTHDAY must be an integer from 1 to 31".to_string()
+                            })?,
+                    )
+                }
+                "UNTIL" => {
+                    until = Some(
+                        parse_date(value)
+                            .ok_or_else(|| "UNTIL must be a date in YYYYMMDD format".to_string())?,
+                    )
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|n| *n > 0)
+                            .ok_or_else(|| "COUNT must be a positive integer".to_string())?,
+                    )
+                }
+                _ => return Err(format!("unsupported part '{}'", key)),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| "FREQ is required".to_string())?;
+        if frequency == Frequency::Monthly && !by_weekday.is_empty() {
+            return Err("BYDAY is only supported with DAILY or WEEKLY".to_string());
+        }
+        if frequency != Frequency::Monthly && by_month_day.is_some() {
+            return Err("BYMONTHDAY is only supported with MONTHLY".to_string());
+        }
+        by_weekday.sort_by_key(|w| w.number_days_from_monday());
+        by_weekday.dedup();
+        let end = match (until, count) {
+            (Some(_), Some(_)) => return Err("UNTIL and COUNT cannot be used together".to_string()),
+            (Some(until), None) => RecurrenceEnd::Until(until),
+            (None, Some(count)) => RecurrenceEnd::Count(count),
+            (None, None) => RecurrenceEnd::Never,
+        };
+
+        Ok(Self {
+            frequency,
+            interval,
+            by_weekday,
+            by_month_day,
+            end,
+        })
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_weekday.is_empty() {
+            let days: Vec<&str> = self.by_weekday.iter().map(|w| weekday_code(*w)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(day) = self.by_month_day {
+            write!(f, ";BYMONTHDAY={}", day)?;
+        }
+        match self.end {
+            RecurrenceEnd::Never => Ok(()),
+            RecurrenceEnd::Until(until) => write!(
+                f,
+                ";UNTIL={:04}{:02}{:02}",
+                until.year(),
+                until.month() as u8,
+                until.day()
+            ),
+            RecurrenceEnd::Count(count) => write!(f, ";COUNT={}", count),
+        }
+    }
+}
+
+const WEEKDAYS: [(Weekday, &str); 7] = [
+    (Weekday::Monday, "MO"),
+    (Weekday::Tuesday, "TU"),
+    (Weekday::Wednesday, "WE"),
+    (Weekday::Thursday, "TH"),
+    (Weekday::Friday, "FR"),
+    (Weekday::Saturday, "SA"),
+    (Weekday::Sunday, "SU"),
+];
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    WEEKDAYS
+        .iter()
+        .find(|(_, code)| code.eq_ignore_ascii_case(value))
+        .map(|(weekday, _)| *weekday)
+        .ok_or_else(|| format!("invalid BYDAY '{}'", value))
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    WEEKDAYS
+        .iter()
+        .find(|(w, _)| *w == weekday)
+        .map(|(_, code)| *code)
+        .unwrap()
+}
+
+// UNTILは日付(YYYYMMDD)のみ受け付け、日時形式の場合は日付部分だけを使う
+fn parse_date(value: &str) -> Option<Date> {
+    let digits = value.get(..8)?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year = digits[..4].parse().ok()?;
+    let month = Month::try_from(digits[4..6].parse::<u8>().ok()?).ok()?;
+    let day = digits[6..8].parse().ok()?;
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Time, UtcOffset};
+
+    use super::*;
+
+    // 2024-10-02(水) 09:00 +09:00
+    fn due() -> OffsetDateTime {
+        Date::from_calendar_date(2024, Month::October, 2)
+            .unwrap()
+            .with_time(Time::from_hms(9, 0, 0).unwrap())
+            .assume_offset(UtcOffset::from_hms(9, 0, 0).unwrap())
+    }
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    fn next(rule: &str, due: OffsetDateTime) -> Option<(OffsetDateTime, String)> {
+        let rule: RecurrenceRule = rule.parse().unwrap();
+        rule.next(due).map(|(d, r)| (d, r.to_string()))
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let rule: RecurrenceRule = "freq=weekly;byday=th,mo,mo;count=3".parse().unwrap();
+
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.by_weekday, vec![Weekday::Monday, Weekday::Thursday]);
+        assert_eq!(rule.end, RecurrenceEnd::Count(3));
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;BYDAY=MO,TH;COUNT=3");
+        assert_eq!(
+            "RRULE:FREQ=MONTHLY;INTERVAL=2;UNTIL=20250101"
+                .parse::<RecurrenceRule>()
+                .unwrap()
+                .to_string(),
+            "FREQ=MONTHLY;INTERVAL=2;UNTIL=20250101"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for rule in [
+            "",
+            "INTERVAL=2",
+            "FREQ=YEARLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;INTERVAL=1001",
+            "FREQ=MONTHLY;INTERVAL=4294967295",
+            "FREQ=MONTHLY;INTERVAL=99999999999",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=MONTHLY;BYDAY=MO",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=WEEKLY;BYMONTHDAY=1",
+            "FREQ=DAILY;UNTIL=2024-10-01",
+            "FREQ=DAILY;UNTIL=20241001;COUNT=2",
+            "FREQ=DAILY;COUNT=0",
+            "FREQ=DAILY;BYMONTH=1",
+        ] {
+            assert!(rule.parse::<RecurrenceRule>().is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn test_next_daily() {
+        let (next_due, rule) = next("FREQ=DAILY;INTERVAL=3", due()).unwrap();
+
+        assert_eq!(next_due, due() + Duration::days(3));
+        assert_eq!(next_due.offset(), due().offset());
+        assert_eq!(rule, "FREQ=DAILY;INTERVAL=3");
+    }
+
+    #[test]
+    fn test_next_daily_by_weekday() {
+        // 水曜の次の平日は木曜、金曜の次は月曜
+        let weekdays = "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR";
+        assert_eq!(
+            next(weekdays, due()).unwrap().0.date(),
+            date(2024, Month::October, 3)
+        );
+        assert_eq!(
+            next(weekdays, due() + Duration::days(2)).unwrap().0.date(),
+            date(2024, Month::October, 7)
+        );
+    }
+
+    #[test]
+    fn test_next_weekly() {
+        assert_eq!(
+            next("FREQ=WEEKLY", due()).unwrap().0,
+            due() + Duration::weeks(1)
+        );
+        // 水曜 -> 金曜 -> 2週後の月曜
+        let rule = "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR";
+        let (friday, _) = next(rule, due()).unwrap();
+        assert_eq!(friday.date(), date(2024, Month::October, 4));
+        let (monday, _) = next(rule, friday).unwrap();
+        assert_eq!(monday.date(), date(2024, Month::October, 14));
+        assert_eq!(monday.time(), due().time());
+    }
+
+    #[test]
+    fn test_next_monthly() {
+        assert_eq!(
+            next("FREQ=MONTHLY", due()).unwrap().0.date(),
+            date(2024, Month::November, 2)
+        );
+        assert_eq!(
+            next("FREQ=MONTHLY;INTERVAL=3", due()).unwrap().0.date(),
+            date(2025, Month::January, 2)
+        );
+        let jan31 = due().replace_date(date(2024, Month::January, 31));
+        assert_eq!(
+            next("FREQ=MONTHLY", jan31).unwrap().0.date(),
+            date(2024, Month::February, 29)
+        );
+    }
+
+    #[test]
+    fn test_next_monthly_from_31st() {
+        // 末日に丸めた翌月以降も31日に戻る
+        let mut due = due().replace_date(date(2025, Month::January, 31));
+        let mut rule = "FREQ=MONTHLY".to_string();
+        let mut dates = Vec::new();
+        for _ in 0..4 {
+            (due, rule) = next(&rule, due).unwrap();
+            dates.push(due.date());
+        }
+
+        assert_eq!(
+            dates,
+            vec![
+                date(2025, Month::February, 28),
+                date(2025, Month::March, 31),
+                date(2025, Month::April, 30),
+                date(2025, Month::May, 31),
+            ]
+        );
+        assert_eq!(rule, "FREQ=MONTHLY;BYMONTHDAY=31");
+    }
+
+    #[test]
+    fn test_next_out_of_range() {
+        // 上限のINTERVALでも、表せる範囲を超える場合は繰り返さない
+        let last_year = due().replace_date(date(9999, Month::June, 1));
+        for rule in [
+            "FREQ=DAILY;INTERVAL=1000",
+            "FREQ=DAILY;INTERVAL=1000;BYDAY=MO",
+            "FREQ=WEEKLY;INTERVAL=1000",
+            "FREQ=WEEKLY;INTERVAL=1000;BYDAY=MO",
+            "FREQ=MONTHLY;INTERVAL=1000",
+        ] {
+            assert_eq!(next(rule, last_year), None, "{}", rule);
+        }
+
+        // パース時の上限を経由しないルールでもパニックしない
+        for frequency in [Frequency::Daily, Frequency::Weekly, Frequency::Monthly] {
+            let rule = RecurrenceRule {
+                frequency,
+                interval: u32::MAX,
+                by_weekday: Vec::new(),
+                by_month_day: None,
+                end: RecurrenceEnd::Never,
+            };
+            assert_eq!(rule.next(due()), None, "{:?}", frequency);
+        }
+    }
+
+    #[test]
+    fn test_next_count() {
+        let (_, rule) = next("FREQ=DAILY;COUNT=2", due()).unwrap();
+
+        assert_eq!(rule, "FREQ=DAILY;COUNT=1");
+        assert_eq!(next(&rule, due()), None);
+    }
+
+    #[test]
+    fn test_next_until() {
+        assert!(next("FREQ=WEEKLY;UNTIL=20241009", due()).is_some());
+        assert_eq!(next("FREQ=WEEKLY;UNTIL=20241008", due()), None);
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use entity::{fired_reminder, reminder_offset};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::domain::task::{Task, TaskStatusExt};
+
+pub type ReminderOffset = reminder_offset::Model;
+pub type FiredReminder = fired_reminder::Model;
+
+// 設定が無いユーザーには1日前と1時間前に通知する
+pub const DEFAULT_OFFSET_MINUTES: [i32; 2] = [24 * 60, 60];
+pub const MAX_OFFSET_MINUTES: i32 = 7 * 24 * 60;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reminder {
+    pub task_id: Uuid,
+    pub user_id: String,
+    pub title: String,
+    pub due_date: OffsetDateTime,
+    pub offset_minutes: i32,
+}
+
+impl Reminder {
+    pub fn fire_at(&self) -> OffsetDateTime {
+        self.due_date - Duration::minutes(self.offset_minutes as i64)
+    }
+}
+
+// 期限前の未完了タスクについて、通知時刻を過ぎていてまだ送っていないリマインドを返す。
+// 通知時刻を過ぎたオフセットが複数ある場合は期限に最も近いものだけを送る
+pub fn due_reminders(
+    tasks: &[Task],
+    offsets: &[ReminderOffset],
+    fired: &[FiredReminder],
+    now: OffsetDateTime,
+) -> Vec<Reminder> {
+    let mut offsets_of: HashMap<&str, Vec<i32>> = HashMap::new();
+    for offset in offsets {
+        offsets_of
+            .entry(offset.user_id.as_str())
+            .or_default()
+            .push(offset.offset_minutes);
+    }
+    // 期限が変更されたタスクは、変更前の期限に対して送ったものを送信済みとみなさない
+    let fired: HashSet<(Uuid, OffsetDateTime, i32)> = fired
+        .iter()
+        .map(|f| (f.task_id, f.due_date, f.offset_minutes))
+        .collect();
+
+    let mut reminders: Vec<Reminder> = tasks
+        .iter()
+        .filter(|task| task.deleted_at.is_none() && !task.status.is_closed() && task.due_date > now)
+        .filter_map(|task| {
+            let offsets = offsets_of
+                .get(task.user_id.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or(&DEFAULT_OFFSET_MINUTES);
+            let offset_minutes = offsets
+                .iter()
+                .copied()
+                .filter(|&o| task.due_date - Duration::minutes(o as i64) <= now)
+                .min()?;
+            if fired.contains(&(task.id, task.due_date, offset_minutes)) {
+                return None;
+            }
+            Some(Reminder {
+                task_id: task.id,
+                user_id: task.user_id.clone(),
+                title: task.title.clone(),
+                due_date: task.due_date,
+                offset_minutes,
+            })
+        })
+        .collect();
+    reminders.sort_by_key(|r| (r.fire_at(), r.task_id));
+    reminders
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::domain::task::TaskStatus;
+
+    const TASK1: Uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+    const TASK2: Uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+
+    fn now() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap()
+    }
+
+    fn create_test_task(id: Uuid, due_in: Duration) -> Task {
+        Task {
+            id,
+            title: "test_title".to_string(),
+            description: "test_description".to_string(),
+            due_date: now() + due_in,
+            priority: 1,
+            weight: 1,
+            created_at: now(),
+            updated_at: now(),
+            deleted_at: None,
+            status: TaskStatus::Todo,
+            completed_at: None,
+            parent_id: None,
+            recurrence: None,
+            version: 1,
+            user_id: "testuserid".to_string(),
+        }
+    }
+
+    fn offset(user_id: &str, offset_minutes: i32) -> ReminderOffset {
+        ReminderOffset {
+            user_id: user_id.to_string(),
+            offset_minutes,
+            created_at: now(),
+        }
+    }
+
+    fn fired(task: &Task, offset_minutes: i32) -> FiredReminder {
+        FiredReminder {
+            task_id: task.id,
+            due_date: task.due_date,
+            offset_minutes,
+            fired_at: now(),
+        }
+    }
+
+    #[test]
+    fn test_default_offsets() {
+        let tasks = vec![
+            create_test_task(TASK1, Duration::hours(23)),
+            create_test_task(TASK2, Duration::hours(25)),
+        ];
+
+        let reminders = due_reminders(&tasks, &[], &[], now());
+
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].task_id, TASK1);
+        assert_eq!(reminders[0].offset_minutes, 24 * 60);
+        assert_eq!(reminders[0].fire_at(), now() - Duration::hours(1));
+    }
+
+    #[test]
+    fn test_user_offsets() {
+        let tasks = vec![create_test_task(TASK1, Duration::minutes(20))];
+        let offsets = vec![offset("testuserid", 30), offset("otheruser", 10)];
+
+        let reminders = due_reminders(&tasks, &offsets, &[], now());
+
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].offset_minutes, 30);
+    }
+
+    #[test]
+    fn test_only_closest_offset() {
+        // 1日前と1時間前の両方を過ぎていても1時間前の分だけ送る
+        let tasks = vec![create_test_task(TASK1, Duration::minutes(30))];
+
+        let reminders = due_reminders(&tasks, &[], &[], now());
+
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].offset_minutes, 60);
+    }
+
+    #[test]
+    fn test_skips_fired() {
+        let tasks = vec![
+            create_test_task(TASK1, Duration::minutes(30)),
+            create_test_task(TASK2, Duration::minutes(30)),
+        ];
+
+        let fired = vec![fired(&tasks[0], 60), fired(&tasks[1], 1440)];
+
+        let reminders = due_reminders(&tasks, &[], &fired, now());
+
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].task_id, TASK2);
+        assert_eq!(reminders[0].offset_minutes, 60);
+    }
+
+    #[test]
+    fn test_due_date_changed_after_fired() {
+        // 1時間前の通知を送った後に期限を延ばし、再び1時間前になった
+        let before = create_test_task(TASK1, Duration::minutes(30));
+        let fired = vec![fired(&before, 60)];
+        let after = create_test_task(TASK1, Duration::minutes(45));
+
+        let reminders = due_reminders(&[after], &[], &fired, now());
+
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].due_date, now() + Duration::minutes(45));
+        assert_eq!(reminders[0].offset_minutes, 60);
+    }
+
+    #[test]
+    fn test_skips_closed_and_overdue() {
+        let mut done = create_test_task(TASK1, Duration::minutes(30));
+        done.status = TaskStatus::Done;
+        let overdue = create_test_task(TASK2, Duration::minutes(-5));
+
+        let reminders = due_reminders(&[done, overdue], &[], &[], now());
+
+        assert!(reminders.is_empty());
+    }
+}
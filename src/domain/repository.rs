@@ -1,2 +1,6 @@
 pub mod hello;
+pub mod reminder;
+pub mod tag;
 pub mod task;
+pub mod unit_of_work;
+pub mod user;
@@ -1,13 +1,14 @@
-use std::{future::Future, sync::Arc};
+use std::future::Future;
 
 use mockall::automock;
-use sea_orm::DatabaseConnection;
 
 use crate::{domain::hello::Hello, error::CustomError};
 
-#[automock]
+#[automock(type Connection = ();)]
 pub trait HelloRepositoryTrait {
-    fn new(conn: Arc<tokio::sync::Mutex<DatabaseConnection>>) -> Self
+    type Connection;
+
+    fn new(conn: Self::Connection) -> Self
     where
         Self: Sized;
     fn insert(&self, hello: Hello) -> impl Future<Output = Result<String, CustomError>> + Send;
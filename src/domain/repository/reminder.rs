@@ -0,0 +1,50 @@
+use std::future::Future;
+
+use crate::{
+    domain::{
+        reminder::{FiredReminder, ReminderOffset},
+        task::Task,
+    },
+    error::CustomError,
+};
+use mockall::automock;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[automock(type Connection = ();)]
+pub trait ReminderRepositoryTrait {
+    type Connection;
+
+    fn new(conn: Self::Connection) -> Self
+    where
+        Self: Sized;
+    // 期限が(from, to]の範囲にある未完了タスク
+    fn find_upcoming_tasks(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> impl Future<Output = Result<Vec<Task>, CustomError>> + Send;
+    fn find_offsets(
+        &self,
+        user_ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<ReminderOffset>, CustomError>> + Send;
+    fn replace_offsets(
+        &self,
+        user_id: String,
+        offsets: Vec<ReminderOffset>,
+    ) -> impl Future<Output = Result<(), CustomError>> + Send;
+    fn find_fired(
+        &self,
+        task_ids: Vec<Uuid>,
+    ) -> impl Future<Output = Result<Vec<FiredReminder>, CustomError>> + Send;
+    // 記録できた場合はtrue、既に記録済みの場合はfalseを返す
+    fn mark_fired(
+        &self,
+        fired: FiredReminder,
+    ) -> impl Future<Output = Result<bool, CustomError>> + Send;
+    // 送信に失敗したリマインドの記録を取り消し、次回に再送できるようにする
+    fn unmark_fired(
+        &self,
+        fired: FiredReminder,
+    ) -> impl Future<Output = Result<(), CustomError>> + Send;
+}
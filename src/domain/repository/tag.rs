@@ -0,0 +1,22 @@
+use std::future::Future;
+
+use crate::{domain::tag::Tag, error::CustomError};
+use mockall::automock;
+use uuid::Uuid;
+
+#[automock(type Connection = ();)]
+pub trait TagRepositoryTrait {
+    type Connection;
+
+    fn new(conn: Self::Connection) -> Self
+    where
+        Self: Sized;
+    fn insert(&self, tag: Tag) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn find(&self, id: Uuid) -> impl Future<Output = Result<Tag, CustomError>> + Send;
+    fn find_from_user_id(
+        &self,
+        user_id: String,
+    ) -> impl Future<Output = Result<Vec<Tag>, CustomError>> + Send;
+    fn update(&self, tag: Tag) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn delete(&self, id: Uuid) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+}
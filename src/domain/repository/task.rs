@@ -1,20 +1,128 @@
-use std::{future::Future, sync::Arc};
+use std::future::Future;
 
-use crate::{domain::task::Task, error::CustomError};
+use crate::{
+    domain::{
+        tag::Tag, task::Task, task_dependency::TaskDependency, task_history::TaskHistory,
+        task_query::TaskQuery,
+    },
+    error::CustomError,
+};
 use mockall::automock;
-use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
-#[automock]
+// 書き込みと同じトランザクションでhistoriesを記録する
+#[automock(type Connection = ();)]
 pub trait TaskRepositoryTrait {
-    fn new(conn: Arc<tokio::sync::Mutex<DatabaseConnection>>) -> Self
+    // DBの場合はコネクションプール、メモリの場合は共有するストア
+    type Connection;
+
+    fn new(conn: Self::Connection) -> Self
     where
         Self: Sized;
-    fn insert(&self, task: Task) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn insert(
+        &self,
+        task: Task,
+        histories: Vec<TaskHistory>,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
     fn find(&self, id: Uuid) -> impl Future<Output = Result<Task, CustomError>> + Send;
+    fn find_with_deleted(&self, id: Uuid)
+        -> impl Future<Output = Result<Task, CustomError>> + Send;
     fn find_from_user_id(
         &self,
         user_id: String,
     ) -> impl Future<Output = Result<Vec<Task>, CustomError>> + Send;
-    fn update(&self, task: Task) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn find_children(
+        &self,
+        parent_ids: Vec<Uuid>,
+    ) -> impl Future<Output = Result<Vec<Task>, CustomError>> + Send;
+    // 論理削除済みの子タスクも含めて、子タスクが存在するか
+    fn has_children(&self, id: Uuid) -> impl Future<Output = Result<bool, CustomError>> + Send;
+    fn search(
+        &self,
+        query: TaskQuery,
+        limit: u64,
+    ) -> impl Future<Output = Result<Vec<Task>, CustomError>> + Send;
+    fn update(
+        &self,
+        task: Task,
+        histories: Vec<TaskHistory>,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    // 完了したタスクの保存と次の回の作成を同じトランザクションで行う
+    fn update_with_next_occurrence(
+        &self,
+        task: Task,
+        next_task: Task,
+        histories: Vec<TaskHistory>,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn delete(
+        &self,
+        id: Uuid,
+        histories: Vec<TaskHistory>,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn restore(
+        &self,
+        id: Uuid,
+        histories: Vec<TaskHistory>,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    // 履歴はタスクを削除した後も残す
+    fn purge(
+        &self,
+        id: Uuid,
+        histories: Vec<TaskHistory>,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn is_shared_with(
+        &self,
+        id: Uuid,
+        user_id: String,
+    ) -> impl Future<Output = Result<bool, CustomError>> + Send;
+    // user_idに共有されているタスクのID
+    fn find_shared_task_ids(
+        &self,
+        user_id: String,
+    ) -> impl Future<Output = Result<Vec<Uuid>, CustomError>> + Send;
+    fn share(
+        &self,
+        id: Uuid,
+        user_id: String,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn unshare(
+        &self,
+        id: Uuid,
+        user_id: String,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn find_dependencies(
+        &self,
+        task_ids: Vec<Uuid>,
+    ) -> impl Future<Output = Result<Vec<TaskDependency>, CustomError>> + Send;
+    fn find_prerequisites(
+        &self,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Vec<Task>, CustomError>> + Send;
+    // 依存を追加すると循環する場合はDependencyCycleを返す
+    fn add_dependency(
+        &self,
+        id: Uuid,
+        depends_on_id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn remove_dependency(
+        &self,
+        id: Uuid,
+        depends_on_id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn find_tag(&self, tag_id: Uuid) -> impl Future<Output = Result<Tag, CustomError>> + Send;
+    fn attach_tag(
+        &self,
+        id: Uuid,
+        tag_id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn detach_tag(
+        &self,
+        id: Uuid,
+        tag_id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    // 古いものから順に返す
+    fn find_history(
+        &self,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Vec<TaskHistory>, CustomError>> + Send;
 }
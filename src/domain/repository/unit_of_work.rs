@@ -0,0 +1,130 @@
+use std::future::Future;
+
+use crate::{
+    domain::repository::{
+        reminder::{MockReminderRepositoryTrait, ReminderRepositoryTrait},
+        tag::{MockTagRepositoryTrait, TagRepositoryTrait},
+        task::{MockTaskRepositoryTrait, TaskRepositoryTrait},
+        user::{MockUserRepositoryTrait, UserRepositoryTrait},
+    },
+    error::CustomError,
+};
+use mockall::automock;
+
+// 複数のリポジトリへの書き込みを1つのトランザクションにまとめる
+#[automock(type Connection = (); type Transaction = MockTransactionScopeTrait;)]
+pub trait UnitOfWorkTrait {
+    type Connection;
+    type Transaction: TransactionScopeTrait + Send;
+
+    fn new(conn: Self::Connection) -> Self
+    where
+        Self: Sized;
+    fn begin(&self) -> impl Future<Output = Result<Self::Transaction, CustomError>> + Send;
+}
+
+// 開始したトランザクション内で読み書きするリポジトリ。
+// commitせずに破棄した場合は全ての書き込みがロールバックされる
+#[automock(
+    type Tasks = MockTaskRepositoryTrait;
+    type Users = MockUserRepositoryTrait;
+    type Tags = MockTagRepositoryTrait;
+    type Reminders = MockReminderRepositoryTrait;
+)]
+pub trait TransactionScopeTrait {
+    type Tasks: TaskRepositoryTrait + Sync;
+    type Users: UserRepositoryTrait + Sync;
+    type Tags: TagRepositoryTrait + Sync;
+    type Reminders: ReminderRepositoryTrait + Sync;
+
+    fn tasks(&self) -> &Self::Tasks;
+    fn users(&self) -> &Self::Users;
+    fn tags(&self) -> &Self::Tags;
+    fn reminders(&self) -> &Self::Reminders;
+    fn commit(self) -> impl Future<Output = Result<(), CustomError>> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        task::{test_task, Task},
+        user::User,
+    };
+    use time::OffsetDateTime;
+
+    // usecaseでの使い方: ユーザーと最初のタスクをまとめて作成する
+    async fn create_user_with_task<U: UnitOfWorkTrait>(
+        unit_of_work: &U,
+        user: User,
+        task: Task,
+    ) -> Result<(), CustomError> {
+        let txn = unit_of_work.begin().await?;
+        txn.users().insert(user).await?;
+        txn.tasks().insert(task, vec![]).await?;
+        txn.commit().await
+    }
+
+    fn mock_unit_of_work(
+        users: MockUserRepositoryTrait,
+        tasks: MockTaskRepositoryTrait,
+        commits: usize,
+    ) -> MockUnitOfWorkTrait {
+        let mut txn = MockTransactionScopeTrait::default();
+        txn.expect_users().return_const(users);
+        txn.expect_tasks().return_const(tasks);
+        txn.expect_commit()
+            .times(commits)
+            .returning(|| Box::pin(async { Ok(()) }));
+
+        let mut unit_of_work = MockUnitOfWorkTrait::default();
+        unit_of_work
+            .expect_begin()
+            .return_once(move || Box::pin(async move { Ok(txn) }));
+        unit_of_work
+    }
+
+    #[tokio::test]
+    async fn test_commit_after_all_writes() {
+        let mut users = MockUserRepositoryTrait::default();
+        users
+            .expect_insert()
+            .times(1)
+            .returning(|user| Box::pin(async move { Ok(user.id) }));
+        let mut tasks = MockTaskRepositoryTrait::default();
+        tasks
+            .expect_insert()
+            .times(1)
+            .returning(|task, _| Box::pin(async move { Ok(task.id) }));
+        let unit_of_work = mock_unit_of_work(users, tasks, 1);
+
+        let result = create_user_with_task(&unit_of_work, create_test_user(), test_task()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_no_commit_on_failure() {
+        let mut users = MockUserRepositoryTrait::default();
+        users
+            .expect_insert()
+            .returning(|user| Box::pin(async move { Ok(user.id) }));
+        let mut tasks = MockTaskRepositoryTrait::default();
+        tasks.expect_insert().returning(|_, _| {
+            Box::pin(async { Err(CustomError::Conflict("duplicate".to_string())) })
+        });
+        let unit_of_work = mock_unit_of_work(users, tasks, 0);
+
+        let result = create_user_with_task(&unit_of_work, create_test_user(), test_task()).await;
+        assert!(matches!(result, Err(CustomError::Conflict(_))));
+    }
+
+    fn create_test_user() -> User {
+        User {
+            id: "testuserid".to_string(),
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
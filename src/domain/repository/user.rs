@@ -0,0 +1,16 @@
+use std::future::Future;
+
+use crate::{domain::user::User, error::CustomError};
+use mockall::automock;
+
+#[automock(type Connection = ();)]
+pub trait UserRepositoryTrait {
+    type Connection;
+
+    fn new(conn: Self::Connection) -> Self
+    where
+        Self: Sized;
+    fn insert(&self, user: User) -> impl Future<Output = Result<String, CustomError>> + Send;
+    fn find(&self, id: String) -> impl Future<Output = Result<User, CustomError>> + Send;
+    fn update(&self, user: User) -> impl Future<Output = Result<String, CustomError>> + Send;
+}
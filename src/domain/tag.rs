@@ -0,0 +1,20 @@
+use entity::tag::Model;
+use time::OffsetDateTime;
+
+pub type Tag = Model;
+
+pub trait TagExt {
+    fn update(&self, name: Option<String>) -> Self;
+}
+
+impl TagExt for Tag {
+    fn update(&self, name: Option<String>) -> Self {
+        Self {
+            id: self.id,
+            user_id: self.user_id.clone(),
+            name: name.unwrap_or_else(|| self.name.clone()),
+            created_at: self.created_at,
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
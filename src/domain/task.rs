@@ -1,8 +1,57 @@
 use entity::task::Model;
 use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{domain::recurrence::RecurrenceRule, error::CustomError};
+
+pub use entity::task::TaskStatus;
 
 pub type Task = Model;
 
+pub const PRIORITY_MIN: i32 = 1;
+pub const PRIORITY_MAX: i32 = 5;
+pub const WEIGHT_MIN: i32 = 1;
+pub const WEIGHT_MAX: i32 = 100;
+
+// 1件分の部分更新。Noneのフィールドは変更しない
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TaskPatch {
+    pub id: Uuid,
+    // クライアントが読み込んだ時点のバージョン
+    pub version: i32,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub due_date: Option<OffsetDateTime>,
+    pub priority: Option<i32>,
+    pub weight: Option<i32>,
+}
+
+pub trait TaskStatusExt {
+    fn can_transition_to(&self, next: TaskStatus) -> bool;
+    fn is_closed(&self) -> bool;
+}
+
+impl TaskStatusExt for TaskStatus {
+    fn can_transition_to(&self, next: TaskStatus) -> bool {
+        use TaskStatus::*;
+        matches!(
+            (self, next),
+            (Todo, InProgress)
+                | (Todo, Done)
+                | (Todo, Cancelled)
+                | (InProgress, Todo)
+                | (InProgress, Done)
+                | (InProgress, Cancelled)
+                | (Done, Todo)
+                | (Cancelled, Todo)
+        )
+    }
+
+    fn is_closed(&self) -> bool {
+        matches!(self, TaskStatus::Done | TaskStatus::Cancelled)
+    }
+}
+
 pub trait TaskExt {
     fn update(
         &self,
@@ -13,6 +62,12 @@ pub trait TaskExt {
         priority: Option<i32>,
         weight: Option<i32>,
     ) -> Self;
+    fn transition(&self, next: TaskStatus) -> Result<Self, CustomError>
+    where
+        Self: Sized;
+    fn next_occurrence(&self) -> Option<Self>
+    where
+        Self: Sized;
 }
 
 impl TaskExt for Task {
@@ -35,6 +90,75 @@ impl TaskExt for Task {
             weight: weight.unwrap_or(self.weight),
             created_at: self.created_at,
             updated_at: OffsetDateTime::now_utc(),
+            deleted_at: self.deleted_at,
+            status: self.status,
+            completed_at: self.completed_at,
+            parent_id: self.parent_id,
+            recurrence: self.recurrence.clone(),
+            version: self.version,
+        }
+    }
+
+    fn transition(&self, next: TaskStatus) -> Result<Self, CustomError> {
+        if !self.status.can_transition_to(next) {
+            return Err(CustomError::InvalidStatusTransition(format!(
+                "{:?} -> {:?}",
+                self.status, next
+            )));
         }
+        let now = OffsetDateTime::now_utc();
+        Ok(Self {
+            status: next,
+            // 完了したときのみ完了日時を記録する
+            completed_at: if next == TaskStatus::Done {
+                Some(now)
+            } else {
+                None
+            },
+            updated_at: now,
+            ..self.clone()
+        })
+    }
+
+    // 繰り返しルールから次のタスクを作る。ルールは次のタスクに引き継ぐ
+    fn next_occurrence(&self) -> Option<Self> {
+        let rule: RecurrenceRule = self.recurrence.as_deref()?.parse().ok()?;
+        let (due_date, next_rule) = rule.next(self.due_date)?;
+        let now = OffsetDateTime::now_utc();
+        Some(Self {
+            id: Uuid::new_v4(),
+            due_date,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            status: TaskStatus::Todo,
+            completed_at: None,
+            recurrence: Some(next_rule.to_string()),
+            version: 1,
+            ..self.clone()
+        })
+    }
+}
+
+// テスト用のタスク。各テストでは必要なフィールドだけを上書きする
+#[cfg(test)]
+pub fn test_task() -> Task {
+    let now = OffsetDateTime::now_utc();
+    Task {
+        id: uuid::Uuid::new_v4(),
+        title: "test_title".to_string(),
+        description: "test_description".to_string(),
+        due_date: now,
+        priority: 1,
+        weight: 1,
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+        status: TaskStatus::Todo,
+        completed_at: None,
+        parent_id: None,
+        recurrence: None,
+        version: 1,
+        user_id: "testuserid".to_string(),
     }
 }
@@ -0,0 +1,140 @@
+use std::collections::{BTreeSet, HashMap};
+
+use entity::task_dependency::Model;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{domain::task::Task, error::CustomError};
+
+pub type TaskDependency = Model;
+
+// 依存先のタスクが先に来るように並べる。順序が決まらないものは期限が早い順
+pub fn topological_sort(
+    tasks: Vec<Task>,
+    dependencies: &[TaskDependency],
+) -> Result<Vec<Task>, CustomError> {
+    let mut tasks: HashMap<Uuid, Task> = tasks.into_iter().map(|t| (t.id, t)).collect();
+    let mut in_degree: HashMap<Uuid, usize> = tasks.keys().map(|id| (*id, 0)).collect();
+    let mut dependents: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    // 対象外のタスクとの依存関係は無視する
+    for dep in dependencies {
+        if tasks.contains_key(&dep.task_id) && tasks.contains_key(&dep.depends_on_id) {
+            *in_degree.entry(dep.task_id).or_default() += 1;
+            dependents
+                .entry(dep.depends_on_id)
+                .or_default()
+                .push(dep.task_id);
+        }
+    }
+
+    let mut ready: BTreeSet<(OffsetDateTime, Uuid)> = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(id, _)| (tasks[id].due_date, *id))
+        .collect();
+    let mut sorted = Vec::with_capacity(tasks.len());
+    while let Some((_, id)) = ready.pop_first() {
+        for dependent in dependents.remove(&id).unwrap_or_default() {
+            let degree = in_degree.get_mut(&dependent).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.insert((tasks[&dependent].due_date, dependent));
+            }
+        }
+        sorted.push(tasks.remove(&id).unwrap());
+    }
+
+    if !tasks.is_empty() {
+        return Err(CustomError::DependencyCycle(format!(
+            "{} tasks are in a dependency cycle",
+            tasks.len()
+        )));
+    }
+    Ok(sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::domain::task::test_task;
+
+    const TASK1: Uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+    const TASK2: Uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+    const TASK3: Uuid = uuid!("00000000-0000-0000-0000-ffff00000003");
+    const TASK4: Uuid = uuid!("00000000-0000-0000-0000-ffff00000004");
+
+    fn create_test_task(id: Uuid, due_in_days: i64) -> Task {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        Task {
+            id,
+            due_date: now + Duration::days(due_in_days),
+            created_at: now,
+            updated_at: now,
+            ..test_task()
+        }
+    }
+
+    fn dependency(task_id: Uuid, depends_on_id: Uuid) -> TaskDependency {
+        TaskDependency {
+            task_id,
+            depends_on_id,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn ids(tasks: &[Task]) -> Vec<Uuid> {
+        tasks.iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn test_topological_sort_without_dependencies() {
+        let tasks = vec![
+            create_test_task(TASK1, 3),
+            create_test_task(TASK2, 1),
+            create_test_task(TASK3, 2),
+        ];
+
+        let sorted = topological_sort(tasks, &[]).unwrap();
+
+        assert_eq!(ids(&sorted), vec![TASK2, TASK3, TASK1]);
+    }
+
+    #[test]
+    fn test_topological_sort_respects_dependencies() {
+        // TASK1 -> TASK2 -> TASK3, TASK4は独立
+        let tasks = vec![
+            create_test_task(TASK1, 1),
+            create_test_task(TASK2, 2),
+            create_test_task(TASK3, 3),
+            create_test_task(TASK4, 4),
+        ];
+        let dependencies = vec![dependency(TASK1, TASK2), dependency(TASK2, TASK3)];
+
+        let sorted = topological_sort(tasks, &dependencies).unwrap();
+
+        assert_eq!(ids(&sorted), vec![TASK3, TASK2, TASK1, TASK4]);
+    }
+
+    #[test]
+    fn test_topological_sort_ignores_unknown_tasks() {
+        let tasks = vec![create_test_task(TASK1, 1), create_test_task(TASK2, 2)];
+        let dependencies = vec![dependency(TASK1, TASK3)];
+
+        let sorted = topological_sort(tasks, &dependencies).unwrap();
+
+        assert_eq!(ids(&sorted), vec![TASK1, TASK2]);
+    }
+
+    #[test]
+    fn test_topological_sort_cycle() {
+        let tasks = vec![create_test_task(TASK1, 1), create_test_task(TASK2, 2)];
+        let dependencies = vec![dependency(TASK1, TASK2), dependency(TASK2, TASK1)];
+
+        let result = topological_sort(tasks, &dependencies);
+
+        assert!(matches!(result, Err(CustomError::DependencyCycle(_))));
+    }
+}
@@ -0,0 +1,72 @@
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::domain::task::Task;
+
+const DEFAULT_CAPACITY: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskEventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskEvent {
+    pub kind: TaskEventKind,
+    pub task: Task,
+}
+
+// タスクの共有の追加(shared = true)と解除
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskShareEvent {
+    pub task_id: Uuid,
+    pub user_id: String,
+    pub shared: bool,
+}
+
+// プロセス内でタスクの変更を配信する。購読者がいない間のイベントは捨てられる
+#[derive(Clone)]
+pub struct TaskEventBus {
+    sender: broadcast::Sender<TaskEvent>,
+    share_sender: broadcast::Sender<TaskShareEvent>,
+}
+
+impl TaskEventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        let (share_sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            share_sender,
+        }
+    }
+
+    pub fn publish(&self, kind: TaskEventKind, task: Task) {
+        // 購読者がいない場合のエラーは無視してよい
+        let _ = self.sender.send(TaskEvent { kind, task });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TaskEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish_share(&self, task_id: Uuid, user_id: String, shared: bool) {
+        let _ = self.share_sender.send(TaskShareEvent {
+            task_id,
+            user_id,
+            shared,
+        });
+    }
+
+    pub fn subscribe_shares(&self) -> broadcast::Receiver<TaskShareEvent> {
+        self.share_sender.subscribe()
+    }
+}
+
+impl Default for TaskEventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
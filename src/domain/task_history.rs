@@ -0,0 +1,177 @@
+use entity::task_history::Model;
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::domain::task::Task;
+
+pub use entity::task_history::TaskHistoryAction;
+
+pub type TaskHistory = Model;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+type FieldValue = fn(&Task) -> Option<String>;
+
+// 履歴に残すフィールド。updated_atなど変更のたびに変わるものは含めない
+const TRACKED_FIELDS: [(&str, FieldValue); 9] = [
+    ("title", |t| Some(t.title.clone())),
+    ("description", |t| Some(t.description.clone())),
+    ("due_date", |t| format_datetime(t.due_date)),
+    ("priority", |t| Some(t.priority.to_string())),
+    ("weight", |t| Some(t.weight.to_string())),
+    ("status", |t| Some(t.status.to_value())),
+    ("completed_at", |t| t.completed_at.and_then(format_datetime)),
+    ("parent_id", |t| t.parent_id.map(|id| id.to_string())),
+    ("recurrence", |t| t.recurrence.clone()),
+];
+
+fn format_datetime(datetime: OffsetDateTime) -> Option<String> {
+    datetime.format(&Rfc3339).ok()
+}
+
+// 値が変わったフィールドだけを返す。作成時はbeforeにNoneを渡す
+pub fn diff(before: Option<&Task>, after: &Task) -> Vec<FieldChange> {
+    TRACKED_FIELDS
+        .iter()
+        .filter_map(|(field, value)| {
+            let before = before.and_then(value);
+            let after = value(after);
+            (before != after).then(|| FieldChange {
+                field: field.to_string(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+pub trait TaskHistoryExt {
+    fn record(
+        task: &Task,
+        actor_id: String,
+        action: TaskHistoryAction,
+        changes: Vec<FieldChange>,
+    ) -> Self;
+    fn changes(&self) -> Vec<FieldChange>;
+}
+
+impl TaskHistoryExt for TaskHistory {
+    // 閲覧権限を履歴だけで判断できるように、タスクの所有者も記録する
+    fn record(
+        task: &Task,
+        actor_id: String,
+        action: TaskHistoryAction,
+        changes: Vec<FieldChange>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            task_id: task.id,
+            owner_id: task.user_id.clone(),
+            actor_id,
+            action,
+            changes: serde_json::to_value(changes).unwrap_or_default(),
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn changes(&self) -> Vec<FieldChange> {
+        serde_json::from_value(self.changes.clone()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::domain::task::{test_task, TaskStatus};
+
+    fn create_test_task() -> Task {
+        Task {
+            id: uuid!("00000000-0000-0000-0000-ffff00000000"),
+            due_date: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+            ..test_task()
+        }
+    }
+
+    #[test]
+    fn test_diff_created() {
+        let task = create_test_task();
+
+        let changes = diff(None, &task);
+
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "title",
+                "description",
+                "due_date",
+                "priority",
+                "weight",
+                "status"
+            ]
+        );
+        assert!(changes.iter().all(|c| c.before.is_none()));
+        assert_eq!(changes[2].after.as_deref(), Some("2023-11-14T22:13:20Z"));
+    }
+
+    #[test]
+    fn test_diff_updated() {
+        let before = create_test_task();
+        let mut after = before.clone();
+        after.priority = 3;
+        after.status = TaskStatus::Done;
+        after.updated_at = OffsetDateTime::now_utc();
+
+        let changes = diff(Some(&before), &after);
+
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange {
+                    field: "priority".to_string(),
+                    before: Some("1".to_string()),
+                    after: Some("3".to_string()),
+                },
+                FieldChange {
+                    field: "status".to_string(),
+                    before: Some("todo".to_string()),
+                    after: Some("done".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let task = create_test_task();
+
+        assert!(diff(Some(&task), &task).is_empty());
+    }
+
+    #[test]
+    fn test_record_changes_round_trip() {
+        let task = create_test_task();
+        let changes = diff(None, &task);
+
+        let history = TaskHistory::record(
+            &task,
+            "frienduser".to_string(),
+            TaskHistoryAction::Created,
+            changes.clone(),
+        );
+
+        assert_eq!(history.task_id, task.id);
+        assert_eq!(history.owner_id, "testuserid");
+        assert_eq!(history.actor_id, "frienduser");
+        assert_eq!(history.changes(), changes);
+    }
+}
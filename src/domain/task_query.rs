@@ -0,0 +1,205 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::task::{Task, TaskStatus};
+
+pub const DEFAULT_PAGE_SIZE: u64 = 100;
+pub const MAX_PAGE_SIZE: u64 = 1000;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TaskFilter {
+    pub due_date_from: Option<OffsetDateTime>,
+    pub due_date_to: Option<OffsetDateTime>,
+    pub priority_min: Option<i32>,
+    pub priority_max: Option<i32>,
+    pub statuses: Vec<TaskStatus>,
+    pub text: Option<String>,
+    // 指定された全てのタグが付いたタスクに絞り込む
+    pub tag_ids: Vec<Uuid>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TaskSortKey {
+    DueDate,
+    Priority,
+    Weight,
+    #[default]
+    CreatedAt,
+}
+
+impl TaskSortKey {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TaskSortKey::DueDate => "due_date",
+            TaskSortKey::Priority => "priority",
+            TaskSortKey::Weight => "weight",
+            TaskSortKey::CreatedAt => "created_at",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "due_date" => Some(TaskSortKey::DueDate),
+            "priority" => Some(TaskSortKey::Priority),
+            "weight" => Some(TaskSortKey::Weight),
+            "created_at" => Some(TaskSortKey::CreatedAt),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorValue {
+    DateTime(OffsetDateTime),
+    Int(i32),
+}
+
+// 直前のページの最後のタスクを指すキーセットページング用のカーソル
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskCursor {
+    pub sort_key: TaskSortKey,
+    pub order: SortOrder,
+    pub value: CursorValue,
+    pub id: Uuid,
+}
+
+impl TaskCursor {
+    pub fn from_task(task: &Task, sort_key: TaskSortKey, order: SortOrder) -> Self {
+        let value = match sort_key {
+            TaskSortKey::DueDate => CursorValue::DateTime(task.due_date),
+            TaskSortKey::Priority => CursorValue::Int(task.priority),
+            TaskSortKey::Weight => CursorValue::Int(task.weight),
+            TaskSortKey::CreatedAt => CursorValue::DateTime(task.created_at),
+        };
+        Self {
+            sort_key,
+            order,
+            value,
+            id: task.id,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let order = match self.order {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        };
+        let value = match self.value {
+            CursorValue::DateTime(datetime) => datetime.unix_timestamp_nanos().to_string(),
+            CursorValue::Int(value) => value.to_string(),
+        };
+        let raw = format!("{}|{}|{}|{}", self.sort_key.as_str(), order, value, self.id);
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(token: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(token).ok()?).ok()?;
+        let mut parts = raw.split('|');
+        let sort_key = TaskSortKey::parse(parts.next()?)?;
+        let order = match parts.next()? {
+            "asc" => SortOrder::Asc,
+            "desc" => SortOrder::Desc,
+            _ => return None,
+        };
+        let value = parts.next()?;
+        let value = match sort_key {
+            TaskSortKey::DueDate | TaskSortKey::CreatedAt => CursorValue::DateTime(
+                OffsetDateTime::from_unix_timestamp_nanos(value.parse().ok()?).ok()?,
+            ),
+            TaskSortKey::Priority | TaskSortKey::Weight => CursorValue::Int(value.parse().ok()?),
+        };
+        let id = Uuid::parse_str(parts.next()?).ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            sort_key,
+            order,
+            value,
+            id,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskQuery {
+    pub user_id: String,
+    pub filter: TaskFilter,
+    pub sort_key: TaskSortKey,
+    pub order: SortOrder,
+    pub page_size: u64,
+    pub cursor: Option<TaskCursor>,
+}
+
+impl TaskQuery {
+    pub fn new(user_id: String) -> Self {
+        Self {
+            user_id,
+            filter: TaskFilter::default(),
+            sort_key: TaskSortKey::default(),
+            order: SortOrder::default(),
+            page_size: DEFAULT_PAGE_SIZE,
+            cursor: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskPage {
+    pub tasks: Vec<Task>,
+    pub next_cursor: Option<TaskCursor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursors = [
+            TaskCursor {
+                sort_key: TaskSortKey::DueDate,
+                order: SortOrder::Asc,
+                value: CursorValue::DateTime(
+                    OffsetDateTime::from_unix_timestamp_nanos(1_728_000_000_123_456_000).unwrap(),
+                ),
+                id: uuid!("00000000-0000-0000-0000-ffff00000000"),
+            },
+            TaskCursor {
+                sort_key: TaskSortKey::Weight,
+                order: SortOrder::Desc,
+                value: CursorValue::Int(-3),
+                id: uuid!("00000000-0000-0000-0000-ffff00000001"),
+            },
+        ];
+        for cursor in cursors {
+            assert_eq!(TaskCursor::decode(&cursor.encode()), Some(cursor));
+        }
+    }
+
+    #[test]
+    fn test_cursor_decode_invalid() {
+        assert_eq!(TaskCursor::decode("not a cursor"), None);
+        assert_eq!(
+            TaskCursor::decode(&URL_SAFE_NO_PAD.encode("weight|asc|x|y")),
+            None
+        );
+        assert_eq!(
+            TaskCursor::decode(
+                &URL_SAFE_NO_PAD
+                    .encode("priority|asc|1|00000000-0000-0000-0000-ffff00000000|extra")
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::domain::task::{Task, TaskStatus};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskNode {
+    pub task: Task,
+    pub children: Vec<TaskNode>,
+    // 子タスクがある場合は子の合計、無い場合は自身の重み
+    pub total_weight: i64,
+    pub completed_weight: i64,
+}
+
+impl TaskNode {
+    // rootとその子孫から木を組み立て、重みと進捗を集計する
+    pub fn build(root: Task, descendants: Vec<Task>) -> Self {
+        let mut children_of: HashMap<Uuid, Vec<Task>> = HashMap::new();
+        for task in descendants {
+            if let Some(parent_id) = task.parent_id {
+                children_of.entry(parent_id).or_default().push(task);
+            }
+        }
+        Self::build_node(root, &mut children_of)
+    }
+
+    fn build_node(task: Task, children_of: &mut HashMap<Uuid, Vec<Task>>) -> Self {
+        let children: Vec<TaskNode> = children_of
+            .remove(&task.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::build_node(child, children_of))
+            .collect();
+        let (total_weight, completed_weight) = rollup(&task, &children);
+        Self {
+            task,
+            children,
+            total_weight,
+            completed_weight,
+        }
+    }
+
+    pub fn progress(&self) -> f64 {
+        if self.total_weight == 0 {
+            return 0.0;
+        }
+        self.completed_weight as f64 / self.total_weight as f64
+    }
+}
+
+fn rollup(task: &Task, children: &[TaskNode]) -> (i64, i64) {
+    // キャンセルされたタスクは集計に含めない
+    if task.status == TaskStatus::Cancelled {
+        return (0, 0);
+    }
+    let total: i64 = children.iter().map(|c| c.total_weight).sum();
+    if total == 0 {
+        let weight = task.weight as i64;
+        let completed = if task.status == TaskStatus::Done {
+            weight
+        } else {
+            0
+        };
+        return (weight, completed);
+    }
+    // 親タスク自体が完了していれば配下も全て完了扱いにする
+    let completed = if task.status == TaskStatus::Done {
+        total
+    } else {
+        children.iter().map(|c| c.completed_weight).sum()
+    };
+    (total, completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::domain::task::test_task;
+
+    fn create_test_task(id: Uuid, parent_id: Option<Uuid>, weight: i32) -> Task {
+        Task {
+            id,
+            weight,
+            parent_id,
+            ..test_task()
+        }
+    }
+
+    const ROOT: Uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+    const CHILD1: Uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+    const CHILD2: Uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+    const GRANDCHILD: Uuid = uuid!("00000000-0000-0000-0000-ffff00000003");
+
+    #[test]
+    fn test_leaf_progress() {
+        let mut task = create_test_task(ROOT, None, 3);
+        let node = TaskNode::build(task.clone(), vec![]);
+        assert_eq!((node.total_weight, node.completed_weight), (3, 0));
+        assert_eq!(node.progress(), 0.0);
+
+        task.status = TaskStatus::Done;
+        let node = TaskNode::build(task, vec![]);
+        assert_eq!((node.total_weight, node.completed_weight), (3, 3));
+        assert_eq!(node.progress(), 1.0);
+    }
+
+    #[test]
+    fn test_rollup_nested() {
+        let root = create_test_task(ROOT, None, 1);
+        let mut child1 = create_test_task(CHILD1, Some(ROOT), 2);
+        child1.status = TaskStatus::Done;
+        let child2 = create_test_task(CHILD2, Some(ROOT), 1);
+        let mut grandchild = create_test_task(GRANDCHILD, Some(CHILD2), 2);
+        grandchild.status = TaskStatus::Done;
+
+        let node = TaskNode::build(root, vec![child1, child2, grandchild]);
+
+        // root = child1(2, 完了) + child2(子のgrandchild 2, 完了)
+        assert_eq!(node.children.len(), 2);
+        assert_eq!(node.children[1].children.len(), 1);
+        assert_eq!((node.total_weight, node.completed_weight), (4, 4));
+        assert_eq!(node.children[1].total_weight, 2);
+        assert_eq!(node.progress(), 1.0);
+    }
+
+    #[test]
+    fn test_rollup_partial_progress() {
+        let root = create_test_task(ROOT, None, 1);
+        let mut child1 = create_test_task(CHILD1, Some(ROOT), 1);
+        child1.status = TaskStatus::Done;
+        let child2 = create_test_task(CHILD2, Some(ROOT), 3);
+
+        let node = TaskNode::build(root, vec![child1, child2]);
+
+        assert_eq!((node.total_weight, node.completed_weight), (4, 1));
+        assert_eq!(node.progress(), 0.25);
+    }
+
+    #[test]
+    fn test_rollup_ignores_cancelled() {
+        let root = create_test_task(ROOT, None, 1);
+        let mut child1 = create_test_task(CHILD1, Some(ROOT), 5);
+        child1.status = TaskStatus::Cancelled;
+        let mut child2 = create_test_task(CHILD2, Some(ROOT), 2);
+        child2.status = TaskStatus::Done;
+
+        let node = TaskNode::build(root, vec![child1, child2]);
+
+        assert_eq!((node.total_weight, node.completed_weight), (2, 2));
+    }
+
+    #[test]
+    fn test_rollup_done_parent() {
+        let mut root = create_test_task(ROOT, None, 1);
+        root.status = TaskStatus::Done;
+        let child1 = create_test_task(CHILD1, Some(ROOT), 2);
+
+        let node = TaskNode::build(root, vec![child1]);
+
+        assert_eq!((node.total_weight, node.completed_weight), (2, 2));
+    }
+}
@@ -0,0 +1,209 @@
+use time::OffsetDateTime;
+
+use crate::domain::task::{Task, TaskStatusExt, PRIORITY_MAX, PRIORITY_MIN, WEIGHT_MAX};
+
+// 作成からこの日数が経つと経過日数の要素が0.5になる
+const AGE_HALF_DAYS: f64 = 7.0;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+// 各要素の重み。合計で割って正規化するため、比率だけが意味を持つ
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UrgencyWeights {
+    pub priority: f64,
+    pub effort: f64,
+    pub due_date: f64,
+    pub age: f64,
+}
+
+impl Default for UrgencyWeights {
+    fn default() -> Self {
+        Self {
+            priority: 0.35,
+            effort: 0.1,
+            due_date: 0.45,
+            age: 0.1,
+        }
+    }
+}
+
+impl UrgencyWeights {
+    fn total(&self) -> f64 {
+        self.priority + self.effort + self.due_date + self.age
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoredTask {
+    pub task: Task,
+    pub score: f64,
+}
+
+// 0.0から1.0の緊急度。各要素を0.0から1.0に正規化してから重み付き平均を取る
+pub fn urgency_score(task: &Task, now: OffsetDateTime, weights: &UrgencyWeights) -> f64 {
+    let total = weights.total();
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let priority = ((task.priority as f64 - PRIORITY_MIN as f64)
+        / (PRIORITY_MAX - PRIORITY_MIN) as f64)
+        .clamp(0.0, 1.0);
+    let effort = (task.weight as f64 / WEIGHT_MAX as f64).clamp(0.0, 1.0);
+    // 期限切れは1.0、期限まで1日で0.5、遠いほど0.0に近づく
+    let days_until_due = (task.due_date - now).as_seconds_f64() / SECONDS_PER_DAY;
+    let due_date = 1.0 / (1.0 + days_until_due.max(0.0));
+    let days_since_created = ((now - task.created_at).as_seconds_f64() / SECONDS_PER_DAY).max(0.0);
+    let age = days_since_created / (days_since_created + AGE_HALF_DAYS);
+
+    (weights.priority * priority
+        + weights.effort * effort
+        + weights.due_date * due_date
+        + weights.age * age)
+        / total
+}
+
+// 未完了のタスクを緊急度の高い順に最大limit件返す。同点の場合は期限が早い順
+pub fn rank(
+    tasks: Vec<Task>,
+    now: OffsetDateTime,
+    weights: &UrgencyWeights,
+    limit: usize,
+) -> Vec<ScoredTask> {
+    let mut scored: Vec<ScoredTask> = tasks
+        .into_iter()
+        .filter(|t| !t.status.is_closed())
+        .map(|task| ScoredTask {
+            score: urgency_score(&task, now, weights),
+            task,
+        })
+        .collect();
+    scored.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.task.due_date.cmp(&b.task.due_date))
+            .then(a.task.id.cmp(&b.task.id))
+    });
+    scored.truncate(limit);
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+    use uuid::{uuid, Uuid};
+
+    use super::*;
+    use crate::domain::task::{test_task, TaskStatus};
+
+    const TASK1: Uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+    const TASK2: Uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+    const TASK3: Uuid = uuid!("00000000-0000-0000-0000-ffff00000003");
+
+    fn now() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap()
+    }
+
+    fn create_test_task(id: Uuid, priority: i32, weight: i32, due_in_days: i64) -> Task {
+        Task {
+            id,
+            due_date: now() + Duration::days(due_in_days),
+            priority,
+            weight,
+            created_at: now(),
+            updated_at: now(),
+            ..test_task()
+        }
+    }
+
+    fn only(weights: fn(&mut UrgencyWeights)) -> UrgencyWeights {
+        let mut w = UrgencyWeights {
+            priority: 0.0,
+            effort: 0.0,
+            due_date: 0.0,
+            age: 0.0,
+        };
+        weights(&mut w);
+        w
+    }
+
+    #[test]
+    fn test_score_components() {
+        let task = create_test_task(TASK1, 5, 50, 1);
+
+        assert_eq!(
+            urgency_score(&task, now(), &only(|w| w.priority = 1.0)),
+            1.0
+        );
+        assert_eq!(urgency_score(&task, now(), &only(|w| w.effort = 1.0)), 0.5);
+        assert_eq!(
+            urgency_score(&task, now(), &only(|w| w.due_date = 1.0)),
+            0.5
+        );
+        assert_eq!(urgency_score(&task, now(), &only(|w| w.age = 1.0)), 0.0);
+        assert_eq!(
+            urgency_score(&task, now() + Duration::days(7), &only(|w| w.age = 1.0)),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_score_overdue() {
+        let task = create_test_task(TASK1, 1, 1, -3);
+
+        assert_eq!(
+            urgency_score(&task, now(), &only(|w| w.due_date = 1.0)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_score_zero_weights() {
+        let task = create_test_task(TASK1, 5, 100, 0);
+
+        assert_eq!(urgency_score(&task, now(), &only(|_| {})), 0.0);
+    }
+
+    #[test]
+    fn test_score_is_normalized() {
+        let task = create_test_task(TASK1, 5, 100, -1);
+        let weights = UrgencyWeights {
+            priority: 2.0,
+            effort: 2.0,
+            due_date: 2.0,
+            age: 0.0,
+        };
+
+        assert_eq!(urgency_score(&task, now(), &weights), 1.0);
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut done = create_test_task(TASK3, 5, 100, -1);
+        done.status = TaskStatus::Done;
+        let tasks = vec![
+            create_test_task(TASK1, 1, 1, 30),
+            create_test_task(TASK2, 5, 1, 1),
+            done,
+        ];
+
+        let ranked = rank(tasks, now(), &UrgencyWeights::default(), 10);
+
+        let ids: Vec<Uuid> = ranked.iter().map(|s| s.task.id).collect();
+        assert_eq!(ids, vec![TASK2, TASK1]);
+        assert!(ranked[0].score > ranked[1].score);
+    }
+
+    #[test]
+    fn test_rank_ties_and_limit() {
+        let tasks = vec![
+            create_test_task(TASK2, 3, 10, 2),
+            create_test_task(TASK1, 3, 10, 2),
+            create_test_task(TASK3, 3, 10, 2),
+        ];
+
+        let ranked = rank(tasks, now(), &only(|w| w.priority = 1.0), 2);
+
+        let ids: Vec<Uuid> = ranked.iter().map(|s| s.task.id).collect();
+        assert_eq!(ids, vec![TASK1, TASK2]);
+    }
+}
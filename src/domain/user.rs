@@ -0,0 +1,20 @@
+use entity::user::Model;
+use time::OffsetDateTime;
+
+pub type User = Model;
+
+pub trait UserExt {
+    fn update(&self, username: Option<String>, email: Option<String>) -> Self;
+}
+
+impl UserExt for User {
+    fn update(&self, username: Option<String>, email: Option<String>) -> Self {
+        Self {
+            id: self.id.clone(),
+            username: username.unwrap_or_else(|| self.username.clone()),
+            email: email.unwrap_or_else(|| self.email.clone()),
+            created_at: self.created_at,
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -1,23 +1,449 @@
-use sea_orm::DbErr;
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use prost::Message;
+use sea_orm::{DbErr, RuntimeErr, SqlErr};
 use thiserror::Error;
-use tonic::Status;
+use tonic::{Code, Status};
+
+const ERROR_DOMAIN: &str = "gakusai2024-backend";
+const ERROR_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+const BAD_REQUEST_TYPE_URL: &str = "type.googleapis.com/google.rpc.BadRequest";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldViolation {
+    pub field: String,
+    pub description: String,
+}
+
+impl FieldViolation {
+    pub fn new(field: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            description: description.into(),
+        }
+    }
+}
+
+// 一括処理で失敗した要素。indexはリクエスト内の位置
+#[derive(Debug)]
+pub struct BatchItemError {
+    pub index: usize,
+    pub error: CustomError,
+}
+
+impl BatchItemError {
+    pub fn new(index: usize, error: CustomError) -> Self {
+        Self { index, error }
+    }
+}
 
 #[derive(Error, Debug)]
 pub enum CustomError {
     #[error("Db error: {0}")]
     Db(#[from] DbErr),
     #[error("record not found: {0}")]
-    DbNotFound(String),
-    #[error("Mutex error")]
-    MutexError,
+    NotFound(String),
+    #[error("invalid argument: {}", format_violations(.0))]
+    InvalidArgument(Vec<FieldViolation>),
+    #[error("conflict: {0}")]
+    Conflict(String),
+    #[error("foreign key violation: {0}")]
+    ForeignKeyViolation(String),
+    #[error("unauthenticated: {0}")]
+    Unauthenticated(String),
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("unavailable: {0}")]
+    Unavailable(String),
+    #[error("invalid status transition: {0}")]
+    InvalidStatusTransition(String),
+    #[error("task has subtasks: {0}")]
+    HasSubtasks(String),
+    #[error("dependency cycle: {0}")]
+    DependencyCycle(String),
+    #[error("prerequisites not completed: {0}")]
+    PrerequisitesNotCompleted(String),
+    #[error("version mismatch: {0}")]
+    VersionMismatch(String),
+    #[error("transaction conflict: {0}")]
+    TransactionConflict(String),
+    #[error("batch failed: {}", format_batch_errors(.0))]
+    BatchFailed(Vec<BatchItemError>),
+}
+
+impl CustomError {
+    pub fn invalid_argument(field: impl Into<String>, description: impl Into<String>) -> Self {
+        Self::InvalidArgument(vec![FieldViolation::new(field, description)])
+    }
+
+    // gRPCのステータスコードとErrorInfoのreasonを決める
+    fn classify(&self) -> (Code, &'static str) {
+        match self {
+            CustomError::Db(err) => classify_db_err(err),
+            CustomError::NotFound(_) => (Code::NotFound, "NOT_FOUND"),
+            CustomError::InvalidArgument(_) => (Code::InvalidArgument, "INVALID_ARGUMENT"),
+            CustomError::Conflict(_) => (Code::AlreadyExists, "CONFLICT"),
+            // DBの外部キー制約違反と同じ扱いにする
+            CustomError::ForeignKeyViolation(_) => {
+                (Code::FailedPrecondition, "FOREIGN_KEY_VIOLATION")
+            }
+            CustomError::Unauthenticated(_) => (Code::Unauthenticated, "UNAUTHENTICATED"),
+            CustomError::PermissionDenied(_) => (Code::PermissionDenied, "PERMISSION_DENIED"),
+            CustomError::Unavailable(_) => (Code::Unavailable, "UNAVAILABLE"),
+            CustomError::InvalidStatusTransition(_) => {
+                (Code::FailedPrecondition, "INVALID_STATUS_TRANSITION")
+            }
+            CustomError::HasSubtasks(_) => (Code::FailedPrecondition, "HAS_SUBTASKS"),
+            CustomError::DependencyCycle(_) => (Code::FailedPrecondition, "DEPENDENCY_CYCLE"),
+            CustomError::PrerequisitesNotCompleted(_) => {
+                (Code::FailedPrecondition, "PREREQUISITES_NOT_COMPLETED")
+            }
+            // 他のクライアントが先に更新した。最新を取得してやり直してもらう
+            CustomError::VersionMismatch(_) => (Code::Aborted, "VERSION_MISMATCH"),
+            // 同時に実行されたトランザクションと競合した。そのままやり直してもらう
+            CustomError::TransactionConflict(_) => (Code::Aborted, "TRANSACTION_CONFLICT"),
+            // ステータスコードは最初に失敗した要素のものを使う
+            CustomError::BatchFailed(items) => (
+                items
+                    .first()
+                    .map_or(Code::Aborted, |item| item.error.classify().0),
+                "BATCH_FAILED",
+            ),
+        }
+    }
+
+    // 一括処理では失敗した要素の位置ごとにreasonを返す
+    fn metadata(&self) -> HashMap<String, String> {
+        match self {
+            CustomError::BatchFailed(items) => items
+                .iter()
+                .map(|item| (item.index.to_string(), item.error.classify().1.to_string()))
+                .collect(),
+            _ => HashMap::new(),
+        }
+    }
+}
+
+fn classify_db_err(err: &DbErr) -> (Code, &'static str) {
+    match err {
+        DbErr::RecordNotFound(_) | DbErr::RecordNotUpdated => (Code::NotFound, "NOT_FOUND"),
+        DbErr::ConnectionAcquire(_) | DbErr::Conn(_) => (Code::Unavailable, "UNAVAILABLE"),
+        DbErr::Exec(RuntimeErr::SqlxError(e)) | DbErr::Query(RuntimeErr::SqlxError(e))
+            if is_serialization_failure(e) =>
+        {
+            (Code::Aborted, "TRANSACTION_CONFLICT")
+        }
+        _ => match err.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => (Code::AlreadyExists, "CONFLICT"),
+            Some(SqlErr::ForeignKeyConstraintViolation(_)) => {
+                (Code::FailedPrecondition, "FOREIGN_KEY_VIOLATION")
+            }
+            _ => (Code::Internal, "DATABASE_ERROR"),
+        },
+    }
+}
+
+// DbErrの文字列にはSQLやテーブル名が含まれるため、クライアントには固定の文言を返す
+fn db_err_message(reason: &str) -> &'static str {
+    match reason {
+        "NOT_FOUND" => "record not found",
+        "UNAVAILABLE" => "database unavailable",
+        "CONFLICT" => "record already exists",
+        "FOREIGN_KEY_VIOLATION" => "referenced record does not exist",
+        "TRANSACTION_CONFLICT" => "transaction conflicted, retry the request",
+        _ => "internal database error",
+    }
+}
+
+// トランザクションが競合した(SQLSTATE 40001)か、行ロックがデッドロックした(SQLSTATE 40P01)
+fn is_serialization_failure(err: &sea_orm::SqlxError) -> bool {
+    err.as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "40001" || code == "40P01")
+}
+
+fn format_violations(violations: &[FieldViolation]) -> String {
+    violations
+        .iter()
+        .map(|v| format!("{}: {}", v.field, v.description))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_batch_errors(items: &[BatchItemError]) -> String {
+    items
+        .iter()
+        .map(|item| format!("[{}] {}", item.index, item.error))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn pack<M: Message>(type_url: &str, message: &M) -> prost_types::Any {
+    prost_types::Any {
+        type_url: type_url.to_string(),
+        value: message.encode_to_vec(),
+    }
 }
 
 impl From<CustomError> for Status {
     fn from(val: CustomError) -> Self {
-        match val {
-            CustomError::Db(err) => Status::internal(err.to_string()),
-            CustomError::DbNotFound(err) => Status::internal(err),
-            CustomError::MutexError => Status::internal("Mutex error".to_string()),
+        let (code, reason) = val.classify();
+        let message = match &val {
+            CustomError::Db(err) => {
+                log::error!("database error ({}): {}", reason, err);
+                db_err_message(reason).to_string()
+            }
+            _ => val.to_string(),
+        };
+
+        // google.rpc.Statusとしてエラー詳細をgrpc-status-details-binに載せる
+        let mut details = vec![pack(
+            ERROR_INFO_TYPE_URL,
+            &rpc::ErrorInfo {
+                reason: reason.to_string(),
+                domain: ERROR_DOMAIN.to_string(),
+                metadata: val.metadata(),
+            },
+        )];
+        if let CustomError::InvalidArgument(violations) = val {
+            details.push(pack(
+                BAD_REQUEST_TYPE_URL,
+                &rpc::BadRequest {
+                    field_violations: violations
+                        .into_iter()
+                        .map(|v| rpc::bad_request::FieldViolation {
+                            field: v.field,
+                            description: v.description,
+                        })
+                        .collect(),
+                },
+            ));
+        }
+        let status = rpc::Status {
+            code: code as i32,
+            message: message.clone(),
+            details,
+        };
+
+        Status::with_details(code, message, Bytes::from(status.encode_to_vec()))
+    }
+}
+
+// google/rpc/status.proto, google/rpc/error_details.proto のうち使用するメッセージ
+pub mod rpc {
+    use std::collections::HashMap;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Status {
+        #[prost(int32, tag = "1")]
+        pub code: i32,
+        #[prost(string, tag = "2")]
+        pub message: String,
+        #[prost(message, repeated, tag = "3")]
+        pub details: Vec<prost_types::Any>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ErrorInfo {
+        #[prost(string, tag = "1")]
+        pub reason: String,
+        #[prost(string, tag = "2")]
+        pub domain: String,
+        #[prost(map = "string, string", tag = "3")]
+        pub metadata: HashMap<String, String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BadRequest {
+        #[prost(message, repeated, tag = "1")]
+        pub field_violations: Vec<bad_request::FieldViolation>,
+    }
+
+    pub mod bad_request {
+        #[derive(Clone, PartialEq, prost::Message)]
+        pub struct FieldViolation {
+            #[prost(string, tag = "1")]
+            pub field: String,
+            #[prost(string, tag = "2")]
+            pub description: String,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_details(status: &Status) -> rpc::Status {
+        rpc::Status::decode(status.details()).unwrap()
+    }
+
+    fn error_info(details: &rpc::Status) -> rpc::ErrorInfo {
+        let any = details
+            .details
+            .iter()
+            .find(|d| d.type_url == ERROR_INFO_TYPE_URL)
+            .unwrap();
+        rpc::ErrorInfo::decode(any.value.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_status_codes() {
+        let cases = vec![
+            (CustomError::NotFound("key".to_string()), Code::NotFound),
+            (
+                CustomError::invalid_argument("title", "required"),
+                Code::InvalidArgument,
+            ),
+            (
+                CustomError::Conflict("dup".to_string()),
+                Code::AlreadyExists,
+            ),
+            (
+                CustomError::ForeignKeyViolation("user: unknown".to_string()),
+                Code::FailedPrecondition,
+            ),
+            (
+                CustomError::Unauthenticated("token".to_string()),
+                Code::Unauthenticated,
+            ),
+            (
+                CustomError::PermissionDenied("owner".to_string()),
+                Code::PermissionDenied,
+            ),
+            (
+                CustomError::Unavailable("db".to_string()),
+                Code::Unavailable,
+            ),
+            (
+                CustomError::InvalidStatusTransition("done -> done".to_string()),
+                Code::FailedPrecondition,
+            ),
+            (
+                CustomError::DependencyCycle("a -> b -> a".to_string()),
+                Code::FailedPrecondition,
+            ),
+            (
+                CustomError::PrerequisitesNotCompleted("a".to_string()),
+                Code::FailedPrecondition,
+            ),
+            (
+                CustomError::VersionMismatch("expected 1, found 2".to_string()),
+                Code::Aborted,
+            ),
+            (
+                CustomError::TransactionConflict("concurrent write".to_string()),
+                Code::Aborted,
+            ),
+            (
+                CustomError::BatchFailed(vec![BatchItemError::new(
+                    1,
+                    CustomError::PermissionDenied("owner".to_string()),
+                )]),
+                Code::PermissionDenied,
+            ),
+            (
+                CustomError::Db(DbErr::RecordNotFound("key".to_string())),
+                Code::NotFound,
+            ),
+            (CustomError::Db(DbErr::RecordNotUpdated), Code::NotFound),
+            (
+                CustomError::Db(DbErr::Custom("boom".to_string())),
+                Code::Internal,
+            ),
+        ];
+
+        for (err, code) in cases {
+            let status: Status = err.into();
+            assert_eq!(status.code(), code);
         }
     }
+
+    #[test]
+    fn test_status_error_info() {
+        let status: Status = CustomError::NotFound("key: 1".to_string()).into();
+        let details = decode_details(&status);
+
+        assert_eq!(details.code, Code::NotFound as i32);
+        assert_eq!(details.message, "record not found: key: 1");
+        let info = error_info(&details);
+        assert_eq!(info.reason, "NOT_FOUND");
+        assert_eq!(info.domain, ERROR_DOMAIN);
+    }
+
+    #[test]
+    fn test_status_hides_db_error() {
+        let status: Status = CustomError::Db(DbErr::Custom(
+            "relation \"tasks\" does not exist".to_string(),
+        ))
+        .into();
+        assert_eq!(status.code(), Code::Internal);
+        assert_eq!(status.message(), "internal database error");
+        let details = decode_details(&status);
+        assert_eq!(details.message, "internal database error");
+        assert_eq!(error_info(&details).reason, "DATABASE_ERROR");
+
+        let status: Status =
+            CustomError::Db(DbErr::RecordNotFound("tasks.id = 1".to_string())).into();
+        assert_eq!(status.message(), "record not found");
+    }
+
+    #[test]
+    fn test_status_field_violations() {
+        let status: Status = CustomError::InvalidArgument(vec![
+            FieldViolation::new("title", "must not be empty"),
+            FieldViolation::new("priority", "must be positive"),
+        ])
+        .into();
+        assert_eq!(
+            status.message(),
+            "invalid argument: title: must not be empty, priority: must be positive"
+        );
+
+        let details = decode_details(&status);
+        let any = details
+            .details
+            .iter()
+            .find(|d| d.type_url == BAD_REQUEST_TYPE_URL)
+            .unwrap();
+        let bad_request = rpc::BadRequest::decode(any.value.as_slice()).unwrap();
+        assert_eq!(
+            bad_request.field_violations,
+            vec![
+                rpc::bad_request::FieldViolation {
+                    field: "title".to_string(),
+                    description: "must not be empty".to_string(),
+                },
+                rpc::bad_request::FieldViolation {
+                    field: "priority".to_string(),
+                    description: "must be positive".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_status_batch_failed() {
+        let status: Status = CustomError::BatchFailed(vec![
+            BatchItemError::new(0, CustomError::NotFound("key: 1".to_string())),
+            BatchItemError::new(2, CustomError::VersionMismatch("key: 2".to_string())),
+        ])
+        .into();
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(
+            status.message(),
+            "batch failed: [0] record not found: key: 1, [2] version mismatch: key: 2"
+        );
+
+        let info = error_info(&decode_details(&status));
+        assert_eq!(info.reason, "BATCH_FAILED");
+        assert_eq!(
+            info.metadata,
+            HashMap::from([
+                ("0".to_string(), "NOT_FOUND".to_string()),
+                ("2".to_string(), "VERSION_MISMATCH".to_string()),
+            ])
+        );
+    }
 }
@@ -1 +1,54 @@
+use std::env;
+
 pub mod db;
+pub mod memory;
+pub mod notifier;
+
+// リポジトリの保存先。REPOSITORY_BACKENDが未設定の場合はDATABASE_URLのDBを使う
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RepositoryBackend {
+    // PostgreSQL、またはsqlite featureを有効にした場合のSQLite
+    #[default]
+    Database,
+    // 再起動するとデータは消える。DB無しでの動作確認やテスト用
+    Memory,
+}
+
+impl RepositoryBackend {
+    pub fn from_env() -> Self {
+        match env::var("REPOSITORY_BACKEND") {
+            Ok(value) => Self::parse(&value)
+                .unwrap_or_else(|| panic!("unknown REPOSITORY_BACKEND: {}", value)),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            // postgres/sqliteも同じくDATABASE_URLのDBを使う。postgresは以前の設定との互換のため
+            "database" | "postgres" | "sqlite" => Some(RepositoryBackend::Database),
+            "memory" => Some(RepositoryBackend::Memory),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for value in ["database", "postgres", "sqlite"] {
+            assert_eq!(
+                RepositoryBackend::parse(value),
+                Some(RepositoryBackend::Database)
+            );
+        }
+        assert_eq!(
+            RepositoryBackend::parse("memory"),
+            Some(RepositoryBackend::Memory)
+        );
+        assert_eq!(RepositoryBackend::parse("mysql"), None);
+    }
+}
@@ -1,20 +1,215 @@
-use std::sync::Arc;
+use std::{env, sync::Arc, time::Duration};
 
-use sea_orm::DatabaseConnection;
-use tokio::sync::Mutex;
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseBackend, DatabaseConnection,
+    DatabaseTransaction, DbErr, ExecResult, QueryResult, Statement, TransactionTrait,
+};
 
 pub mod hello;
+pub mod migrate;
+pub mod reminder;
+pub mod tag;
 pub mod task;
+pub mod unit_of_work;
+pub mod user;
 
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+const DEFAULT_MIN_CONNECTIONS: u32 = 1;
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 8;
+const DEFAULT_ACQUIRE_TIMEOUT_SECS: u64 = 8;
+const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 600;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DbConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout: Duration,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Duration,
+    // 起動時に未適用のマイグレーションを適用する
+    pub auto_migrate: bool,
+}
+
+impl DbConfig {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            min_connections: DEFAULT_MIN_CONNECTIONS,
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            acquire_timeout: Duration::from_secs(DEFAULT_ACQUIRE_TIMEOUT_SECS),
+            idle_timeout: Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS),
+            auto_migrate: false,
+        }
+    }
+
+    // DATABASE_URL以外は未設定ならデフォルト値を使う
+    pub fn from_env() -> Self {
+        let url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let default = Self::new(url);
+        Self {
+            max_connections: env_or("DATABASE_MAX_CONNECTIONS", default.max_connections),
+            min_connections: env_or("DATABASE_MIN_CONNECTIONS", default.min_connections),
+            connect_timeout: Duration::from_secs(env_or(
+                "DATABASE_CONNECT_TIMEOUT_SECS",
+                DEFAULT_CONNECT_TIMEOUT_SECS,
+            )),
+            acquire_timeout: Duration::from_secs(env_or(
+                "DATABASE_ACQUIRE_TIMEOUT_SECS",
+                DEFAULT_ACQUIRE_TIMEOUT_SECS,
+            )),
+            idle_timeout: Duration::from_secs(env_or(
+                "DATABASE_IDLE_TIMEOUT_SECS",
+                DEFAULT_IDLE_TIMEOUT_SECS,
+            )),
+            auto_migrate: env_bool_or("DATABASE_AUTO_MIGRATE", default.auto_migrate),
+            ..default
+        }
+    }
+
+    pub async fn connect(&self) -> Result<Arc<DatabaseConnection>, DbErr> {
+        let mut options = ConnectOptions::new(self.url.clone());
+        options
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .connect_timeout(self.connect_timeout)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout);
+        // SQLiteのインメモリDBは接続ごとに別のDBになるため、1つの接続だけを使う
+        if self.is_sqlite_memory() {
+            options.max_connections(1).min_connections(1);
+        }
+        Ok(Arc::new(Database::connect(options).await?))
+    }
+
+    fn is_sqlite_memory(&self) -> bool {
+        self.url.starts_with("sqlite::memory:") || self.url.contains("mode=memory")
+    }
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+// 設定を誤ったまま起動しないように、真偽値として解釈できない値の場合は起動しない
+fn env_bool_or(key: &str, default: bool) -> bool {
+    match env::var(key) {
+        Ok(value) => parse_bool(&value).unwrap_or_else(|| panic!("invalid {}: {}", key, value)),
+        Err(_) => default,
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+// DatabaseConnectionは内部でコネクションプールを持つため、Arcで共有するだけでよい
+#[derive(Clone)]
 struct Repository {
-    db: Arc<Mutex<DatabaseConnection>>,
+    db: Connection,
 }
 
 impl Repository {
-    fn new(conn: Arc<Mutex<DatabaseConnection>>) -> Self {
-        Self { db: conn }
+    fn new(conn: Arc<DatabaseConnection>) -> Self {
+        Self {
+            db: Connection::Pool(conn),
+        }
+    }
+    // Unit of Workのトランザクション内で書き込むリポジトリを作る
+    fn with_transaction(txn: Arc<DatabaseTransaction>) -> Self {
+        Self {
+            db: Connection::Transaction(txn),
+        }
+    }
+    fn get_db(&self) -> &Connection {
+        &self.db
     }
-    fn get_db(&self) -> Arc<Mutex<DatabaseConnection>> {
-        self.db.clone()
+}
+
+// リポジトリはプールとトランザクションのどちらに対しても同じクエリを発行する
+#[derive(Clone)]
+enum Connection {
+    Pool(Arc<DatabaseConnection>),
+    Transaction(Arc<DatabaseTransaction>),
+}
+
+impl Connection {
+    // トランザクション内で呼ばれた場合はセーブポイントを使う
+    async fn begin(&self) -> Result<DatabaseTransaction, DbErr> {
+        match self {
+            Connection::Pool(conn) => conn.begin().await,
+            Connection::Transaction(txn) => txn.begin().await,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ConnectionTrait for Connection {
+    fn get_database_backend(&self) -> DatabaseBackend {
+        match self {
+            Connection::Pool(conn) => conn.get_database_backend(),
+            Connection::Transaction(txn) => txn.get_database_backend(),
+        }
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        match self {
+            Connection::Pool(conn) => conn.execute(stmt).await,
+            Connection::Transaction(txn) => txn.execute(stmt).await,
+        }
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        match self {
+            Connection::Pool(conn) => conn.execute_unprepared(sql).await,
+            Connection::Transaction(txn) => txn.execute_unprepared(sql).await,
+        }
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        match self {
+            Connection::Pool(conn) => conn.query_one(stmt).await,
+            Connection::Transaction(txn) => txn.query_one(stmt).await,
+        }
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        match self {
+            Connection::Pool(conn) => conn.query_all(stmt).await,
+            Connection::Transaction(txn) => txn.query_all(stmt).await,
+        }
+    }
+
+    fn is_mock_connection(&self) -> bool {
+        match self {
+            Connection::Pool(conn) => conn.is_mock_connection(),
+            Connection::Transaction(txn) => txn.is_mock_connection(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bool() {
+        for value in ["true", "1", "yes", "on", "TRUE", " Yes "] {
+            assert_eq!(parse_bool(value), Some(true));
+        }
+        for value in ["false", "0", "no", "off", "False"] {
+            assert_eq!(parse_bool(value), Some(false));
+        }
+        for value in ["", "2", "enable", "truee"] {
+            assert_eq!(parse_bool(value), None);
+        }
     }
 }
@@ -1,8 +1,7 @@
-use std::{ops::Deref, sync::Arc};
+use std::sync::Arc;
 
 use entity::hello::{self, ActiveModel};
 use sea_orm::{DatabaseConnection, EntityTrait, IntoSimpleExpr, QueryFilter, Set};
-use tokio::sync::Mutex;
 
 use crate::{
     domain::{hello::Hello, repository::hello::HelloRepositoryTrait},
@@ -18,7 +17,9 @@ pub struct HelloPersistence {
 }
 
 impl HelloRepositoryTrait for HelloPersistence {
-    fn new(conn: Arc<Mutex<DatabaseConnection>>) -> Self
+    type Connection = Arc<DatabaseConnection>;
+
+    fn new(conn: Arc<DatabaseConnection>) -> Self
     where
         Self: Sized,
     {
@@ -27,9 +28,7 @@ impl HelloRepositoryTrait for HelloPersistence {
         }
     }
     async fn insert(&self, hello: Hello) -> Result<String, CustomError> {
-        let db_unlock = self.repository.get_db();
-        let db_lock = db_unlock.lock().await;
-        let db = db_lock.deref();
+        let db = self.repository.get_db();
         let hello_am = ActiveModel {
             name: Set(hello.name),
             message: Set(hello.message),
@@ -39,9 +38,7 @@ impl HelloRepositoryTrait for HelloPersistence {
     }
 
     async fn find(&self, name: String) -> Result<Hello, CustomError> {
-        let db_unlock = self.repository.get_db();
-        let db_lock = db_unlock.lock().await;
-        let db = db_lock.deref();
+        let db = self.repository.get_db();
         let result = HelloEntity::find()
             .filter(hello::Column::Name.into_simple_expr().eq(&name))
             .one(db)
@@ -51,7 +48,7 @@ impl HelloRepositoryTrait for HelloPersistence {
                 name: hello.name,
                 message: hello.message,
             }),
-            None => Err(CustomError::DbNotFound(format!("key: {}", &name))),
+            None => Err(CustomError::NotFound(format!("key: {}", &name))),
         }
     }
 }
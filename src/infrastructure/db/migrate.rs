@@ -0,0 +1,57 @@
+use std::collections::HashSet;
+
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{DatabaseConnection, DbErr};
+
+// 未適用のマイグレーションを適用し、新たに適用したバージョンを返す。
+// このバイナリが知らないマイグレーションが適用済みの場合は、
+// DBのスキーマの方が新しいとみなして何もせずにエラーを返す
+pub async fn run_migrations(conn: &DatabaseConnection) -> Result<Vec<String>, DbErr> {
+    let applied: Vec<String> = Migrator::get_migration_models(conn)
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+    let known: Vec<String> = Migrator::migrations()
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+
+    let unknown = unknown_versions(&applied, &known);
+    if !unknown.is_empty() {
+        return Err(DbErr::Migration(format!(
+            "database schema is newer than this binary (unknown migrations: {})",
+            unknown.join(", ")
+        )));
+    }
+    log::info!(
+        "Database schema version: {} ({} migrations applied)",
+        applied.last().map_or("none", String::as_str),
+        applied.len()
+    );
+
+    let pending: Vec<String> = Migrator::get_pending_migrations(conn)
+        .await?
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+    if pending.is_empty() {
+        log::info!("No pending migrations");
+        return Ok(pending);
+    }
+    Migrator::up(conn, None).await?;
+    for version in &pending {
+        log::info!("Applied migration {}", version);
+    }
+    Ok(pending)
+}
+
+// 適用済みのうちバイナリに含まれていないもの
+fn unknown_versions(applied: &[String], known: &[String]) -> Vec<String> {
+    let known: HashSet<&String> = known.iter().collect();
+    applied
+        .iter()
+        .filter(|version| !known.contains(version))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use entity::fired_reminder::{self, Entity as FiredReminderEntity};
+use entity::reminder_offset::{self, Entity as ReminderOffsetEntity};
+use entity::task::{self, Entity as TaskEntity, TaskStatus};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        reminder::{FiredReminder, ReminderOffset},
+        repository::reminder::ReminderRepositoryTrait,
+        task::Task,
+    },
+    error::CustomError,
+};
+
+use super::Repository;
+
+pub struct ReminderPersistence {
+    repository: Repository,
+}
+
+impl ReminderPersistence {
+    pub(super) fn with_transaction(txn: Arc<DatabaseTransaction>) -> Self {
+        Self {
+            repository: Repository::with_transaction(txn),
+        }
+    }
+}
+
+impl ReminderRepositoryTrait for ReminderPersistence {
+    type Connection = Arc<DatabaseConnection>;
+
+    fn new(conn: Arc<DatabaseConnection>) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            repository: Repository::new(conn),
+        }
+    }
+
+    async fn find_upcoming_tasks(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<Task>, CustomError> {
+        let db = self.repository.get_db();
+        let result = TaskEntity::find()
+            .filter(task::Column::DueDate.gt(from))
+            .filter(task::Column::DueDate.lte(to))
+            .filter(task::Column::DeletedAt.is_null())
+            .filter(task::Column::Status.is_in([TaskStatus::Todo, TaskStatus::InProgress]))
+            .order_by_asc(task::Column::DueDate)
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+
+    async fn find_offsets(
+        &self,
+        user_ids: Vec<String>,
+    ) -> Result<Vec<ReminderOffset>, CustomError> {
+        let db = self.repository.get_db();
+        let result = ReminderOffsetEntity::find()
+            .filter(reminder_offset::Column::UserId.is_in(user_ids))
+            .order_by_desc(reminder_offset::Column::OffsetMinutes)
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+
+    async fn replace_offsets(
+        &self,
+        user_id: String,
+        offsets: Vec<ReminderOffset>,
+    ) -> Result<(), CustomError> {
+        let db = self.repository.get_db();
+        let txn = db.begin().await?;
+        ReminderOffsetEntity::delete_many()
+            .filter(reminder_offset::Column::UserId.eq(&user_id))
+            .exec(&txn)
+            .await?;
+        if !offsets.is_empty() {
+            let offset_ams = offsets.into_iter().map(|o| reminder_offset::ActiveModel {
+                user_id: Set(o.user_id),
+                offset_minutes: Set(o.offset_minutes),
+                created_at: Set(o.created_at),
+            });
+            ReminderOffsetEntity::insert_many(offset_ams)
+                .exec(&txn)
+                .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn find_fired(&self, task_ids: Vec<Uuid>) -> Result<Vec<FiredReminder>, CustomError> {
+        let db = self.repository.get_db();
+        let result = FiredReminderEntity::find()
+            .filter(fired_reminder::Column::TaskId.is_in(task_ids))
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+
+    async fn mark_fired(&self, fired: FiredReminder) -> Result<bool, CustomError> {
+        let db = self.repository.get_db();
+        let fired_am = fired_reminder::ActiveModel {
+            task_id: Set(fired.task_id),
+            due_date: Set(fired.due_date),
+            offset_minutes: Set(fired.offset_minutes),
+            fired_at: Set(fired.fired_at),
+        };
+        // 複数のワーカーが同時に送らないよう、記録できたものだけを送信対象にする
+        let rows = FiredReminderEntity::insert(fired_am)
+            .on_conflict(
+                OnConflict::columns([
+                    fired_reminder::Column::TaskId,
+                    fired_reminder::Column::DueDate,
+                    fired_reminder::Column::OffsetMinutes,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        Ok(rows > 0)
+    }
+
+    async fn unmark_fired(&self, fired: FiredReminder) -> Result<(), CustomError> {
+        let db = self.repository.get_db();
+        FiredReminderEntity::delete_many()
+            .filter(fired_reminder::Column::TaskId.eq(fired.task_id))
+            .filter(fired_reminder::Column::DueDate.eq(fired.due_date))
+            .filter(fired_reminder::Column::OffsetMinutes.eq(fired.offset_minutes))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use entity::tag::{self, ActiveModel};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::{
+    domain::{repository::tag::TagRepositoryTrait, tag::Tag},
+    error::CustomError,
+};
+
+use entity::tag::Entity as TagEntity;
+
+use super::Repository;
+
+pub struct TagPersistence {
+    repository: Repository,
+}
+
+impl TagPersistence {
+    pub(super) fn with_transaction(txn: Arc<DatabaseTransaction>) -> Self {
+        Self {
+            repository: Repository::with_transaction(txn),
+        }
+    }
+}
+
+impl TagRepositoryTrait for TagPersistence {
+    type Connection = Arc<DatabaseConnection>;
+
+    fn new(conn: Arc<DatabaseConnection>) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            repository: Repository::new(conn),
+        }
+    }
+
+    async fn insert(&self, tag: Tag) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let tag_am = ActiveModel {
+            id: Set(tag.id),
+            user_id: Set(tag.user_id),
+            name: Set(tag.name),
+            created_at: Set(tag.created_at),
+            updated_at: Set(tag.updated_at),
+        };
+        let insert_result = TagEntity::insert(tag_am).exec(db).await?;
+        Ok(insert_result.last_insert_id)
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Tag, CustomError> {
+        let db = self.repository.get_db();
+        TagEntity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &id)))
+    }
+
+    async fn find_from_user_id(&self, user_id: String) -> Result<Vec<Tag>, CustomError> {
+        let db = self.repository.get_db();
+        let result = TagEntity::find()
+            .filter(tag::Column::UserId.eq(&user_id))
+            .order_by_asc(tag::Column::Name)
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+
+    async fn update(&self, tag: Tag) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let tag_am = ActiveModel {
+            id: Set(tag.id),
+            user_id: Set(tag.user_id),
+            name: Set(tag.name),
+            created_at: Set(tag.created_at),
+            updated_at: Set(tag.updated_at),
+        };
+        let update_result = TagEntity::update(tag_am).exec(db).await?;
+        Ok(update_result.id)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let delete_result = TagEntity::delete_by_id(id).exec(db).await?;
+        if delete_result.rows_affected == 0 {
+            return Err(CustomError::NotFound(format!("key: {}", &id)));
+        }
+        Ok(id)
+    }
+}
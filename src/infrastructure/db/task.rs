@@ -1,16 +1,32 @@
-use std::{ops::Deref, sync::Arc};
+use std::{collections::HashSet, sync::Arc};
 
 use entity::task::{self, ActiveModel};
-use sea_orm::{DatabaseConnection, EntityTrait, IntoSimpleExpr, QueryFilter, Set};
-use tokio::sync::Mutex;
+use sea_orm::{
+    sea_query::{Expr, Func, LikeExpr, Query},
+    ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction, EntityTrait, IntoSimpleExpr,
+    Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, Value,
+};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::{
-    domain::{repository::task::TaskRepositoryTrait, task::Task},
+    domain::{
+        repository::task::TaskRepositoryTrait,
+        tag::Tag,
+        task::Task,
+        task_dependency::TaskDependency,
+        task_history::TaskHistory,
+        task_query::{CursorValue, SortOrder, TaskQuery, TaskSortKey},
+    },
     error::CustomError,
 };
 
+use entity::tag::Entity as TagEntity;
 use entity::task::Entity as TaskEntity;
+use entity::task_dependency::{self, Entity as TaskDependencyEntity};
+use entity::task_history::{self, Entity as TaskHistoryEntity};
+use entity::task_share::{self, Entity as TaskShareEntity};
+use entity::task_tag::{self, Entity as TaskTagEntity};
 
 use super::Repository;
 
@@ -18,8 +34,18 @@ pub struct TaskPersistence {
     repository: Repository,
 }
 
+impl TaskPersistence {
+    pub(super) fn with_transaction(txn: Arc<DatabaseTransaction>) -> Self {
+        Self {
+            repository: Repository::with_transaction(txn),
+        }
+    }
+}
+
 impl TaskRepositoryTrait for TaskPersistence {
-    fn new(conn: Arc<Mutex<DatabaseConnection>>) -> Self
+    type Connection = Arc<DatabaseConnection>;
+
+    fn new(conn: Arc<DatabaseConnection>) -> Self
     where
         Self: Sized,
     {
@@ -27,31 +53,20 @@ impl TaskRepositoryTrait for TaskPersistence {
             repository: Repository::new(conn),
         }
     }
-    async fn insert(&self, task: Task) -> Result<Uuid, CustomError> {
-        let db_unlock = self.repository.get_db();
-        let db_lock = db_unlock.lock().await;
-        let db = db_lock.deref();
-        let task_am = ActiveModel {
-            id: Set(task.id),
-            title: Set(task.title),
-            description: Set(task.description),
-            due_date: Set(task.due_date),
-            priority: Set(task.priority),
-            weight: Set(task.weight),
-            created_at: Set(task.created_at),
-            updated_at: Set(task.updated_at),
-            user_id: Set(task.user_id),
-        };
-        let insert_result = TaskEntity::insert(task_am).exec(db).await?;
-        Ok(insert_result.last_insert_id)
+    async fn insert(&self, task: Task, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let txn = db.begin().await?;
+        let id = insert_task(&txn, task).await?;
+        insert_histories(&txn, histories).await?;
+        txn.commit().await?;
+        Ok(id)
     }
 
     async fn find(&self, id: Uuid) -> Result<Task, CustomError> {
-        let db_unlock = self.repository.get_db();
-        let db_lock = db_unlock.lock().await;
-        let db = db_lock.deref();
+        let db = self.repository.get_db();
         let result = TaskEntity::find()
             .filter(task::Column::Id.into_simple_expr().eq(id))
+            .filter(task::Column::DeletedAt.is_null())
             .one(db)
             .await?;
         match result {
@@ -64,19 +79,32 @@ impl TaskRepositoryTrait for TaskPersistence {
                 weight: task.weight,
                 created_at: task.created_at,
                 updated_at: task.updated_at,
+                deleted_at: task.deleted_at,
+                status: task.status,
+                completed_at: task.completed_at,
+                parent_id: task.parent_id,
+                recurrence: task.recurrence,
+                version: task.version,
                 user_id: task.user_id,
             }),
-            None => Err(CustomError::DbNotFound(format!("key: {}", &id))),
+            None => Err(CustomError::NotFound(format!("key: {}", &id))),
         }
     }
 
+    async fn find_with_deleted(&self, id: Uuid) -> Result<Task, CustomError> {
+        let db = self.repository.get_db();
+        TaskEntity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &id)))
+    }
+
     async fn find_from_user_id(&self, user_id: String) -> Result<Vec<Task>, CustomError> {
-        let db_unlock = self.repository.get_db();
-        let db_lock = db_unlock.lock().await;
-        let db = db_lock.deref();
+        let db = self.repository.get_db();
 
         let result = TaskEntity::find()
             .filter(task::Column::UserId.into_simple_expr().eq(&user_id))
+            .filter(task::Column::DeletedAt.is_null())
             .all(db)
             .await?;
 
@@ -91,27 +119,511 @@ impl TaskRepositoryTrait for TaskPersistence {
                 weight: t.weight,
                 created_at: t.created_at,
                 updated_at: t.updated_at,
+                deleted_at: t.deleted_at,
+                status: t.status,
+                completed_at: t.completed_at,
+                parent_id: t.parent_id,
+                recurrence: t.recurrence.clone(),
+                version: t.version,
                 user_id: t.user_id.clone(),
             })
             .collect())
     }
 
-    async fn update(&self, task: Task) -> Result<Uuid, CustomError> {
-        let db_unlock = self.repository.get_db();
-        let db_lock = db_unlock.lock().await;
-        let db = db_lock.deref();
-        let task_am = ActiveModel {
-            id: Set(task.id),
-            title: Set(task.title),
-            description: Set(task.description),
-            due_date: Set(task.due_date),
-            priority: Set(task.priority),
-            weight: Set(task.weight),
-            created_at: Set(task.created_at),
-            updated_at: Set(task.updated_at),
-            user_id: Set(task.user_id),
+    async fn find_children(&self, parent_ids: Vec<Uuid>) -> Result<Vec<Task>, CustomError> {
+        let db = self.repository.get_db();
+        let result = TaskEntity::find()
+            .filter(task::Column::ParentId.is_in(parent_ids))
+            .filter(task::Column::DeletedAt.is_null())
+            .order_by_asc(task::Column::CreatedAt)
+            .order_by_asc(task::Column::Id)
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+
+    async fn has_children(&self, id: Uuid) -> Result<bool, CustomError> {
+        let db = self.repository.get_db();
+        let count = TaskEntity::find()
+            .filter(task::Column::ParentId.eq(id))
+            .count(db)
+            .await?;
+        Ok(count > 0)
+    }
+
+    async fn search(&self, query: TaskQuery, limit: u64) -> Result<Vec<Task>, CustomError> {
+        let db = self.repository.get_db();
+
+        let mut filter = query.filter;
+        let mut condition = Condition::all()
+            .add(task::Column::UserId.eq(&query.user_id))
+            .add(task::Column::DeletedAt.is_null());
+        if let Some(from) = filter.due_date_from {
+            condition = condition.add(task::Column::DueDate.gte(from));
+        }
+        if let Some(to) = filter.due_date_to {
+            condition = condition.add(task::Column::DueDate.lte(to));
+        }
+        if let Some(min) = filter.priority_min {
+            condition = condition.add(task::Column::Priority.gte(min));
+        }
+        if let Some(max) = filter.priority_max {
+            condition = condition.add(task::Column::Priority.lte(max));
+        }
+        if !filter.statuses.is_empty() {
+            condition = condition.add(task::Column::Status.is_in(filter.statuses));
+        }
+        if let Some(text) = filter.text.filter(|t| !t.is_empty()) {
+            // タイトルと説明文を大文字小文字を区別せずに部分一致検索する
+            let pattern = format!(
+                "%{}%",
+                text.to_lowercase()
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            condition = condition.add(
+                Condition::any()
+                    .add(
+                        Expr::expr(Func::lower(Expr::col(task::Column::Title)))
+                            .like(LikeExpr::new(&pattern).escape('\\')),
+                    )
+                    .add(
+                        Expr::expr(Func::lower(Expr::col(task::Column::Description)))
+                            .like(LikeExpr::new(&pattern).escape('\\')),
+                    ),
+            );
+        }
+
+        if !filter.tag_ids.is_empty() {
+            filter.tag_ids.sort();
+            filter.tag_ids.dedup();
+            let tag_count = filter.tag_ids.len() as i64;
+            condition = condition.add(
+                task::Column::Id.in_subquery(
+                    Query::select()
+                        .column(task_tag::Column::TaskId)
+                        .from(TaskTagEntity)
+                        .and_where(task_tag::Column::TagId.is_in(filter.tag_ids))
+                        .group_by_col(task_tag::Column::TaskId)
+                        .and_having(Expr::col(task_tag::Column::TagId).count().eq(tag_count))
+                        .to_owned(),
+                ),
+            );
+        }
+
+        let sort_column = match query.sort_key {
+            TaskSortKey::DueDate => task::Column::DueDate,
+            TaskSortKey::Priority => task::Column::Priority,
+            TaskSortKey::Weight => task::Column::Weight,
+            TaskSortKey::CreatedAt => task::Column::CreatedAt,
+        };
+        let order = match query.order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        };
+
+        // ソートキーが同じ場合はIDで順序を確定させ、カーソル以降の行のみを取得する
+        if let Some(cursor) = query.cursor {
+            let value: Value = match cursor.value {
+                CursorValue::DateTime(datetime) => datetime.into(),
+                CursorValue::Int(value) => value.into(),
+            };
+            let after_cursor = match query.order {
+                SortOrder::Asc => Condition::any().add(sort_column.gt(value.clone())).add(
+                    Condition::all()
+                        .add(sort_column.eq(value))
+                        .add(task::Column::Id.gt(cursor.id)),
+                ),
+                SortOrder::Desc => Condition::any().add(sort_column.lt(value.clone())).add(
+                    Condition::all()
+                        .add(sort_column.eq(value))
+                        .add(task::Column::Id.lt(cursor.id)),
+                ),
+            };
+            condition = condition.add(after_cursor);
+        }
+
+        let result = TaskEntity::find()
+            .filter(condition)
+            .order_by(sort_column, order.clone())
+            .order_by(task::Column::Id, order)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn update(&self, task: Task, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let txn = db.begin().await?;
+        let id = update_versioned(&txn, task).await?;
+        insert_histories(&txn, histories).await?;
+        txn.commit().await?;
+        Ok(id)
+    }
+
+    async fn update_with_next_occurrence(
+        &self,
+        task: Task,
+        next_task: Task,
+        histories: Vec<TaskHistory>,
+    ) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let txn = db.begin().await?;
+        let id = update_versioned(&txn, task).await?;
+        insert_task(&txn, next_task).await?;
+        insert_histories(&txn, histories).await?;
+        txn.commit().await?;
+        Ok(id)
+    }
+
+    async fn delete(&self, id: Uuid, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let txn = db.begin().await?;
+        delete_task(&txn, id).await?;
+        insert_histories(&txn, histories).await?;
+        txn.commit().await?;
+        Ok(id)
+    }
+
+    async fn restore(&self, id: Uuid, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let txn = db.begin().await?;
+        let update_result = TaskEntity::update_many()
+            .col_expr(
+                task::Column::DeletedAt,
+                Expr::value(Option::<OffsetDateTime>::None),
+            )
+            .col_expr(
+                task::Column::Version,
+                Expr::col(task::Column::Version).add(1),
+            )
+            .filter(task::Column::Id.into_simple_expr().eq(id))
+            .filter(task::Column::DeletedAt.is_not_null())
+            .exec(&txn)
+            .await?;
+        if update_result.rows_affected == 0 {
+            return Err(CustomError::NotFound(format!("key: {}", &id)));
+        }
+        insert_histories(&txn, histories).await?;
+        txn.commit().await?;
+        Ok(id)
+    }
+
+    async fn purge(&self, id: Uuid, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let txn = db.begin().await?;
+        let delete_result = TaskEntity::delete_many()
+            .filter(task::Column::Id.into_simple_expr().eq(id))
+            .exec(&txn)
+            .await?;
+        if delete_result.rows_affected == 0 {
+            return Err(CustomError::NotFound(format!("key: {}", &id)));
+        }
+        insert_histories(&txn, histories).await?;
+        txn.commit().await?;
+        Ok(id)
+    }
+
+    async fn is_shared_with(&self, id: Uuid, user_id: String) -> Result<bool, CustomError> {
+        let db = self.repository.get_db();
+        let result = TaskShareEntity::find_by_id((id, user_id)).one(db).await?;
+        Ok(result.is_some())
+    }
+
+    async fn find_shared_task_ids(&self, user_id: String) -> Result<Vec<Uuid>, CustomError> {
+        let db = self.repository.get_db();
+        let result = TaskShareEntity::find()
+            .filter(task_share::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+        Ok(result.into_iter().map(|s| s.task_id).collect())
+    }
+
+    async fn share(&self, id: Uuid, user_id: String) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let share_am = task_share::ActiveModel {
+            task_id: Set(id),
+            user_id: Set(user_id),
+            created_at: Set(OffsetDateTime::now_utc()),
+        };
+        TaskShareEntity::insert(share_am).exec(db).await?;
+        Ok(id)
+    }
+
+    async fn unshare(&self, id: Uuid, user_id: String) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let delete_result = TaskShareEntity::delete_many()
+            .filter(task_share::Column::TaskId.eq(id))
+            .filter(task_share::Column::UserId.eq(&user_id))
+            .exec(db)
+            .await?;
+        if delete_result.rows_affected == 0 {
+            return Err(CustomError::NotFound(format!(
+                "key: {}, user: {}",
+                &id, &user_id
+            )));
+        }
+        Ok(id)
+    }
+
+    async fn find_dependencies(
+        &self,
+        task_ids: Vec<Uuid>,
+    ) -> Result<Vec<TaskDependency>, CustomError> {
+        let db = self.repository.get_db();
+        let result = TaskDependencyEntity::find()
+            .filter(task_dependency::Column::TaskId.is_in(task_ids))
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+
+    async fn find_prerequisites(&self, id: Uuid) -> Result<Vec<Task>, CustomError> {
+        let db = self.repository.get_db();
+        let result = TaskEntity::find()
+            .filter(
+                task::Column::Id.in_subquery(
+                    Query::select()
+                        .column(task_dependency::Column::DependsOnId)
+                        .from(TaskDependencyEntity)
+                        .and_where(task_dependency::Column::TaskId.eq(id))
+                        .to_owned(),
+                ),
+            )
+            .filter(task::Column::DeletedAt.is_null())
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+
+    async fn add_dependency(&self, id: Uuid, depends_on_id: Uuid) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        // Unit of Workのトランザクション内ではセーブポイントになり分離レベルを変えられないため、
+        // 分離レベルには頼らずに行ロックで循環の確認と追加を直列化する
+        let txn = db.begin().await?;
+        ensure_no_cycle(&txn, id, depends_on_id).await?;
+        let dependency_am = task_dependency::ActiveModel {
+            task_id: Set(id),
+            depends_on_id: Set(depends_on_id),
+            created_at: Set(OffsetDateTime::now_utc()),
+        };
+        TaskDependencyEntity::insert(dependency_am)
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(id)
+    }
+
+    async fn remove_dependency(&self, id: Uuid, depends_on_id: Uuid) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let delete_result = TaskDependencyEntity::delete_many()
+            .filter(task_dependency::Column::TaskId.eq(id))
+            .filter(task_dependency::Column::DependsOnId.eq(depends_on_id))
+            .exec(db)
+            .await?;
+        if delete_result.rows_affected == 0 {
+            return Err(CustomError::NotFound(format!(
+                "key: {}, depends_on: {}",
+                &id, &depends_on_id
+            )));
+        }
+        Ok(id)
+    }
+
+    async fn find_tag(&self, tag_id: Uuid) -> Result<Tag, CustomError> {
+        let db = self.repository.get_db();
+        TagEntity::find_by_id(tag_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &tag_id)))
+    }
+
+    async fn attach_tag(&self, id: Uuid, tag_id: Uuid) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let task_tag_am = task_tag::ActiveModel {
+            task_id: Set(id),
+            tag_id: Set(tag_id),
+            created_at: Set(OffsetDateTime::now_utc()),
+        };
+        TaskTagEntity::insert(task_tag_am).exec(db).await?;
+        Ok(id)
+    }
+
+    async fn detach_tag(&self, id: Uuid, tag_id: Uuid) -> Result<Uuid, CustomError> {
+        let db = self.repository.get_db();
+        let delete_result = TaskTagEntity::delete_many()
+            .filter(task_tag::Column::TaskId.eq(id))
+            .filter(task_tag::Column::TagId.eq(tag_id))
+            .exec(db)
+            .await?;
+        if delete_result.rows_affected == 0 {
+            return Err(CustomError::NotFound(format!(
+                "key: {}, tag: {}",
+                &id, &tag_id
+            )));
+        }
+        Ok(id)
+    }
+
+    async fn find_history(&self, id: Uuid) -> Result<Vec<TaskHistory>, CustomError> {
+        let db = self.repository.get_db();
+        let result = TaskHistoryEntity::find()
+            .filter(task_history::Column::TaskId.eq(id))
+            .order_by_asc(task_history::Column::CreatedAt)
+            .order_by_asc(task_history::Column::Id)
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+}
+
+async fn delete_task(txn: &DatabaseTransaction, id: Uuid) -> Result<Uuid, CustomError> {
+    let update_result = TaskEntity::update_many()
+        .col_expr(
+            task::Column::DeletedAt,
+            Expr::value(Some(OffsetDateTime::now_utc())),
+        )
+        .col_expr(
+            task::Column::Version,
+            Expr::col(task::Column::Version).add(1),
+        )
+        .filter(task::Column::Id.into_simple_expr().eq(id))
+        .filter(task::Column::DeletedAt.is_null())
+        .exec(txn)
+        .await?;
+    if update_result.rows_affected == 0 {
+        return Err(CustomError::NotFound(format!("key: {}", &id)));
+    }
+    Ok(id)
+}
+
+async fn insert_histories(
+    txn: &DatabaseTransaction,
+    histories: Vec<TaskHistory>,
+) -> Result<(), CustomError> {
+    if histories.is_empty() {
+        return Ok(());
+    }
+    let history_ams = histories
+        .into_iter()
+        .map(|history| task_history::ActiveModel {
+            id: Set(history.id),
+            task_id: Set(history.task_id),
+            owner_id: Set(history.owner_id),
+            actor_id: Set(history.actor_id),
+            action: Set(history.action),
+            changes: Set(history.changes),
+            created_at: Set(history.created_at),
+        });
+    TaskHistoryEntity::insert_many(history_ams)
+        .exec(txn)
+        .await?;
+    Ok(())
+}
+
+fn active_model(task: Task) -> ActiveModel {
+    ActiveModel {
+        id: Set(task.id),
+        title: Set(task.title),
+        description: Set(task.description),
+        due_date: Set(task.due_date),
+        priority: Set(task.priority),
+        weight: Set(task.weight),
+        created_at: Set(task.created_at),
+        updated_at: Set(task.updated_at),
+        deleted_at: Set(task.deleted_at),
+        status: Set(task.status),
+        completed_at: Set(task.completed_at),
+        parent_id: Set(task.parent_id),
+        recurrence: Set(task.recurrence),
+        version: Set(task.version),
+        user_id: Set(task.user_id),
+    }
+}
+
+async fn insert_task(txn: &DatabaseTransaction, task: Task) -> Result<Uuid, CustomError> {
+    let insert_result = TaskEntity::insert(active_model(task)).exec(txn).await?;
+    Ok(insert_result.last_insert_id)
+}
+
+// task.versionは読み込んだ時点のバージョン。他で更新されていた場合は書き込まない
+async fn update_versioned(txn: &DatabaseTransaction, task: Task) -> Result<Uuid, CustomError> {
+    let id = task.id;
+    let expected_version = task.version;
+    let task_am = active_model(Task {
+        version: expected_version + 1,
+        ..task
+    });
+    let update_result = TaskEntity::update_many()
+        .set(task_am)
+        .filter(task::Column::Id.into_simple_expr().eq(id))
+        .filter(task::Column::Version.eq(expected_version))
+        .exec(txn)
+        .await?;
+    if update_result.rows_affected == 0 {
+        // 存在しないのか、先に更新されたのかを区別する
+        return match TaskEntity::find_by_id(id).one(txn).await? {
+            Some(current) => Err(CustomError::VersionMismatch(format!(
+                "key: {}, expected version {}, found {}",
+                &id, expected_version, current.version
+            ))),
+            None => Err(CustomError::NotFound(format!("key: {}", &id))),
         };
-        let update_result = TaskEntity::update(task_am).exec(db).await?;
-        Ok(update_result.id)
     }
+    Ok(id)
+}
+
+// depends_on_idから依存先を辿ってidに到達する場合、依存を追加すると循環する
+// 追加する依存の両端と、辿った全てのタスクの行をFOR UPDATEでロックする。
+// 同時に追加される依存で循環ができる場合は、一方の端点を他方が辿るため後から確認する側が待たされ、
+// 待った後に読み直す依存にはもう一方の追加が含まれる
+async fn ensure_no_cycle(
+    txn: &DatabaseTransaction,
+    id: Uuid,
+    depends_on_id: Uuid,
+) -> Result<(), CustomError> {
+    let mut endpoints = [id, depends_on_id];
+    endpoints.sort();
+    lock_tasks(txn, endpoints.to_vec()).await?;
+
+    let mut visited = HashSet::from([depends_on_id]);
+    let mut frontier = vec![depends_on_id];
+    while !frontier.is_empty() {
+        let mut next = Vec::new();
+        lock_tasks(txn, frontier.clone()).await?;
+        let dependencies = TaskDependencyEntity::find()
+            .filter(task_dependency::Column::TaskId.is_in(frontier))
+            .all(txn)
+            .await?;
+        for dep in dependencies {
+            if dep.depends_on_id == id {
+                return Err(CustomError::DependencyCycle(format!(
+                    "task {} already depends on task {}",
+                    depends_on_id, id
+                )));
+            }
+            if visited.insert(dep.depends_on_id) {
+                next.push(dep.depends_on_id);
+            }
+        }
+        frontier = next;
+    }
+    Ok(())
+}
+
+// SQLiteは行ロックに対応していないが、書き込みはデータベース全体で直列化される
+async fn lock_tasks(txn: &DatabaseTransaction, ids: Vec<Uuid>) -> Result<(), CustomError> {
+    TaskEntity::find()
+        .select_only()
+        .column(task::Column::Id)
+        .filter(task::Column::Id.is_in(ids))
+        .order_by_asc(task::Column::Id)
+        .lock_exclusive()
+        .into_tuple::<Uuid>()
+        .all(txn)
+        .await?;
+    Ok(())
 }
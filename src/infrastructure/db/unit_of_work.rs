@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use sea_orm::{DatabaseConnection, DatabaseTransaction, DbErr, TransactionTrait};
+
+use crate::{
+    domain::repository::unit_of_work::{TransactionScopeTrait, UnitOfWorkTrait},
+    error::CustomError,
+};
+
+use super::{
+    reminder::ReminderPersistence, tag::TagPersistence, task::TaskPersistence,
+    user::UserPersistence,
+};
+
+pub struct UnitOfWork {
+    conn: Arc<DatabaseConnection>,
+}
+
+impl UnitOfWorkTrait for UnitOfWork {
+    type Connection = Arc<DatabaseConnection>;
+    type Transaction = TransactionScope;
+
+    fn new(conn: Arc<DatabaseConnection>) -> Self
+    where
+        Self: Sized,
+    {
+        Self { conn }
+    }
+
+    async fn begin(&self) -> Result<TransactionScope, CustomError> {
+        let txn = Arc::new(self.conn.begin().await?);
+        Ok(TransactionScope {
+            tasks: TaskPersistence::with_transaction(txn.clone()),
+            users: UserPersistence::with_transaction(txn.clone()),
+            tags: TagPersistence::with_transaction(txn.clone()),
+            reminders: ReminderPersistence::with_transaction(txn.clone()),
+            txn,
+        })
+    }
+}
+
+// 各リポジトリは同じトランザクションを共有する
+pub struct TransactionScope {
+    txn: Arc<DatabaseTransaction>,
+    tasks: TaskPersistence,
+    users: UserPersistence,
+    tags: TagPersistence,
+    reminders: ReminderPersistence,
+}
+
+impl TransactionScopeTrait for TransactionScope {
+    type Tasks = TaskPersistence;
+    type Users = UserPersistence;
+    type Tags = TagPersistence;
+    type Reminders = ReminderPersistence;
+
+    fn tasks(&self) -> &TaskPersistence {
+        &self.tasks
+    }
+
+    fn users(&self) -> &UserPersistence {
+        &self.users
+    }
+
+    fn tags(&self) -> &TagPersistence {
+        &self.tags
+    }
+
+    fn reminders(&self) -> &ReminderPersistence {
+        &self.reminders
+    }
+
+    async fn commit(self) -> Result<(), CustomError> {
+        let Self {
+            txn,
+            tasks,
+            users,
+            tags,
+            reminders,
+        } = self;
+        // commitにはトランザクションの所有権が必要なので、先にリポジトリ側の参照を手放す
+        drop((tasks, users, tags, reminders));
+        let txn = Arc::try_unwrap(txn)
+            .map_err(|_| DbErr::Custom("transaction is still in use".to_string()))?;
+        txn.commit().await?;
+        Ok(())
+    }
+}
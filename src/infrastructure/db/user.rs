@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use entity::user::{self, ActiveModel};
+use sea_orm::{
+    DatabaseConnection, DatabaseTransaction, EntityTrait, IntoSimpleExpr, QueryFilter, Set,
+};
+
+use crate::{
+    domain::{repository::user::UserRepositoryTrait, user::User},
+    error::CustomError,
+};
+
+use entity::user::Entity as UserEntity;
+
+use super::Repository;
+
+pub struct UserPersistence {
+    repository: Repository,
+}
+
+impl UserPersistence {
+    pub(super) fn with_transaction(txn: Arc<DatabaseTransaction>) -> Self {
+        Self {
+            repository: Repository::with_transaction(txn),
+        }
+    }
+}
+
+impl UserRepositoryTrait for UserPersistence {
+    type Connection = Arc<DatabaseConnection>;
+
+    fn new(conn: Arc<DatabaseConnection>) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            repository: Repository::new(conn),
+        }
+    }
+    async fn insert(&self, user: User) -> Result<String, CustomError> {
+        let db = self.repository.get_db();
+        let user_am = ActiveModel {
+            id: Set(user.id),
+            username: Set(user.username),
+            email: Set(user.email),
+            created_at: Set(user.created_at),
+            updated_at: Set(user.updated_at),
+        };
+        let insert_result = UserEntity::insert(user_am).exec(db).await?;
+        Ok(insert_result.last_insert_id)
+    }
+
+    async fn find(&self, id: String) -> Result<User, CustomError> {
+        let db = self.repository.get_db();
+        let result = UserEntity::find()
+            .filter(user::Column::Id.into_simple_expr().eq(&id))
+            .one(db)
+            .await?;
+        match result {
+            Some(user) => Ok(user),
+            None => Err(CustomError::NotFound(format!("key: {}", &id))),
+        }
+    }
+
+    async fn update(&self, user: User) -> Result<String, CustomError> {
+        let db = self.repository.get_db();
+        let user_am = ActiveModel {
+            id: Set(user.id),
+            username: Set(user.username),
+            email: Set(user.email),
+            created_at: Set(user.created_at),
+            updated_at: Set(user.updated_at),
+        };
+        let update_result = UserEntity::update(user_am).exec(db).await?;
+        Ok(update_result.id)
+    }
+}
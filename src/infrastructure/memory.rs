@@ -0,0 +1,608 @@
+use std::{
+    collections::BTreeMap,
+    mem,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        hello::Hello,
+        reminder::{FiredReminder, ReminderOffset},
+        tag::Tag,
+        task::Task,
+        task_dependency::TaskDependency,
+        task_history::TaskHistory,
+        user::User,
+    },
+    error::CustomError,
+};
+
+pub mod hello;
+pub mod reminder;
+pub mod tag;
+pub mod task;
+pub mod unit_of_work;
+pub mod user;
+
+// DBを使わずに動かすためのストア。
+// 各リポジトリは同じストアを共有し、DBと同じ制約(主キー・一意制約・外部キー)を検査する
+#[derive(Clone, Default)]
+pub struct InMemoryStore {
+    state: Arc<Mutex<State>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    tables: Tables,
+    // 書き込みを反映するたびに進め、書き込んだ行のバージョンにする
+    version: u64,
+}
+
+impl State {
+    // 読み書きした行がその後に他の書き込みで変更されていなければ、書き込んだ行を反映する
+    fn commit(&mut self, changes: Changes) -> Result<(), CustomError> {
+        changes.validate(&self.tables)?;
+        self.version += 1;
+        changes.apply(&mut self.tables, self.version);
+        Ok(())
+    }
+}
+
+// リポジトリはストアに直接、またはUnit of Workのトランザクション内で読み書きする
+#[derive(Clone)]
+enum Session {
+    Store(InMemoryStore),
+    Transaction(InMemoryStore, Arc<Mutex<Changes>>),
+}
+
+impl Session {
+    fn transaction(store: InMemoryStore) -> Self {
+        Session::Transaction(store, Arc::new(Mutex::new(Changes::default())))
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&mut View) -> T) -> T {
+        match self {
+            Session::Store(store) => {
+                let state = store.lock();
+                f(&mut View::new(&state.tables, &mut Changes::default()))
+            }
+            Session::Transaction(store, changes) => {
+                let state = store.lock();
+                let mut changes = changes.lock().unwrap_or_else(PoisonError::into_inner);
+                f(&mut View::new(&state.tables, &mut changes))
+            }
+        }
+    }
+
+    // 途中で失敗した場合は、その呼び出しで書き込んだ行を全て取り消す
+    fn write<T>(
+        &self,
+        f: impl FnOnce(&mut View) -> Result<T, CustomError>,
+    ) -> Result<T, CustomError> {
+        match self {
+            Session::Store(store) => {
+                let mut state = store.lock();
+                let mut changes = Changes::default();
+                let result = f(&mut View::new(&state.tables, &mut changes))?;
+                state.commit(changes)?;
+                Ok(result)
+            }
+            Session::Transaction(store, changes) => {
+                let state = store.lock();
+                let mut changes = changes.lock().unwrap_or_else(PoisonError::into_inner);
+                let savepoint = changes.clone();
+                let result = f(&mut View::new(&state.tables, &mut changes));
+                if result.is_err() {
+                    *changes = savepoint;
+                }
+                result
+            }
+        }
+    }
+
+    fn commit(&self) -> Result<(), CustomError> {
+        let Session::Transaction(store, changes) = self else {
+            return Ok(());
+        };
+        let changes = mem::take(&mut *changes.lock().unwrap_or_else(PoisonError::into_inner));
+        store.lock().commit(changes)
+    }
+}
+
+// 行ごとに、最後に書き込まれた時点のバージョンを持つ
+struct Table<K, V> {
+    rows: BTreeMap<K, (u64, V)>,
+}
+
+impl<K, V> Default for Table<K, V> {
+    fn default() -> Self {
+        Self {
+            rows: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, V> Table<K, V> {
+    // 行が無い場合は0
+    fn version(&self, key: &K) -> u64 {
+        self.rows.get(key).map_or(0, |(version, _)| *version)
+    }
+}
+
+// トランザクション内で読み書きした行。
+// 読み込んだ行(書き込んだ行を含む)のバージョンが反映時に変わっていればTransactionConflictにする
+#[derive(Clone)]
+struct TableChanges<K, V> {
+    reads: BTreeMap<K, u64>,
+    // Noneは削除
+    writes: BTreeMap<K, Option<V>>,
+}
+
+impl<K, V> Default for TableChanges<K, V> {
+    fn default() -> Self {
+        Self {
+            reads: BTreeMap::new(),
+            writes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, V> TableChanges<K, V> {
+    fn validate(&self, table: &Table<K, V>) -> Result<(), CustomError> {
+        if self
+            .reads
+            .iter()
+            .any(|(key, version)| table.version(key) != *version)
+        {
+            return Err(CustomError::TransactionConflict(
+                "row was modified by another write".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply(self, table: &mut Table<K, V>, version: u64) {
+        for (key, row) in self.writes {
+            match row {
+                Some(value) => table.rows.insert(key, (version, value)),
+                None => table.rows.remove(&key),
+            };
+        }
+    }
+}
+
+// トランザクション内で書き込んだ行を、反映済みの行より優先して読む
+struct TableView<'a, K, V> {
+    table: &'a Table<K, V>,
+    changes: &'a mut TableChanges<K, V>,
+}
+
+impl<K: Ord + Clone, V: Clone> TableView<'_, K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        if let Some(row) = self.changes.writes.get(key) {
+            return row.clone();
+        }
+        self.changes
+            .reads
+            .entry(key.clone())
+            .or_insert_with(|| self.table.version(key));
+        self.table.rows.get(key).map(|(_, value)| value.clone())
+    }
+
+    fn contains(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.changes
+            .reads
+            .entry(key.clone())
+            .or_insert_with(|| self.table.version(&key));
+        self.changes.writes.insert(key, Some(value));
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let current = self.get(key);
+        self.changes.writes.insert(key.clone(), None);
+        current
+    }
+
+    // 条件に一致した行だけを読み込んだ行として記録する
+    fn entries(&mut self, predicate: impl Fn(&K, &V) -> bool) -> Vec<(K, V)> {
+        let committed = self
+            .table
+            .rows
+            .iter()
+            .filter(|(key, _)| !self.changes.writes.contains_key(key))
+            .map(|(key, (_, value))| (key, value));
+        let written = self
+            .changes
+            .writes
+            .iter()
+            .filter_map(|(key, row)| row.as_ref().map(|value| (key, value)));
+        let mut result: Vec<(K, V)> = committed
+            .chain(written)
+            .filter(|(key, value)| predicate(key, value))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        result.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (key, _) in &result {
+            self.changes
+                .reads
+                .entry(key.clone())
+                .or_insert_with(|| self.table.version(key));
+        }
+        result
+    }
+
+    fn values(&mut self, predicate: impl Fn(&V) -> bool) -> Vec<V> {
+        self.entries(|_, value| predicate(value))
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    fn keys(&mut self, predicate: impl Fn(&K) -> bool) -> Vec<K> {
+        self.entries(|key, _| predicate(key))
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Tables {
+    hellos: Table<String, Hello>,
+    users: Table<String, User>,
+    tasks: Table<Uuid, Task>,
+    task_shares: Table<(Uuid, String), ()>,
+    task_dependencies: Table<(Uuid, Uuid), TaskDependency>,
+    tags: Table<Uuid, Tag>,
+    task_tags: Table<(Uuid, Uuid), ()>,
+    task_histories: Table<Uuid, TaskHistory>,
+    reminder_offsets: Table<(String, i32), ReminderOffset>,
+    fired_reminders: Table<(Uuid, OffsetDateTime, i32), FiredReminder>,
+}
+
+#[derive(Clone, Default)]
+struct Changes {
+    hellos: TableChanges<String, Hello>,
+    users: TableChanges<String, User>,
+    tasks: TableChanges<Uuid, Task>,
+    task_shares: TableChanges<(Uuid, String), ()>,
+    task_dependencies: TableChanges<(Uuid, Uuid), TaskDependency>,
+    tags: TableChanges<Uuid, Tag>,
+    task_tags: TableChanges<(Uuid, Uuid), ()>,
+    task_histories: TableChanges<Uuid, TaskHistory>,
+    reminder_offsets: TableChanges<(String, i32), ReminderOffset>,
+    fired_reminders: TableChanges<(Uuid, OffsetDateTime, i32), FiredReminder>,
+}
+
+impl Changes {
+    fn validate(&self, tables: &Tables) -> Result<(), CustomError> {
+        self.hellos.validate(&tables.hellos)?;
+        self.users.validate(&tables.users)?;
+        self.tasks.validate(&tables.tasks)?;
+        self.task_shares.validate(&tables.task_shares)?;
+        self.task_dependencies.validate(&tables.task_dependencies)?;
+        self.tags.validate(&tables.tags)?;
+        self.task_tags.validate(&tables.task_tags)?;
+        self.task_histories.validate(&tables.task_histories)?;
+        self.reminder_offsets.validate(&tables.reminder_offsets)?;
+        self.fired_reminders.validate(&tables.fired_reminders)
+    }
+
+    fn apply(self, tables: &mut Tables, version: u64) {
+        self.hellos.apply(&mut tables.hellos, version);
+        self.users.apply(&mut tables.users, version);
+        self.tasks.apply(&mut tables.tasks, version);
+        self.task_shares.apply(&mut tables.task_shares, version);
+        self.task_dependencies
+            .apply(&mut tables.task_dependencies, version);
+        self.tags.apply(&mut tables.tags, version);
+        self.task_tags.apply(&mut tables.task_tags, version);
+        self.task_histories
+            .apply(&mut tables.task_histories, version);
+        self.reminder_offsets
+            .apply(&mut tables.reminder_offsets, version);
+        self.fired_reminders
+            .apply(&mut tables.fired_reminders, version);
+    }
+}
+
+// 反映済みの行と、トランザクション内で書き込んだ行を合わせて読み書きする
+struct View<'a> {
+    tables: &'a Tables,
+    changes: &'a mut Changes,
+}
+
+impl<'a> View<'a> {
+    fn new(tables: &'a Tables, changes: &'a mut Changes) -> Self {
+        Self { tables, changes }
+    }
+
+    fn hellos(&mut self) -> TableView<'_, String, Hello> {
+        TableView {
+            table: &self.tables.hellos,
+            changes: &mut self.changes.hellos,
+        }
+    }
+
+    fn users(&mut self) -> TableView<'_, String, User> {
+        TableView {
+            table: &self.tables.users,
+            changes: &mut self.changes.users,
+        }
+    }
+
+    fn tasks(&mut self) -> TableView<'_, Uuid, Task> {
+        TableView {
+            table: &self.tables.tasks,
+            changes: &mut self.changes.tasks,
+        }
+    }
+
+    fn task_shares(&mut self) -> TableView<'_, (Uuid, String), ()> {
+        TableView {
+            table: &self.tables.task_shares,
+            changes: &mut self.changes.task_shares,
+        }
+    }
+
+    fn task_dependencies(&mut self) -> TableView<'_, (Uuid, Uuid), TaskDependency> {
+        TableView {
+            table: &self.tables.task_dependencies,
+            changes: &mut self.changes.task_dependencies,
+        }
+    }
+
+    fn tags(&mut self) -> TableView<'_, Uuid, Tag> {
+        TableView {
+            table: &self.tables.tags,
+            changes: &mut self.changes.tags,
+        }
+    }
+
+    fn task_tags(&mut self) -> TableView<'_, (Uuid, Uuid), ()> {
+        TableView {
+            table: &self.tables.task_tags,
+            changes: &mut self.changes.task_tags,
+        }
+    }
+
+    fn task_histories(&mut self) -> TableView<'_, Uuid, TaskHistory> {
+        TableView {
+            table: &self.tables.task_histories,
+            changes: &mut self.changes.task_histories,
+        }
+    }
+
+    fn reminder_offsets(&mut self) -> TableView<'_, (String, i32), ReminderOffset> {
+        TableView {
+            table: &self.tables.reminder_offsets,
+            changes: &mut self.changes.reminder_offsets,
+        }
+    }
+
+    fn fired_reminders(&mut self) -> TableView<'_, (Uuid, OffsetDateTime, i32), FiredReminder> {
+        TableView {
+            table: &self.tables.fired_reminders,
+            changes: &mut self.changes.fired_reminders,
+        }
+    }
+
+    fn require_user(&mut self, user_id: &str) -> Result<(), CustomError> {
+        if !self.users().contains(&user_id.to_string()) {
+            return Err(CustomError::ForeignKeyViolation(format!(
+                "user: {}",
+                user_id
+            )));
+        }
+        Ok(())
+    }
+
+    fn require_task(&mut self, task_id: Uuid) -> Result<(), CustomError> {
+        if !self.tasks().contains(&task_id) {
+            return Err(CustomError::ForeignKeyViolation(format!(
+                "task: {}",
+                task_id
+            )));
+        }
+        Ok(())
+    }
+
+    fn require_tag(&mut self, tag_id: Uuid) -> Result<(), CustomError> {
+        if !self.tags().contains(&tag_id) {
+            return Err(CustomError::ForeignKeyViolation(format!("tag: {}", tag_id)));
+        }
+        Ok(())
+    }
+
+    // マイグレーションでON DELETE CASCADEになっている行だけを一緒に削除する。履歴は残す。
+    // 子タスクの外部キーはNO ACTIONなので、子タスクが残っている場合はForeignKeyViolationを返す
+    fn purge_task(&mut self, id: Uuid) -> Result<(), CustomError> {
+        if !self.tasks().values(|t| t.parent_id == Some(id)).is_empty() {
+            return Err(CustomError::ForeignKeyViolation(format!(
+                "task: {} has child tasks",
+                id
+            )));
+        }
+        self.tasks().remove(&id);
+        for key in self.task_shares().keys(|(task_id, _)| *task_id == id) {
+            self.task_shares().remove(&key);
+        }
+        for key in self
+            .task_dependencies()
+            .keys(|(task_id, depends_on_id)| *task_id == id || *depends_on_id == id)
+        {
+            self.task_dependencies().remove(&key);
+        }
+        for key in self.task_tags().keys(|(task_id, _)| *task_id == id) {
+            self.task_tags().remove(&key);
+        }
+        for key in self
+            .fired_reminders()
+            .keys(|(task_id, _, _)| *task_id == id)
+        {
+            self.fired_reminders().remove(&key);
+        }
+        Ok(())
+    }
+
+    fn purge_tag(&mut self, id: Uuid) {
+        self.tags().remove(&id);
+        for key in self.task_tags().keys(|(_, tag_id)| *tag_id == id) {
+            self.task_tags().remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{repository::task::TaskRepositoryTrait, task::test_task};
+
+    fn create_test_user(id: &str) -> User {
+        User {
+            id: id.to_string(),
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn insert_user(session: &Session, id: &str) {
+        session
+            .write(|view| {
+                view.users().insert(id.to_string(), create_test_user(id));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_write_rolls_back_on_error() {
+        let session = Session::Store(InMemoryStore::new());
+        let result: Result<(), CustomError> = session.write(|view| {
+            view.users()
+                .insert("first".to_string(), create_test_user("first"));
+            view.require_user("unknown")
+        });
+        assert!(matches!(result, Err(CustomError::ForeignKeyViolation(_))));
+        assert!(!session.read(|view| view.users().contains(&"first".to_string())));
+    }
+
+    #[test]
+    fn test_transaction_is_applied_on_commit() {
+        let store = InMemoryStore::new();
+        let session = Session::Store(store.clone());
+        let transaction = Session::transaction(store);
+        insert_user(&transaction, "testuserid");
+
+        // commitするまでは他から見えない
+        assert!(!session.read(|view| view.users().contains(&"testuserid".to_string())));
+        transaction.commit().unwrap();
+        assert!(session.read(|view| view.users().contains(&"testuserid".to_string())));
+    }
+
+    #[test]
+    fn test_conflict_on_modified_row() {
+        let store = InMemoryStore::new();
+        let session = Session::Store(store.clone());
+        insert_user(&session, "testuserid");
+        let task = test_task();
+        session
+            .write(|view| {
+                view.tasks().insert(task.id, task.clone());
+                Ok(())
+            })
+            .unwrap();
+
+        let transaction = Session::transaction(store);
+        transaction
+            .write(|view| {
+                let mut task = view.tasks().get(&task.id).unwrap();
+                task.title = "from_transaction".to_string();
+                view.tasks().insert(task.id, task);
+                Ok(())
+            })
+            .unwrap();
+        // 読み込んだ行が他の書き込みで変更された
+        session
+            .write(|view| {
+                view.tasks().remove(&task.id);
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(matches!(
+            transaction.commit(),
+            Err(CustomError::TransactionConflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_no_conflict_on_other_rows() {
+        let store = InMemoryStore::new();
+        let session = Session::Store(store.clone());
+        insert_user(&session, "testuserid");
+
+        let transaction = Session::transaction(store);
+        transaction
+            .write(|view| {
+                view.require_user("testuserid")?;
+                view.users()
+                    .insert("first".to_string(), create_test_user("first"));
+                Ok(())
+            })
+            .unwrap();
+        // 同じテーブルでも、読み書きしていない行への書き込みは競合しない
+        insert_user(&session, "second");
+
+        transaction.commit().unwrap();
+        assert_eq!(session.read(|view| view.users().keys(|_| true)).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_purge_fails_with_child_task() {
+        let store = InMemoryStore::new();
+        insert_user(&Session::Store(store.clone()), "testuserid");
+        let tasks = task::InMemoryTaskPersistence::new(store);
+        let parent = test_task();
+        let child = Task {
+            parent_id: Some(parent.id),
+            ..test_task()
+        };
+        tasks.insert(parent.clone(), vec![]).await.unwrap();
+        tasks.insert(child.clone(), vec![]).await.unwrap();
+        tasks.add_dependency(child.id, parent.id).await.unwrap();
+
+        // Postgresと同じく、子タスクが残っている親タスクは削除できない
+        let result = tasks.purge(parent.id, vec![]).await;
+        assert!(matches!(result, Err(CustomError::ForeignKeyViolation(_))));
+        assert!(tasks.find(parent.id).await.is_ok());
+
+        // 依存関係はON DELETE CASCADEで一緒に削除される
+        tasks.purge(child.id, vec![]).await.unwrap();
+        assert!(tasks.find_prerequisites(child.id).await.unwrap().is_empty());
+        tasks.purge(parent.id, vec![]).await.unwrap();
+        assert!(matches!(
+            tasks.find(parent.id).await,
+            Err(CustomError::NotFound(_))
+        ));
+    }
+}
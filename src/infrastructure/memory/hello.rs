@@ -0,0 +1,40 @@
+use crate::{
+    domain::{hello::Hello, repository::hello::HelloRepositoryTrait},
+    error::CustomError,
+};
+
+use super::{InMemoryStore, Session};
+
+pub struct InMemoryHelloPersistence {
+    session: Session,
+}
+
+impl HelloRepositoryTrait for InMemoryHelloPersistence {
+    type Connection = InMemoryStore;
+
+    fn new(store: InMemoryStore) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            session: Session::Store(store),
+        }
+    }
+
+    async fn insert(&self, hello: Hello) -> Result<String, CustomError> {
+        self.session.write(|view| {
+            if view.hellos().contains(&hello.name) {
+                return Err(CustomError::Conflict(format!("key: {}", &hello.name)));
+            }
+            let name = hello.name.clone();
+            view.hellos().insert(name.clone(), hello);
+            Ok(name)
+        })
+    }
+
+    async fn find(&self, name: String) -> Result<Hello, CustomError> {
+        self.session
+            .read(|view| view.hellos().get(&name))
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &name)))
+    }
+}
@@ -0,0 +1,117 @@
+use std::cmp::Reverse;
+
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        reminder::{FiredReminder, ReminderOffset},
+        repository::reminder::ReminderRepositoryTrait,
+        task::{Task, TaskStatus},
+    },
+    error::CustomError,
+};
+
+use super::{InMemoryStore, Session};
+
+pub struct InMemoryReminderPersistence {
+    session: Session,
+}
+
+impl InMemoryReminderPersistence {
+    pub(super) fn with_session(session: Session) -> Self {
+        Self { session }
+    }
+}
+
+impl ReminderRepositoryTrait for InMemoryReminderPersistence {
+    type Connection = InMemoryStore;
+
+    fn new(store: InMemoryStore) -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_session(Session::Store(store))
+    }
+
+    async fn find_upcoming_tasks(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<Task>, CustomError> {
+        let mut result = self.session.read(|view| {
+            view.tasks().values(|t| {
+                t.due_date > from
+                    && t.due_date <= to
+                    && t.deleted_at.is_none()
+                    && matches!(t.status, TaskStatus::Todo | TaskStatus::InProgress)
+            })
+        });
+        result.sort_by_key(|t| t.due_date);
+        Ok(result)
+    }
+
+    async fn find_offsets(
+        &self,
+        user_ids: Vec<String>,
+    ) -> Result<Vec<ReminderOffset>, CustomError> {
+        let mut result = self.session.read(|view| {
+            view.reminder_offsets()
+                .values(|o| user_ids.contains(&o.user_id))
+        });
+        result.sort_by_key(|o| Reverse(o.offset_minutes));
+        Ok(result)
+    }
+
+    async fn replace_offsets(
+        &self,
+        user_id: String,
+        offsets: Vec<ReminderOffset>,
+    ) -> Result<(), CustomError> {
+        self.session.write(|view| {
+            for key in view.reminder_offsets().keys(|(id, _)| *id == user_id) {
+                view.reminder_offsets().remove(&key);
+            }
+            for offset in offsets {
+                view.require_user(&offset.user_id)?;
+                let key = (offset.user_id.clone(), offset.offset_minutes);
+                if view.reminder_offsets().contains(&key) {
+                    return Err(CustomError::Conflict(format!(
+                        "user: {}, offset: {}",
+                        &key.0, key.1
+                    )));
+                }
+                view.reminder_offsets().insert(key, offset);
+            }
+            Ok(())
+        })
+    }
+
+    async fn find_fired(&self, task_ids: Vec<Uuid>) -> Result<Vec<FiredReminder>, CustomError> {
+        Ok(self.session.read(|view| {
+            view.fired_reminders()
+                .values(|f| task_ids.contains(&f.task_id))
+        }))
+    }
+
+    async fn mark_fired(&self, fired: FiredReminder) -> Result<bool, CustomError> {
+        self.session.write(|view| {
+            view.require_task(fired.task_id)?;
+            let key = (fired.task_id, fired.due_date, fired.offset_minutes);
+            // 複数のワーカーが同時に送らないよう、記録できたものだけを送信対象にする
+            if view.fired_reminders().contains(&key) {
+                return Ok(false);
+            }
+            view.fired_reminders().insert(key, fired);
+            Ok(true)
+        })
+    }
+
+    async fn unmark_fired(&self, fired: FiredReminder) -> Result<(), CustomError> {
+        self.session.write(|view| {
+            view.fired_reminders()
+                .remove(&(fired.task_id, fired.due_date, fired.offset_minutes));
+            Ok(())
+        })
+    }
+}
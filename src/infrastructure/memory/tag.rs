@@ -0,0 +1,93 @@
+use uuid::Uuid;
+
+use crate::{
+    domain::{repository::tag::TagRepositoryTrait, tag::Tag},
+    error::CustomError,
+};
+
+use super::{InMemoryStore, Session, View};
+
+pub struct InMemoryTagPersistence {
+    session: Session,
+}
+
+impl InMemoryTagPersistence {
+    pub(super) fn with_session(session: Session) -> Self {
+        Self { session }
+    }
+}
+
+impl TagRepositoryTrait for InMemoryTagPersistence {
+    type Connection = InMemoryStore;
+
+    fn new(store: InMemoryStore) -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_session(Session::Store(store))
+    }
+
+    async fn insert(&self, tag: Tag) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            if view.tags().contains(&tag.id) {
+                return Err(CustomError::Conflict(format!("key: {}", &tag.id)));
+            }
+            view.require_user(&tag.user_id)?;
+            check_unique_name(view, &tag)?;
+            let id = tag.id;
+            view.tags().insert(id, tag);
+            Ok(id)
+        })
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Tag, CustomError> {
+        self.session
+            .read(|view| view.tags().get(&id))
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &id)))
+    }
+
+    async fn find_from_user_id(&self, user_id: String) -> Result<Vec<Tag>, CustomError> {
+        let mut result = self
+            .session
+            .read(|view| view.tags().values(|t| t.user_id == user_id));
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(result)
+    }
+
+    async fn update(&self, tag: Tag) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            if !view.tags().contains(&tag.id) {
+                return Err(CustomError::NotFound(format!("key: {}", &tag.id)));
+            }
+            view.require_user(&tag.user_id)?;
+            check_unique_name(view, &tag)?;
+            let id = tag.id;
+            view.tags().insert(id, tag);
+            Ok(id)
+        })
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            if !view.tags().contains(&id) {
+                return Err(CustomError::NotFound(format!("key: {}", &id)));
+            }
+            view.purge_tag(id);
+            Ok(id)
+        })
+    }
+}
+
+// (user_id, name)の一意制約
+fn check_unique_name(view: &mut View, tag: &Tag) -> Result<(), CustomError> {
+    let duplicated = view
+        .tags()
+        .values(|t| t.id != tag.id && t.user_id == tag.user_id && t.name == tag.name);
+    if !duplicated.is_empty() {
+        return Err(CustomError::Conflict(format!(
+            "user: {}, name: {}",
+            &tag.user_id, &tag.name
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,432 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        repository::task::TaskRepositoryTrait,
+        tag::Tag,
+        task::Task,
+        task_dependency::TaskDependency,
+        task_history::TaskHistory,
+        task_query::{CursorValue, SortOrder, TaskCursor, TaskQuery},
+    },
+    error::CustomError,
+};
+
+use super::{InMemoryStore, Session, View};
+
+pub struct InMemoryTaskPersistence {
+    session: Session,
+}
+
+impl InMemoryTaskPersistence {
+    pub(super) fn with_session(session: Session) -> Self {
+        Self { session }
+    }
+}
+
+impl TaskRepositoryTrait for InMemoryTaskPersistence {
+    type Connection = InMemoryStore;
+
+    fn new(store: InMemoryStore) -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_session(Session::Store(store))
+    }
+
+    async fn insert(&self, task: Task, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            let id = insert_task(view, task)?;
+            insert_histories(view, histories)?;
+            Ok(id)
+        })
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Task, CustomError> {
+        self.session
+            .read(|view| view.tasks().get(&id))
+            .filter(|t| t.deleted_at.is_none())
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &id)))
+    }
+
+    async fn find_with_deleted(&self, id: Uuid) -> Result<Task, CustomError> {
+        self.session
+            .read(|view| view.tasks().get(&id))
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &id)))
+    }
+
+    async fn find_from_user_id(&self, user_id: String) -> Result<Vec<Task>, CustomError> {
+        Ok(self.find_tasks(|t| t.user_id == user_id && t.deleted_at.is_none()))
+    }
+
+    async fn find_children(&self, parent_ids: Vec<Uuid>) -> Result<Vec<Task>, CustomError> {
+        Ok(self.find_tasks(|t| {
+            t.parent_id.is_some_and(|id| parent_ids.contains(&id)) && t.deleted_at.is_none()
+        }))
+    }
+
+    async fn has_children(&self, id: Uuid) -> Result<bool, CustomError> {
+        Ok(!self.find_tasks(|t| t.parent_id == Some(id)).is_empty())
+    }
+
+    async fn search(&self, query: TaskQuery, limit: u64) -> Result<Vec<Task>, CustomError> {
+        let mut filter = query.filter;
+        filter.tag_ids.sort();
+        filter.tag_ids.dedup();
+        // タイトルと説明文を大文字小文字を区別せずに部分一致検索する
+        let text = filter
+            .text
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_lowercase());
+
+        let mut result = self.session.read(|view| {
+            let mut tasks = view.tasks().values(|t| {
+                t.user_id == query.user_id
+                    && t.deleted_at.is_none()
+                    && filter.due_date_from.is_none_or(|from| t.due_date >= from)
+                    && filter.due_date_to.is_none_or(|to| t.due_date <= to)
+                    && filter.priority_min.is_none_or(|min| t.priority >= min)
+                    && filter.priority_max.is_none_or(|max| t.priority <= max)
+                    && (filter.statuses.is_empty() || filter.statuses.contains(&t.status))
+                    && text.as_ref().is_none_or(|text| {
+                        t.title.to_lowercase().contains(text)
+                            || t.description.to_lowercase().contains(text)
+                    })
+            });
+            tasks.retain(|t| {
+                filter
+                    .tag_ids
+                    .iter()
+                    .all(|tag_id| view.task_tags().contains(&(t.id, *tag_id)))
+            });
+            tasks
+        });
+
+        // ソートキーが同じ場合はIDで順序を確定させ、カーソル以降の行のみを取得する
+        let cursor_of = |t: &Task| TaskCursor::from_task(t, query.sort_key, query.order);
+        let compare = |a: &TaskCursor, b: &TaskCursor| {
+            let ordering = compare_cursor_value(&a.value, &b.value).then(a.id.cmp(&b.id));
+            match query.order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        };
+        if let Some(cursor) = query.cursor {
+            result.retain(|t| compare(&cursor_of(t), &cursor) == Ordering::Greater);
+        }
+        result.sort_by(|a, b| compare(&cursor_of(a), &cursor_of(b)));
+        result.truncate(limit as usize);
+        Ok(result)
+    }
+
+    async fn update(&self, task: Task, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            let id = update_versioned(view, task)?;
+            insert_histories(view, histories)?;
+            Ok(id)
+        })
+    }
+
+    async fn update_with_next_occurrence(
+        &self,
+        task: Task,
+        next_task: Task,
+        histories: Vec<TaskHistory>,
+    ) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            let id = update_versioned(view, task)?;
+            insert_task(view, next_task)?;
+            insert_histories(view, histories)?;
+            Ok(id)
+        })
+    }
+
+    async fn delete(&self, id: Uuid, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            let Some(task) = view.tasks().get(&id).filter(|t| t.deleted_at.is_none()) else {
+                return Err(CustomError::NotFound(format!("key: {}", &id)));
+            };
+            view.tasks().insert(
+                id,
+                Task {
+                    deleted_at: Some(OffsetDateTime::now_utc()),
+                    version: task.version + 1,
+                    ..task
+                },
+            );
+            insert_histories(view, histories)?;
+            Ok(id)
+        })
+    }
+
+    async fn restore(&self, id: Uuid, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            let Some(task) = view.tasks().get(&id).filter(|t| t.deleted_at.is_some()) else {
+                return Err(CustomError::NotFound(format!("key: {}", &id)));
+            };
+            view.tasks().insert(
+                id,
+                Task {
+                    deleted_at: None,
+                    version: task.version + 1,
+                    ..task
+                },
+            );
+            insert_histories(view, histories)?;
+            Ok(id)
+        })
+    }
+
+    async fn purge(&self, id: Uuid, histories: Vec<TaskHistory>) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            if !view.tasks().contains(&id) {
+                return Err(CustomError::NotFound(format!("key: {}", &id)));
+            }
+            view.purge_task(id)?;
+            insert_histories(view, histories)?;
+            Ok(id)
+        })
+    }
+
+    async fn is_shared_with(&self, id: Uuid, user_id: String) -> Result<bool, CustomError> {
+        Ok(self
+            .session
+            .read(|view| view.task_shares().contains(&(id, user_id))))
+    }
+
+    async fn find_shared_task_ids(&self, user_id: String) -> Result<Vec<Uuid>, CustomError> {
+        let shares = self
+            .session
+            .read(|view| view.task_shares().keys(|(_, id)| *id == user_id));
+        Ok(shares.into_iter().map(|(task_id, _)| task_id).collect())
+    }
+
+    async fn share(&self, id: Uuid, user_id: String) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            view.require_task(id)?;
+            view.require_user(&user_id)?;
+            let key = (id, user_id);
+            if view.task_shares().contains(&key) {
+                return Err(CustomError::Conflict(format!(
+                    "key: {}, user: {}",
+                    &id, &key.1
+                )));
+            }
+            view.task_shares().insert(key, ());
+            Ok(id)
+        })
+    }
+
+    async fn unshare(&self, id: Uuid, user_id: String) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            if view.task_shares().remove(&(id, user_id.clone())).is_none() {
+                return Err(CustomError::NotFound(format!(
+                    "key: {}, user: {}",
+                    &id, &user_id
+                )));
+            }
+            Ok(id)
+        })
+    }
+
+    async fn find_dependencies(
+        &self,
+        task_ids: Vec<Uuid>,
+    ) -> Result<Vec<TaskDependency>, CustomError> {
+        Ok(self.session.read(|view| {
+            view.task_dependencies()
+                .values(|d| task_ids.contains(&d.task_id))
+        }))
+    }
+
+    async fn find_prerequisites(&self, id: Uuid) -> Result<Vec<Task>, CustomError> {
+        Ok(self.session.read(|view| {
+            view.task_dependencies()
+                .values(|d| d.task_id == id)
+                .into_iter()
+                .filter_map(|d| view.tasks().get(&d.depends_on_id))
+                .filter(|t| t.deleted_at.is_none())
+                .collect()
+        }))
+    }
+
+    async fn add_dependency(&self, id: Uuid, depends_on_id: Uuid) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            view.require_task(id)?;
+            view.require_task(depends_on_id)?;
+            ensure_no_cycle(view, id, depends_on_id)?;
+            let key = (id, depends_on_id);
+            if view.task_dependencies().contains(&key) {
+                return Err(CustomError::Conflict(format!(
+                    "key: {}, depends_on: {}",
+                    &id, &depends_on_id
+                )));
+            }
+            view.task_dependencies().insert(
+                key,
+                TaskDependency {
+                    task_id: id,
+                    depends_on_id,
+                    created_at: OffsetDateTime::now_utc(),
+                },
+            );
+            Ok(id)
+        })
+    }
+
+    async fn remove_dependency(&self, id: Uuid, depends_on_id: Uuid) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            if view
+                .task_dependencies()
+                .remove(&(id, depends_on_id))
+                .is_none()
+            {
+                return Err(CustomError::NotFound(format!(
+                    "key: {}, depends_on: {}",
+                    &id, &depends_on_id
+                )));
+            }
+            Ok(id)
+        })
+    }
+
+    async fn find_tag(&self, tag_id: Uuid) -> Result<Tag, CustomError> {
+        self.session
+            .read(|view| view.tags().get(&tag_id))
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &tag_id)))
+    }
+
+    async fn attach_tag(&self, id: Uuid, tag_id: Uuid) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            view.require_task(id)?;
+            view.require_tag(tag_id)?;
+            if view.task_tags().contains(&(id, tag_id)) {
+                return Err(CustomError::Conflict(format!(
+                    "key: {}, tag: {}",
+                    &id, &tag_id
+                )));
+            }
+            view.task_tags().insert((id, tag_id), ());
+            Ok(id)
+        })
+    }
+
+    async fn detach_tag(&self, id: Uuid, tag_id: Uuid) -> Result<Uuid, CustomError> {
+        self.session.write(|view| {
+            if view.task_tags().remove(&(id, tag_id)).is_none() {
+                return Err(CustomError::NotFound(format!(
+                    "key: {}, tag: {}",
+                    &id, &tag_id
+                )));
+            }
+            Ok(id)
+        })
+    }
+
+    async fn find_history(&self, id: Uuid) -> Result<Vec<TaskHistory>, CustomError> {
+        let mut result = self
+            .session
+            .read(|view| view.task_histories().values(|h| h.task_id == id));
+        result.sort_by_key(|h| (h.created_at, h.id));
+        Ok(result)
+    }
+}
+
+impl InMemoryTaskPersistence {
+    // DBの一覧取得と同じく作成日時順に返す
+    fn find_tasks(&self, predicate: impl Fn(&Task) -> bool) -> Vec<Task> {
+        let mut result = self.session.read(|view| view.tasks().values(predicate));
+        result.sort_by_key(|t| (t.created_at, t.id));
+        result
+    }
+}
+
+fn compare_cursor_value(a: &CursorValue, b: &CursorValue) -> Ordering {
+    match (a, b) {
+        (CursorValue::DateTime(a), CursorValue::DateTime(b)) => a.cmp(b),
+        (CursorValue::Int(a), CursorValue::Int(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+    }
+}
+
+// 外部キーの参照先(ユーザーと親タスク)が存在するかを確認する
+fn check_task_references(view: &mut View, task: &Task) -> Result<(), CustomError> {
+    view.require_user(&task.user_id)?;
+    if let Some(parent_id) = task.parent_id {
+        view.require_task(parent_id)?;
+    }
+    Ok(())
+}
+
+fn insert_task(view: &mut View, task: Task) -> Result<Uuid, CustomError> {
+    if view.tasks().contains(&task.id) {
+        return Err(CustomError::Conflict(format!("key: {}", &task.id)));
+    }
+    check_task_references(view, &task)?;
+    let id = task.id;
+    view.tasks().insert(id, task);
+    Ok(id)
+}
+
+// task.versionは読み込んだ時点のバージョン。他で更新されていた場合は書き込まない
+fn update_versioned(view: &mut View, task: Task) -> Result<Uuid, CustomError> {
+    let id = task.id;
+    let expected_version = task.version;
+    let Some(current) = view.tasks().get(&id) else {
+        return Err(CustomError::NotFound(format!("key: {}", &id)));
+    };
+    if current.version != expected_version {
+        return Err(CustomError::VersionMismatch(format!(
+            "key: {}, expected version {}, found {}",
+            &id, expected_version, current.version
+        )));
+    }
+    check_task_references(view, &task)?;
+    view.tasks().insert(
+        id,
+        Task {
+            version: expected_version + 1,
+            ..task
+        },
+    );
+    Ok(id)
+}
+
+// 履歴はタスクを削除した後も残すため、タスクへの外部キーは持たない
+fn insert_histories(view: &mut View, histories: Vec<TaskHistory>) -> Result<(), CustomError> {
+    for history in histories {
+        if view.task_histories().contains(&history.id) {
+            return Err(CustomError::Conflict(format!("key: {}", &history.id)));
+        }
+        view.task_histories().insert(history.id, history);
+    }
+    Ok(())
+}
+
+// depends_on_idから依存先を辿ってidに到達する場合、依存を追加すると循環する
+fn ensure_no_cycle(view: &mut View, id: Uuid, depends_on_id: Uuid) -> Result<(), CustomError> {
+    let mut visited = HashSet::from([depends_on_id]);
+    let mut frontier = vec![depends_on_id];
+    while !frontier.is_empty() {
+        let mut next = Vec::new();
+        for dep in view
+            .task_dependencies()
+            .values(|d| frontier.contains(&d.task_id))
+        {
+            if dep.depends_on_id == id {
+                return Err(CustomError::DependencyCycle(format!(
+                    "task {} already depends on task {}",
+                    depends_on_id, id
+                )));
+            }
+            if visited.insert(dep.depends_on_id) {
+                next.push(dep.depends_on_id);
+            }
+        }
+        frontier = next;
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use crate::{
+    domain::repository::unit_of_work::{TransactionScopeTrait, UnitOfWorkTrait},
+    error::CustomError,
+};
+
+use super::{
+    reminder::InMemoryReminderPersistence, tag::InMemoryTagPersistence,
+    task::InMemoryTaskPersistence, user::InMemoryUserPersistence, InMemoryStore, Session,
+};
+
+pub struct InMemoryUnitOfWork {
+    store: InMemoryStore,
+}
+
+impl UnitOfWorkTrait for InMemoryUnitOfWork {
+    type Connection = InMemoryStore;
+    type Transaction = InMemoryTransactionScope;
+
+    fn new(store: InMemoryStore) -> Self
+    where
+        Self: Sized,
+    {
+        Self { store }
+    }
+
+    async fn begin(&self) -> Result<InMemoryTransactionScope, CustomError> {
+        let session = Session::transaction(self.store.clone());
+        Ok(InMemoryTransactionScope {
+            tasks: InMemoryTaskPersistence::with_session(session.clone()),
+            users: InMemoryUserPersistence::with_session(session.clone()),
+            tags: InMemoryTagPersistence::with_session(session.clone()),
+            reminders: InMemoryReminderPersistence::with_session(session.clone()),
+            session,
+        })
+    }
+}
+
+// 各リポジトリの書き込みはcommitするまでストアに反映しない。
+// 読み書きした行がその後に他の書き込みで変更されていた場合はTransactionConflictを返す
+pub struct InMemoryTransactionScope {
+    session: Session,
+    tasks: InMemoryTaskPersistence,
+    users: InMemoryUserPersistence,
+    tags: InMemoryTagPersistence,
+    reminders: InMemoryReminderPersistence,
+}
+
+impl TransactionScopeTrait for InMemoryTransactionScope {
+    type Tasks = InMemoryTaskPersistence;
+    type Users = InMemoryUserPersistence;
+    type Tags = InMemoryTagPersistence;
+    type Reminders = InMemoryReminderPersistence;
+
+    fn tasks(&self) -> &InMemoryTaskPersistence {
+        &self.tasks
+    }
+
+    fn users(&self) -> &InMemoryUserPersistence {
+        &self.users
+    }
+
+    fn tags(&self) -> &InMemoryTagPersistence {
+        &self.tags
+    }
+
+    fn reminders(&self) -> &InMemoryReminderPersistence {
+        &self.reminders
+    }
+
+    async fn commit(self) -> Result<(), CustomError> {
+        self.session.commit()
+    }
+}
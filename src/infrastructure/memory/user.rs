@@ -0,0 +1,55 @@
+use crate::{
+    domain::{repository::user::UserRepositoryTrait, user::User},
+    error::CustomError,
+};
+
+use super::{InMemoryStore, Session};
+
+pub struct InMemoryUserPersistence {
+    session: Session,
+}
+
+impl InMemoryUserPersistence {
+    pub(super) fn with_session(session: Session) -> Self {
+        Self { session }
+    }
+}
+
+impl UserRepositoryTrait for InMemoryUserPersistence {
+    type Connection = InMemoryStore;
+
+    fn new(store: InMemoryStore) -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_session(Session::Store(store))
+    }
+
+    async fn insert(&self, user: User) -> Result<String, CustomError> {
+        self.session.write(|view| {
+            if view.users().contains(&user.id) {
+                return Err(CustomError::Conflict(format!("key: {}", &user.id)));
+            }
+            let id = user.id.clone();
+            view.users().insert(id.clone(), user);
+            Ok(id)
+        })
+    }
+
+    async fn find(&self, id: String) -> Result<User, CustomError> {
+        self.session
+            .read(|view| view.users().get(&id))
+            .ok_or_else(|| CustomError::NotFound(format!("key: {}", &id)))
+    }
+
+    async fn update(&self, user: User) -> Result<String, CustomError> {
+        self.session.write(|view| {
+            if !view.users().contains(&user.id) {
+                return Err(CustomError::NotFound(format!("key: {}", &user.id)));
+            }
+            let id = user.id.clone();
+            view.users().insert(id.clone(), user);
+            Ok(id)
+        })
+    }
+}
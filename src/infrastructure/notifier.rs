@@ -0,0 +1,43 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    domain::{notifier::Notifier, reminder::Reminder},
+    error::CustomError,
+};
+
+// 送信先が用意されるまではログに出すだけにする
+#[derive(Clone, Default)]
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    async fn notify(&self, reminder: Reminder) -> Result<(), CustomError> {
+        log::info!(
+            "Reminder: task {} ({}) for user {} is due at {} ({} minutes before)",
+            reminder.task_id,
+            reminder.title,
+            reminder.user_id,
+            reminder.due_date,
+            reminder.offset_minutes
+        );
+        Ok(())
+    }
+}
+
+// 送信したリマインドをメモリに溜めておく。テストで送信内容を確認するために使う
+#[derive(Clone, Default)]
+pub struct InMemoryNotifier {
+    sent: Arc<Mutex<Vec<Reminder>>>,
+}
+
+impl InMemoryNotifier {
+    pub fn sent(&self) -> Vec<Reminder> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl Notifier for InMemoryNotifier {
+    async fn notify(&self, reminder: Reminder) -> Result<(), CustomError> {
+        self.sent.lock().unwrap().push(reminder);
+        Ok(())
+    }
+}
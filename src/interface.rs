@@ -1 +1,4 @@
+pub mod auth;
 pub mod handler;
+pub mod validation;
+pub mod worker;
@@ -0,0 +1,240 @@
+use std::{env, time::Duration};
+
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tonic::{service::Interceptor, Request, Status};
+
+use crate::error::CustomError;
+
+const AUTHORIZATION_HEADER: &str = "authorization";
+const BEARER_PREFIX: &str = "Bearer ";
+
+// トークンのroleクレーム。未指定の場合は一般ユーザー
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    User,
+    // タスクの完全削除など、管理者だけが行える操作を許可する
+    Admin,
+}
+
+// 認証済みのユーザー。インターセプタがリクエストのextensionsに入れる
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthUser {
+    pub user_id: String,
+    pub role: Role,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    exp: u64,
+    #[serde(default)]
+    role: Role,
+}
+
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl AuthInterceptor {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            decoding_key: DecodingKey::from_secret(secret),
+            validation: Validation::new(Algorithm::HS256),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let secret = env::var("AUTH_SECRET").expect("AUTH_SECRET must be set");
+        Self::new(secret.as_bytes())
+    }
+
+    fn authenticate<T>(&self, request: &Request<T>) -> Result<AuthUser, CustomError> {
+        let header = request
+            .metadata()
+            .get(AUTHORIZATION_HEADER)
+            .ok_or_else(|| CustomError::Unauthenticated("missing bearer token".to_string()))?;
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|v| v.strip_prefix(BEARER_PREFIX))
+            .ok_or_else(|| {
+                CustomError::Unauthenticated("malformed authorization header".to_string())
+            })?;
+
+        let claims = decode::<Claims>(token, &self.decoding_key, &self.validation)
+            .map_err(|err| CustomError::Unauthenticated(format!("invalid token: {}", err)))?
+            .claims;
+        if claims.sub.is_empty() {
+            return Err(CustomError::Unauthenticated(
+                "token has no subject".to_string(),
+            ));
+        }
+
+        Ok(AuthUser {
+            user_id: claims.sub,
+            role: claims.role,
+        })
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let user = self.authenticate(&request)?;
+        request.extensions_mut().insert(user);
+        Ok(request)
+    }
+}
+
+// インターセプタを通ったリクエストから認証済みユーザーを取り出す
+pub fn authenticated_user<T>(request: &Request<T>) -> Result<AuthUser, CustomError> {
+    request
+        .extensions()
+        .get::<AuthUser>()
+        .cloned()
+        .ok_or_else(|| CustomError::Unauthenticated("request is not authenticated".to_string()))
+}
+
+pub fn issue_token(
+    secret: &[u8],
+    user_id: &str,
+    expires_in: Duration,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    issue_token_with_role(secret, user_id, Role::User, expires_in)
+}
+
+pub fn issue_token_with_role(
+    secret: &[u8],
+    user_id: &str,
+    role: Role,
+    expires_in: Duration,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = Claims {
+        sub: user_id.to_string(),
+        exp: (OffsetDateTime::now_utc().unix_timestamp() as u64) + expires_in.as_secs(),
+        role,
+    };
+    encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(secret),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::Code;
+
+    use super::*;
+
+    const SECRET: &[u8] = b"test-secret";
+
+    fn request_with_token(token: &str) -> Request<()> {
+        let mut request = Request::new(());
+        request.metadata_mut().insert(
+            AUTHORIZATION_HEADER,
+            format!("{}{}", BEARER_PREFIX, token).parse().unwrap(),
+        );
+        request
+    }
+
+    #[test]
+    fn test_interceptor_injects_user() {
+        let token = issue_token(SECRET, "test_user", Duration::from_secs(60)).unwrap();
+        let mut interceptor = AuthInterceptor::new(SECRET);
+
+        let request = interceptor.call(request_with_token(&token)).unwrap();
+
+        assert_eq!(
+            authenticated_user(&request).unwrap(),
+            AuthUser {
+                user_id: "test_user".to_string(),
+                role: Role::User,
+            }
+        );
+    }
+
+    #[test]
+    fn test_interceptor_injects_admin_role() {
+        let token =
+            issue_token_with_role(SECRET, "admin", Role::Admin, Duration::from_secs(60)).unwrap();
+        let mut interceptor = AuthInterceptor::new(SECRET);
+
+        let request = interceptor.call(request_with_token(&token)).unwrap();
+
+        assert!(authenticated_user(&request).unwrap().is_admin());
+    }
+
+    #[test]
+    fn test_interceptor_rejects_missing_token() {
+        let mut interceptor = AuthInterceptor::new(SECRET);
+
+        let status = interceptor.call(Request::new(())).unwrap_err();
+
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_interceptor_rejects_malformed_header() {
+        let mut interceptor = AuthInterceptor::new(SECRET);
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert(AUTHORIZATION_HEADER, "Basic abc".parse().unwrap());
+
+        let status = interceptor.call(request).unwrap_err();
+
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_interceptor_rejects_wrong_secret() {
+        let token = issue_token(b"other-secret", "test_user", Duration::from_secs(60)).unwrap();
+        let mut interceptor = AuthInterceptor::new(SECRET);
+
+        let status = interceptor.call(request_with_token(&token)).unwrap_err();
+
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_interceptor_rejects_expired_token() {
+        let claims = Claims {
+            sub: "test_user".to_string(),
+            exp: (OffsetDateTime::now_utc().unix_timestamp() - 3600) as u64,
+            role: Role::User,
+        };
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(SECRET),
+        )
+        .unwrap();
+        let mut interceptor = AuthInterceptor::new(SECRET);
+
+        let status = interceptor.call(request_with_token(&token)).unwrap_err();
+
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_authenticated_user_without_interceptor() {
+        let request = Request::new(());
+
+        assert!(matches!(
+            authenticated_user(&request),
+            Err(CustomError::Unauthenticated(_))
+        ));
+    }
+}
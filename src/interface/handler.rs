@@ -1,3 +1,6 @@
 pub mod api;
 pub mod hello;
+pub mod reminder;
+pub mod tag;
 pub mod task;
+pub mod user;
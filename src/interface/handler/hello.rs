@@ -4,7 +4,10 @@ use gakusai2024_proto::api::{
 };
 use tonic::{Request, Response, Status};
 
-use crate::{domain::repository::hello::HelloRepositoryTrait, usecase::hello::HelloUsecaseTrait};
+use crate::{
+    domain::repository::hello::HelloRepositoryTrait, error::CustomError,
+    usecase::hello::HelloUsecaseTrait,
+};
 
 pub trait HelloHandlerTrait<HU, HR>
 where
@@ -53,7 +56,7 @@ where
         let hello = request
             .into_inner()
             .hello
-            .ok_or_else(|| Status::invalid_argument("Hello is required"))?;
+            .ok_or_else(|| CustomError::invalid_argument("hello", "Hello is required"))?;
 
         _ = self
             .usecase
@@ -0,0 +1,85 @@
+use gakusai2024_proto::api::{
+    reminder_service_server::ReminderService, GetReminderSettingsRequest,
+    GetReminderSettingsResponse, UpdateReminderSettingsRequest, UpdateReminderSettingsResponse,
+};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    domain::{notifier::Notifier, repository::reminder::ReminderRepositoryTrait},
+    interface::{auth::authenticated_user, validation::validate_reminder_offsets},
+    usecase::reminder::ReminderUsecaseTrait,
+};
+
+pub trait ReminderHandlerTrait<RU, RR, N>
+where
+    RU: ReminderUsecaseTrait<RR, N>,
+    RR: ReminderRepositoryTrait + 'static,
+    N: Notifier + 'static,
+{
+    fn new(usecase: Box<RU>) -> Self
+    where
+        Self: Sized;
+}
+
+pub struct ReminderHandler<RU, RR, N>
+where
+    RU: ReminderUsecaseTrait<RR, N>,
+    RR: ReminderRepositoryTrait + 'static,
+    N: Notifier + 'static,
+{
+    usecase: Box<RU>,
+    _phantom: std::marker::PhantomData<(RR, N)>,
+}
+
+impl<RU, RR, N> ReminderHandlerTrait<RU, RR, N> for ReminderHandler<RU, RR, N>
+where
+    RU: ReminderUsecaseTrait<RR, N>,
+    RR: ReminderRepositoryTrait,
+    N: Notifier,
+{
+    fn new(usecase: Box<RU>) -> Self {
+        Self {
+            usecase,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<RU, RR, N> ReminderService for ReminderHandler<RU, RR, N>
+where
+    RU: ReminderUsecaseTrait<RR, N> + 'static + Sync + Send,
+    RR: ReminderRepositoryTrait + Sync + Send + 'static,
+    N: Notifier + Sync + Send + 'static,
+{
+    async fn get_reminder_settings(
+        &self,
+        request: Request<GetReminderSettingsRequest>,
+    ) -> Result<Response<GetReminderSettingsResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+
+        let offset_minutes = self.usecase.get_offsets(user.user_id).await?;
+
+        Ok(Response::new(GetReminderSettingsResponse {
+            offset_minutes,
+        }))
+    }
+
+    async fn update_reminder_settings(
+        &self,
+        request: Request<UpdateReminderSettingsRequest>,
+    ) -> Result<Response<UpdateReminderSettingsResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let offsets = validate_reminder_offsets(request.into_inner().offset_minutes)?;
+
+        let offset_minutes = self.usecase.set_offsets(user.user_id, offsets).await?;
+
+        Ok(Response::new(UpdateReminderSettingsResponse {
+            offset_minutes,
+        }))
+    }
+}
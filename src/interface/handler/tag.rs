@@ -0,0 +1,172 @@
+use gakusai2024_proto::api::{
+    tag_service_server::TagService, CreateTagRequest, CreateTagResponse, DeleteTagRequest,
+    DeleteTagResponse, GetListTagsRequest, GetListTagsResponse, GetTagRequest, GetTagResponse,
+    Tag as ProtoTag, UpdateTagRequest, UpdateTagResponse,
+};
+use time::OffsetDateTime;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        repository::tag::TagRepositoryTrait,
+        tag::{Tag, TagExt},
+    },
+    interface::{
+        auth::authenticated_user,
+        validation::{parse_uuid, validate_tag_name},
+    },
+    usecase::tag::TagUsecaseTrait,
+};
+
+pub trait TagHandlerTrait<TU, TR>
+where
+    TU: TagUsecaseTrait<TR>,
+    TR: TagRepositoryTrait + 'static,
+{
+    fn new(usecase: Box<TU>) -> Self
+    where
+        Self: Sized;
+}
+
+pub struct TagHandler<TU, TR>
+where
+    TU: TagUsecaseTrait<TR>,
+    TR: TagRepositoryTrait + 'static,
+{
+    usecase: Box<TU>,
+    _phantom: std::marker::PhantomData<TR>,
+}
+
+impl<TU, TR> TagHandlerTrait<TU, TR> for TagHandler<TU, TR>
+where
+    TU: TagUsecaseTrait<TR>,
+    TR: TagRepositoryTrait,
+{
+    fn new(usecase: Box<TU>) -> Self {
+        Self {
+            usecase,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+fn to_proto_tag(tag: Tag) -> ProtoTag {
+    ProtoTag {
+        id: tag.id.to_string(),
+        name: tag.name,
+        created_at: Some(prost_types::Timestamp {
+            seconds: tag.created_at.unix_timestamp(),
+            nanos: tag.created_at.nanosecond() as i32,
+        }),
+        updated_at: Some(prost_types::Timestamp {
+            seconds: tag.updated_at.unix_timestamp(),
+            nanos: tag.updated_at.nanosecond() as i32,
+        }),
+    }
+}
+
+#[tonic::async_trait]
+impl<TU, TR> TagService for TagHandler<TU, TR>
+where
+    TU: TagUsecaseTrait<TR> + 'static + Sync + Send,
+    TR: TagRepositoryTrait + Sync + Send + 'static,
+{
+    async fn create_tag(
+        &self,
+        request: Request<CreateTagRequest>,
+    ) -> Result<Response<CreateTagResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let name = validate_tag_name("name", request.into_inner().name)?;
+
+        let now = OffsetDateTime::now_utc();
+        let tag_id = self
+            .usecase
+            .insert(
+                user.user_id.clone(),
+                Tag {
+                    id: Uuid::new_v4(),
+                    user_id: user.user_id,
+                    name,
+                    created_at: now,
+                    updated_at: now,
+                },
+            )
+            .await?;
+
+        Ok(Response::new(CreateTagResponse {
+            tag_id: tag_id.to_string(),
+        }))
+    }
+
+    async fn get_tag(
+        &self,
+        request: Request<GetTagRequest>,
+    ) -> Result<Response<GetTagResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("tag_id", &request.into_inner().tag_id)?;
+
+        let tag = self.usecase.find(user.user_id, uuid).await?;
+
+        Ok(Response::new(GetTagResponse {
+            tag: Some(to_proto_tag(tag)),
+        }))
+    }
+
+    async fn get_list_tags(
+        &self,
+        request: Request<GetListTagsRequest>,
+    ) -> Result<Response<GetListTagsResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+
+        let tags = self.usecase.find_from_user_id(user.user_id).await?;
+
+        Ok(Response::new(GetListTagsResponse {
+            tags: tags.into_iter().map(to_proto_tag).collect(),
+        }))
+    }
+
+    async fn update_tag(
+        &self,
+        request: Request<UpdateTagRequest>,
+    ) -> Result<Response<UpdateTagResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+        let uuid = parse_uuid("tag_id", &inner_request.tag_id)?;
+        let name = validate_tag_name("name", inner_request.name)?;
+
+        let existing_tag = self.usecase.find(user.user_id.clone(), uuid).await?;
+        let tag_id = self
+            .usecase
+            .update(user.user_id, existing_tag.update(Some(name)))
+            .await?;
+
+        Ok(Response::new(UpdateTagResponse {
+            tag_id: tag_id.to_string(),
+        }))
+    }
+
+    async fn delete_tag(
+        &self,
+        request: Request<DeleteTagRequest>,
+    ) -> Result<Response<DeleteTagResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("tag_id", &request.into_inner().tag_id)?;
+
+        let tag_id = self.usecase.delete(user.user_id, uuid).await?;
+
+        Ok(Response::new(DeleteTagResponse {
+            tag_id: tag_id.to_string(),
+        }))
+    }
+}
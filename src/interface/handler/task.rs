@@ -1,42 +1,77 @@
+use std::pin::Pin;
+
 use gakusai2024_proto::api::{
-    task_service_server::TaskService, CreateTaskRequest, CreateTaskResponse, GetListTasksRequest,
-    GetListTasksResponse, GetTaskRequest, GetTaskResponse, Task as ProtoTask, UpdateTaskRequest,
-    UpdateTaskResponse,
+    task_service_server::TaskService, AddTaskDependencyRequest, AddTaskDependencyResponse,
+    AttachTagRequest, AttachTagResponse, BatchCreateTasksRequest, BatchCreateTasksResponse,
+    BatchDeleteTasksRequest, BatchDeleteTasksResponse, BatchUpdateTasksRequest,
+    BatchUpdateTasksResponse, CompleteTaskRequest, CompleteTaskResponse, CreateTaskRequest,
+    CreateTaskResponse, DeleteTaskRequest, DeleteTaskResponse, DetachTagRequest, DetachTagResponse,
+    FieldChange as ProtoFieldChange, GeneratePlanRequest, GeneratePlanResponse,
+    GetListTasksRequest, GetListTasksResponse, GetNextTasksRequest, GetNextTasksResponse,
+    GetTaskHistoryRequest, GetTaskHistoryResponse, GetTaskRequest, GetTaskResponse,
+    GetTaskSubtreeRequest, GetTaskSubtreeResponse, GetTasksInDependencyOrderRequest,
+    GetTasksInDependencyOrderResponse, PlanDay as ProtoPlanDay, PlanEntry as ProtoPlanEntry,
+    PurgeTaskRequest, PurgeTaskResponse, RemoveTaskDependencyRequest, RemoveTaskDependencyResponse,
+    ReopenTaskRequest, ReopenTaskResponse, RestoreTaskRequest, RestoreTaskResponse,
+    ScoredTask as ProtoScoredTask, ShareTaskRequest, ShareTaskResponse, Task as ProtoTask,
+    TaskEventType as ProtoTaskEventType, TaskHistoryAction as ProtoTaskHistoryAction,
+    TaskHistoryEntry as ProtoTaskHistoryEntry, TaskNode as ProtoTaskNode,
+    TaskStatus as ProtoTaskStatus, UnshareTaskRequest, UnshareTaskResponse, UpdateTaskRequest,
+    UpdateTaskResponse, UpdateTaskStatusRequest, UpdateTaskStatusResponse, WatchTasksRequest,
+    WatchTasksResponse,
 };
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
 use tonic::{Request, Response, Status};
 use uuid::Uuid;
 
 use crate::{
     domain::{
-        repository::task::TaskRepositoryTrait,
-        task::{Task, TaskExt},
+        planner::PlanDay,
+        repository::{task::TaskRepositoryTrait, unit_of_work::UnitOfWorkTrait},
+        task::{Task, TaskStatus},
+        task_event::{TaskEvent, TaskEventKind},
+        task_history::{TaskHistory, TaskHistoryAction, TaskHistoryExt},
+        task_tree::TaskNode,
+    },
+    error::CustomError,
+    interface::auth::authenticated_user,
+    interface::validation::{
+        from_proto_status, parse_uuid, validate_batch, validate_batch_ids, validate_get_list_tasks,
+        validate_next_tasks_request, validate_plan_request, validate_task_request,
+        validate_unique_ids, validate_update_task_request, TaskInput,
     },
     usecase::task::TaskUsecaseTrait,
 };
 
-pub trait TaskHandlerTrait<TU, TR>
+const WATCH_BUFFER_SIZE: usize = 16;
+
+pub trait TaskHandlerTrait<TU, TR, UW>
 where
-    TU: TaskUsecaseTrait<TR>,
+    TU: TaskUsecaseTrait<TR, UW>,
     TR: TaskRepositoryTrait + 'static,
+    UW: UnitOfWorkTrait + 'static,
 {
     fn new(usecase: Box<TU>) -> Self
     where
         Self: Sized;
 }
 
-pub struct TaskHandler<TU, TR>
+pub struct TaskHandler<TU, TR, UW>
 where
-    TU: TaskUsecaseTrait<TR>,
+    TU: TaskUsecaseTrait<TR, UW>,
     TR: TaskRepositoryTrait + 'static,
+    UW: UnitOfWorkTrait + 'static,
 {
     usecase: Box<TU>,
-    _phantom: std::marker::PhantomData<TR>,
+    _phantom: std::marker::PhantomData<(TR, UW)>,
 }
 
-impl<TU, TR> TaskHandlerTrait<TU, TR> for TaskHandler<TU, TR>
+impl<TU, TR, UW> TaskHandlerTrait<TU, TR, UW> for TaskHandler<TU, TR, UW>
 where
-    TU: TaskUsecaseTrait<TR>,
+    TU: TaskUsecaseTrait<TR, UW>,
     TR: TaskRepositoryTrait,
+    UW: UnitOfWorkTrait,
 {
     fn new(usecase: Box<TU>) -> Self {
         Self {
@@ -46,38 +81,151 @@ where
     }
 }
 
+fn to_proto_timestamp(datetime: time::OffsetDateTime) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: datetime.unix_timestamp(),
+        nanos: datetime.nanosecond() as i32,
+    }
+}
+
+fn to_proto_event(event: TaskEvent) -> WatchTasksResponse {
+    let r#type = match event.kind {
+        TaskEventKind::Created => ProtoTaskEventType::Created,
+        TaskEventKind::Updated => ProtoTaskEventType::Updated,
+        TaskEventKind::Deleted => ProtoTaskEventType::Deleted,
+    };
+    WatchTasksResponse {
+        r#type: r#type as i32,
+        task: Some(to_proto_task(event.task)),
+    }
+}
+
+fn to_proto_history(history: TaskHistory) -> ProtoTaskHistoryEntry {
+    let action = match history.action {
+        TaskHistoryAction::Created => ProtoTaskHistoryAction::Created,
+        TaskHistoryAction::Updated => ProtoTaskHistoryAction::Updated,
+        TaskHistoryAction::Deleted => ProtoTaskHistoryAction::Deleted,
+        TaskHistoryAction::Restored => ProtoTaskHistoryAction::Restored,
+        TaskHistoryAction::Purged => ProtoTaskHistoryAction::Purged,
+    };
+    ProtoTaskHistoryEntry {
+        id: history.id.to_string(),
+        task_id: history.task_id.to_string(),
+        actor_id: history.actor_id.clone(),
+        action: action as i32,
+        changes: history
+            .changes()
+            .into_iter()
+            .map(|c| ProtoFieldChange {
+                field: c.field,
+                before: c.before,
+                after: c.after,
+            })
+            .collect(),
+        created_at: Some(to_proto_timestamp(history.created_at)),
+    }
+}
+
+fn to_proto_status(status: TaskStatus) -> ProtoTaskStatus {
+    match status {
+        TaskStatus::Todo => ProtoTaskStatus::Todo,
+        TaskStatus::InProgress => ProtoTaskStatus::InProgress,
+        TaskStatus::Done => ProtoTaskStatus::Done,
+        TaskStatus::Cancelled => ProtoTaskStatus::Cancelled,
+    }
+}
+
+fn new_task(user_id: String, task: TaskInput) -> Task {
+    Task {
+        id: Uuid::new_v4(),
+        title: task.title,
+        description: task.description.unwrap_or("none".to_string()),
+        due_date: task.due_date,
+        priority: task.priority,
+        weight: task.weight,
+        created_at: time::OffsetDateTime::now_utc(),
+        updated_at: time::OffsetDateTime::now_utc(),
+        deleted_at: None,
+        status: TaskStatus::Todo,
+        completed_at: None,
+        parent_id: task.parent_id,
+        recurrence: task.recurrence,
+        version: 1,
+        user_id,
+    }
+}
+
+fn to_proto_task(task: Task) -> ProtoTask {
+    ProtoTask {
+        id: task.id.to_string(),
+        title: task.title,
+        description: Some(task.description),
+        due_date: Some(to_proto_timestamp(task.due_date)),
+        priority: task.priority,
+        weight: task.weight,
+        created_at: Some(to_proto_timestamp(task.created_at)),
+        updated_at: Some(to_proto_timestamp(task.updated_at)),
+        user_id: task.user_id,
+        status: to_proto_status(task.status).into(),
+        completed_at: task.completed_at.map(to_proto_timestamp),
+        parent_id: task.parent_id.map(|id| id.to_string()),
+        recurrence: task.recurrence,
+        version: task.version,
+    }
+}
+
+fn to_proto_task_node(node: TaskNode) -> ProtoTaskNode {
+    let progress = node.progress();
+    ProtoTaskNode {
+        task: Some(to_proto_task(node.task)),
+        children: node.children.into_iter().map(to_proto_task_node).collect(),
+        total_weight: node.total_weight,
+        completed_weight: node.completed_weight,
+        progress,
+    }
+}
+
+fn to_proto_plan_day(day: PlanDay) -> ProtoPlanDay {
+    let scheduled_hours = day.scheduled_hours() as i32;
+    ProtoPlanDay {
+        date: Some(to_proto_timestamp(day.date.midnight().assume_utc())),
+        entries: day
+            .entries
+            .into_iter()
+            .map(|e| ProtoPlanEntry {
+                task_id: e.task_id.to_string(),
+                hours: e.hours as i32,
+            })
+            .collect(),
+        scheduled_hours,
+    }
+}
+
 #[tonic::async_trait]
-impl<TU, TR> TaskService for TaskHandler<TU, TR>
+impl<TU, TR, UW> TaskService for TaskHandler<TU, TR, UW>
 where
-    TU: TaskUsecaseTrait<TR> + 'static + Sync + Send,
+    TU: TaskUsecaseTrait<TR, UW> + 'static + Sync + Send,
     TR: TaskRepositoryTrait + Sync + Send + 'static,
+    UW: UnitOfWorkTrait + Sync + Send + 'static,
 {
+    type WatchTasksStream = Pin<Box<dyn Stream<Item = Result<WatchTasksResponse, Status>> + Send>>;
+
     async fn create_task(
         &self,
         request: Request<CreateTaskRequest>,
     ) -> Result<Response<CreateTaskResponse>, Status> {
         log::info!("Got a request: {:?}", request);
 
+        let user = authenticated_user(&request)?;
         let task = request
             .into_inner()
             .task_request
-            .ok_or_else(|| Status::invalid_argument("Task is required"))?;
+            .ok_or_else(|| CustomError::invalid_argument("task_request", "Task is required"))?;
+        let task = validate_task_request(task)?;
 
-        let uuid = Uuid::new_v4();
-        _ = self
+        let uuid = self
             .usecase
-            .insert(crate::domain::task::Task {
-                id: uuid, // 仮
-                title: task.title,
-                description: task.description.unwrap_or("none".to_string()),
-                due_date: time::OffsetDateTime::from_unix_timestamp(task.due_date.unwrap().seconds)
-                    .map_err(|_| Status::invalid_argument("Invalid timestamp"))?, //Copilotくん
-                priority: task.priority,
-                weight: task.weight,
-                created_at: time::OffsetDateTime::now_utc(),
-                updated_at: time::OffsetDateTime::now_utc(),
-                user_id: task.user_id,
-            })
+            .insert(user.user_id.clone(), new_task(user.user_id, task))
             .await?;
 
         Ok(Response::new(CreateTaskResponse {
@@ -91,31 +239,13 @@ where
     ) -> Result<Response<GetTaskResponse>, Status> {
         log::info!("Got a request: {:?}", request);
 
-        let id = Uuid::parse_str(request.into_inner().task_id.as_str()).unwrap();
+        let user = authenticated_user(&request)?;
+        let id = parse_uuid("task_id", &request.into_inner().task_id)?;
 
-        let task = self.usecase.find(id).await?;
+        let task = self.usecase.find(user.user_id, id).await?;
 
         Ok(Response::new(GetTaskResponse {
-            task: Some(ProtoTask {
-                id: task.id.to_string(),
-                title: task.title,
-                description: Some(task.description),
-                due_date: Some(prost_types::Timestamp {
-                    seconds: task.due_date.unix_timestamp(),
-                    nanos: task.due_date.nanosecond() as i32,
-                }),
-                priority: task.priority,
-                weight: task.weight,
-                created_at: Some(prost_types::Timestamp {
-                    seconds: task.created_at.unix_timestamp(),
-                    nanos: task.created_at.nanosecond() as i32,
-                }),
-                updated_at: Some(prost_types::Timestamp {
-                    seconds: task.updated_at.unix_timestamp(),
-                    nanos: task.updated_at.nanosecond() as i32,
-                }),
-                user_id: task.user_id,
-            }),
+            task: Some(to_proto_task(task)),
         }))
     }
 
@@ -125,34 +255,16 @@ where
     ) -> Result<Response<GetListTasksResponse>, Status> {
         log::info!("Got a request: {:?}", request);
 
-        let user_id = request.into_inner().user_id;
+        // リクエストのuser_idではなく、トークンのユーザーのタスクを返す
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
 
-        let tasks = self.usecase.find_from_user_id(user_id).await?;
+        let query = validate_get_list_tasks(user.user_id, inner_request)?;
+        let page = self.usecase.list(query).await?;
 
         Ok(Response::new(GetListTasksResponse {
-            tasks: tasks
-                .iter()
-                .map(|t| ProtoTask {
-                    id: t.id.to_string(),
-                    title: t.title.clone(),
-                    description: Some(t.description.clone()),
-                    due_date: Some(prost_types::Timestamp {
-                        seconds: t.due_date.unix_timestamp(),
-                        nanos: t.due_date.nanosecond() as i32,
-                    }),
-                    priority: t.priority,
-                    weight: t.weight,
-                    created_at: Some(prost_types::Timestamp {
-                        seconds: t.created_at.unix_timestamp(),
-                        nanos: t.created_at.nanosecond() as i32,
-                    }),
-                    updated_at: Some(prost_types::Timestamp {
-                        seconds: t.updated_at.unix_timestamp(),
-                        nanos: t.updated_at.nanosecond() as i32,
-                    }),
-                    user_id: t.user_id.clone(),
-                })
-                .collect(),
+            tasks: page.tasks.into_iter().map(to_proto_task).collect(),
+            next_page_token: page.next_cursor.map(|c| c.encode()).unwrap_or_default(),
         }))
     }
 
@@ -162,42 +274,440 @@ where
     ) -> Result<Response<UpdateTaskResponse>, Status> {
         log::info!("Got a request: {:?}", request);
 
-        let inner_request = request.into_inner();
+        let user = authenticated_user(&request)?;
+        let patch = validate_update_task_request(request.into_inner())?;
 
-        let task_request = inner_request
-            .task_update
-            .ok_or_else(|| Status::invalid_argument("Task is required"))?;
-
-        // タスクIDをパース
-        let uuid = Uuid::parse_str(inner_request.task_id.as_str())
-            .map_err(|_| Status::invalid_argument("Invalid task ID"))?;
-
-        // 既存のタスクを取得
-        let existing_task = self.usecase.find(uuid).await?;
-
-        // ProtoTaskRequest -> ドメイン Task 変換
-        let due_date = if let Some(ts) = task_request.due_date {
-            Some(
-                time::OffsetDateTime::from_unix_timestamp(ts.seconds)
-                    .map_err(|_| Status::invalid_argument("Invalid timestamp"))?,
-            )
-        } else {
-            None
-        };
-        let updated_task = existing_task.update(
-            task_request.title,
-            task_request.description,
-            task_request.user_id,
-            due_date,
-            task_request.priority,
-            task_request.weight,
-        );
-
-        // 更新処理
-        self.usecase.update(updated_task).await?;
+        // クライアントが読み込んだバージョンと異なる場合はAbortedを返す
+        let task = self.usecase.update(user.user_id, patch).await?;
 
         Ok(Response::new(UpdateTaskResponse {
+            task_id: task.id.to_string(),
+            version: task.version,
+        }))
+    }
+
+    async fn batch_create_tasks(
+        &self,
+        request: Request<BatchCreateTasksRequest>,
+    ) -> Result<Response<BatchCreateTasksResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inputs = validate_batch("requests", request.into_inner().requests, |request| {
+            let task = request
+                .task_request
+                .ok_or_else(|| CustomError::invalid_argument("task_request", "Task is required"))?;
+            validate_task_request(task)
+        })?;
+        let tasks = inputs
+            .into_iter()
+            .map(|input| new_task(user.user_id.clone(), input))
+            .collect();
+
+        let ids = self.usecase.batch_insert(user.user_id, tasks).await?;
+
+        Ok(Response::new(BatchCreateTasksResponse {
+            task_ids: ids.iter().map(|id| id.to_string()).collect(),
+        }))
+    }
+
+    async fn batch_update_tasks(
+        &self,
+        request: Request<BatchUpdateTasksRequest>,
+    ) -> Result<Response<BatchUpdateTasksResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let patches = validate_batch(
+            "requests",
+            request.into_inner().requests,
+            validate_update_task_request,
+        )?;
+        validate_unique_ids(patches.iter().map(|patch| patch.id), |i| {
+            format!("requests[{}].task_id", i)
+        })?;
+
+        let tasks = self.usecase.batch_update(user.user_id, patches).await?;
+
+        Ok(Response::new(BatchUpdateTasksResponse {
+            responses: tasks
+                .into_iter()
+                .map(|task| UpdateTaskResponse {
+                    task_id: task.id.to_string(),
+                    version: task.version,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn batch_delete_tasks(
+        &self,
+        request: Request<BatchDeleteTasksRequest>,
+    ) -> Result<Response<BatchDeleteTasksResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let ids = validate_batch_ids("task_ids", request.into_inner().task_ids)?;
+
+        let ids = self.usecase.batch_delete(user.user_id, ids).await?;
+
+        Ok(Response::new(BatchDeleteTasksResponse {
+            task_ids: ids.iter().map(|id| id.to_string()).collect(),
+        }))
+    }
+
+    async fn delete_task(
+        &self,
+        request: Request<DeleteTaskRequest>,
+    ) -> Result<Response<DeleteTaskResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("task_id", &request.into_inner().task_id)?;
+
+        self.usecase.delete(user.user_id, uuid).await?;
+
+        Ok(Response::new(DeleteTaskResponse {
+            task_id: uuid.to_string(),
+        }))
+    }
+
+    async fn restore_task(
+        &self,
+        request: Request<RestoreTaskRequest>,
+    ) -> Result<Response<RestoreTaskResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("task_id", &request.into_inner().task_id)?;
+
+        self.usecase.restore(user.user_id, uuid).await?;
+
+        Ok(Response::new(RestoreTaskResponse {
             task_id: uuid.to_string(),
         }))
     }
+
+    async fn purge_task(
+        &self,
+        request: Request<PurgeTaskRequest>,
+    ) -> Result<Response<PurgeTaskResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("task_id", &request.into_inner().task_id)?;
+
+        // 論理削除済みかどうかに関わらず物理削除する(管理者のみ)
+        let is_admin = user.is_admin();
+        self.usecase.purge(user.user_id, is_admin, uuid).await?;
+
+        Ok(Response::new(PurgeTaskResponse {
+            task_id: uuid.to_string(),
+        }))
+    }
+
+    async fn complete_task(
+        &self,
+        request: Request<CompleteTaskRequest>,
+    ) -> Result<Response<CompleteTaskResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("task_id", &request.into_inner().task_id)?;
+
+        let task = self.usecase.complete(user.user_id, uuid).await?;
+
+        Ok(Response::new(CompleteTaskResponse {
+            task: Some(to_proto_task(task)),
+        }))
+    }
+
+    async fn reopen_task(
+        &self,
+        request: Request<ReopenTaskRequest>,
+    ) -> Result<Response<ReopenTaskResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("task_id", &request.into_inner().task_id)?;
+
+        let task = self.usecase.reopen(user.user_id, uuid).await?;
+
+        Ok(Response::new(ReopenTaskResponse {
+            task: Some(to_proto_task(task)),
+        }))
+    }
+
+    async fn update_task_status(
+        &self,
+        request: Request<UpdateTaskStatusRequest>,
+    ) -> Result<Response<UpdateTaskStatusResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+
+        let uuid = parse_uuid("task_id", &inner_request.task_id)?;
+        let status = from_proto_status(inner_request.status)
+            .ok_or_else(|| CustomError::invalid_argument("status", "Invalid task status"))?;
+
+        let task = self
+            .usecase
+            .change_status(user.user_id, uuid, status)
+            .await?;
+
+        Ok(Response::new(UpdateTaskStatusResponse {
+            task: Some(to_proto_task(task)),
+        }))
+    }
+
+    async fn share_task(
+        &self,
+        request: Request<ShareTaskRequest>,
+    ) -> Result<Response<ShareTaskResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+        let uuid = parse_uuid("task_id", &inner_request.task_id)?;
+        if inner_request.user_id.is_empty() {
+            return Err(CustomError::invalid_argument("user_id", "must not be empty").into());
+        }
+
+        self.usecase
+            .share(user.user_id, uuid, inner_request.user_id)
+            .await?;
+
+        Ok(Response::new(ShareTaskResponse {
+            task_id: uuid.to_string(),
+        }))
+    }
+
+    async fn unshare_task(
+        &self,
+        request: Request<UnshareTaskRequest>,
+    ) -> Result<Response<UnshareTaskResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+        let uuid = parse_uuid("task_id", &inner_request.task_id)?;
+
+        self.usecase
+            .unshare(user.user_id, uuid, inner_request.user_id)
+            .await?;
+
+        Ok(Response::new(UnshareTaskResponse {
+            task_id: uuid.to_string(),
+        }))
+    }
+
+    async fn get_task_subtree(
+        &self,
+        request: Request<GetTaskSubtreeRequest>,
+    ) -> Result<Response<GetTaskSubtreeResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("task_id", &request.into_inner().task_id)?;
+
+        let node = self.usecase.subtree(user.user_id, uuid).await?;
+
+        Ok(Response::new(GetTaskSubtreeResponse {
+            root: Some(to_proto_task_node(node)),
+        }))
+    }
+
+    async fn add_task_dependency(
+        &self,
+        request: Request<AddTaskDependencyRequest>,
+    ) -> Result<Response<AddTaskDependencyResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+        let uuid = parse_uuid("task_id", &inner_request.task_id)?;
+        let depends_on_uuid = parse_uuid("depends_on_task_id", &inner_request.depends_on_task_id)?;
+
+        self.usecase
+            .add_dependency(user.user_id, uuid, depends_on_uuid)
+            .await?;
+
+        Ok(Response::new(AddTaskDependencyResponse {
+            task_id: uuid.to_string(),
+        }))
+    }
+
+    async fn remove_task_dependency(
+        &self,
+        request: Request<RemoveTaskDependencyRequest>,
+    ) -> Result<Response<RemoveTaskDependencyResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+        let uuid = parse_uuid("task_id", &inner_request.task_id)?;
+        let depends_on_uuid = parse_uuid("depends_on_task_id", &inner_request.depends_on_task_id)?;
+
+        self.usecase
+            .remove_dependency(user.user_id, uuid, depends_on_uuid)
+            .await?;
+
+        Ok(Response::new(RemoveTaskDependencyResponse {
+            task_id: uuid.to_string(),
+        }))
+    }
+
+    async fn get_tasks_in_dependency_order(
+        &self,
+        request: Request<GetTasksInDependencyOrderRequest>,
+    ) -> Result<Response<GetTasksInDependencyOrderResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let tasks = self.usecase.dependency_order(user.user_id).await?;
+
+        Ok(Response::new(GetTasksInDependencyOrderResponse {
+            tasks: tasks.into_iter().map(to_proto_task).collect(),
+        }))
+    }
+
+    async fn generate_plan(
+        &self,
+        request: Request<GeneratePlanRequest>,
+    ) -> Result<Response<GeneratePlanResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let options = validate_plan_request(request.into_inner())?;
+
+        let plan = self.usecase.plan(user.user_id, options).await?;
+
+        Ok(Response::new(GeneratePlanResponse {
+            days: plan.days.into_iter().map(to_proto_plan_day).collect(),
+            late_task_ids: plan.late_task_ids.iter().map(Uuid::to_string).collect(),
+            unscheduled_task_ids: plan
+                .unscheduled_task_ids
+                .iter()
+                .map(Uuid::to_string)
+                .collect(),
+        }))
+    }
+
+    async fn get_next_tasks(
+        &self,
+        request: Request<GetNextTasksRequest>,
+    ) -> Result<Response<GetNextTasksResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let input = validate_next_tasks_request(request.into_inner())?;
+
+        let tasks = self
+            .usecase
+            .next_tasks(user.user_id, input.limit, input.weights)
+            .await?;
+
+        Ok(Response::new(GetNextTasksResponse {
+            tasks: tasks
+                .into_iter()
+                .map(|s| ProtoScoredTask {
+                    task: Some(to_proto_task(s.task)),
+                    score: s.score,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn attach_tag(
+        &self,
+        request: Request<AttachTagRequest>,
+    ) -> Result<Response<AttachTagResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+        let uuid = parse_uuid("task_id", &inner_request.task_id)?;
+        let tag_uuid = parse_uuid("tag_id", &inner_request.tag_id)?;
+
+        self.usecase
+            .attach_tag(user.user_id, uuid, tag_uuid)
+            .await?;
+
+        Ok(Response::new(AttachTagResponse {
+            task_id: uuid.to_string(),
+        }))
+    }
+
+    async fn detach_tag(
+        &self,
+        request: Request<DetachTagRequest>,
+    ) -> Result<Response<DetachTagResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+        let uuid = parse_uuid("task_id", &inner_request.task_id)?;
+        let tag_uuid = parse_uuid("tag_id", &inner_request.tag_id)?;
+
+        self.usecase
+            .detach_tag(user.user_id, uuid, tag_uuid)
+            .await?;
+
+        Ok(Response::new(DetachTagResponse {
+            task_id: uuid.to_string(),
+        }))
+    }
+
+    async fn get_task_history(
+        &self,
+        request: Request<GetTaskHistoryRequest>,
+    ) -> Result<Response<GetTaskHistoryResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+        let uuid = parse_uuid("task_id", &request.into_inner().task_id)?;
+
+        let histories = self.usecase.history(user.user_id, uuid).await?;
+
+        Ok(Response::new(GetTaskHistoryResponse {
+            entries: histories.into_iter().map(to_proto_history).collect(),
+        }))
+    }
+
+    async fn watch_tasks(
+        &self,
+        request: Request<WatchTasksRequest>,
+    ) -> Result<Response<Self::WatchTasksStream>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let user = authenticated_user(&request)?;
+
+        let mut watch = self.usecase.watch(user.user_id).await?;
+        let (tx, rx) = mpsc::channel(WATCH_BUFFER_SIZE);
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    // クライアントが切断したら購読をやめる
+                    _ = tx.closed() => break,
+                    event = watch.recv() => event,
+                };
+                let message = match event {
+                    Ok(event) => Ok(to_proto_event(event)),
+                    // 取りこぼしたイベントは再送できないため、クライアントに再取得させる
+                    Err(RecvError::Lagged(skipped)) => Err(Status::aborted(format!(
+                        "missed {} task events; re-fetch tasks and watch again",
+                        skipped
+                    ))),
+                    Err(RecvError::Closed) => break,
+                };
+                let is_err = message.is_err();
+                if tx.send(message).await.is_err() || is_err {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
 }
@@ -0,0 +1,164 @@
+use gakusai2024_proto::api::{
+    user_service_server::UserService, CreateUserRequest, CreateUserResponse, GetListUsersRequest,
+    GetListUsersResponse, GetUserRequest, GetUserResponse, UpdateUserRequest, UpdateUserResponse,
+    User as ProtoUser,
+};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    domain::{
+        repository::user::UserRepositoryTrait,
+        user::{User, UserExt},
+    },
+    error::CustomError,
+    interface::{
+        auth::authenticated_user,
+        validation::{validate_user_request, validate_user_update},
+    },
+    usecase::user::UserUsecaseTrait,
+};
+
+pub trait UserHandlerTrait<UU, UR>
+where
+    UU: UserUsecaseTrait<UR>,
+    UR: UserRepositoryTrait + 'static,
+{
+    fn new(usecase: Box<UU>) -> Self
+    where
+        Self: Sized;
+}
+
+pub struct UserHandler<UU, UR>
+where
+    UU: UserUsecaseTrait<UR>,
+    UR: UserRepositoryTrait + 'static,
+{
+    usecase: Box<UU>,
+    _phantom: std::marker::PhantomData<UR>,
+}
+
+impl<UU, UR> UserHandlerTrait<UU, UR> for UserHandler<UU, UR>
+where
+    UU: UserUsecaseTrait<UR>,
+    UR: UserRepositoryTrait,
+{
+    fn new(usecase: Box<UU>) -> Self {
+        Self {
+            usecase,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+fn to_proto_user(user: User) -> ProtoUser {
+    ProtoUser {
+        id: user.id,
+        username: user.username,
+        email: user.email,
+        created_at: Some(prost_types::Timestamp {
+            seconds: user.created_at.unix_timestamp(),
+            nanos: user.created_at.nanosecond() as i32,
+        }),
+        updated_at: Some(prost_types::Timestamp {
+            seconds: user.updated_at.unix_timestamp(),
+            nanos: user.updated_at.nanosecond() as i32,
+        }),
+    }
+}
+
+#[tonic::async_trait]
+impl<UU, UR> UserService for UserHandler<UU, UR>
+where
+    UU: UserUsecaseTrait<UR> + 'static + Sync + Send,
+    UR: UserRepositoryTrait + Sync + Send + 'static,
+{
+    async fn create_user(
+        &self,
+        request: Request<CreateUserRequest>,
+    ) -> Result<Response<CreateUserResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let auth_user = authenticated_user(&request)?;
+        let user = request
+            .into_inner()
+            .user_request
+            .ok_or_else(|| CustomError::invalid_argument("user_request", "User is required"))?;
+        let user = validate_user_request(user)?;
+
+        // idを省略した場合はトークンのユーザーとして登録する。異なるidはusecaseで拒否する
+        let user_id = self
+            .usecase
+            .insert(
+                auth_user.user_id.clone(),
+                User {
+                    id: user.id.unwrap_or(auth_user.user_id),
+                    username: user.username,
+                    email: user.email,
+                    created_at: time::OffsetDateTime::now_utc(),
+                    updated_at: time::OffsetDateTime::now_utc(),
+                },
+            )
+            .await?;
+
+        Ok(Response::new(CreateUserResponse { user_id }))
+    }
+
+    async fn get_user(
+        &self,
+        request: Request<GetUserRequest>,
+    ) -> Result<Response<GetUserResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let auth_user = authenticated_user(&request)?;
+        let user = self
+            .usecase
+            .find(auth_user.user_id, request.into_inner().user_id)
+            .await?;
+
+        Ok(Response::new(GetUserResponse {
+            user: Some(to_proto_user(user)),
+        }))
+    }
+
+    async fn get_list_users(
+        &self,
+        request: Request<GetListUsersRequest>,
+    ) -> Result<Response<GetListUsersResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let auth_user = authenticated_user(&request)?;
+        let users = self.usecase.find_self(auth_user.user_id).await?;
+
+        Ok(Response::new(GetListUsersResponse {
+            users: users.into_iter().map(to_proto_user).collect(),
+        }))
+    }
+
+    async fn update_user(
+        &self,
+        request: Request<UpdateUserRequest>,
+    ) -> Result<Response<UpdateUserResponse>, Status> {
+        log::info!("Got a request: {:?}", request);
+
+        let auth_user = authenticated_user(&request)?;
+        let inner_request = request.into_inner();
+
+        let user_update = inner_request
+            .user_update
+            .ok_or_else(|| CustomError::invalid_argument("user_update", "User is required"))?;
+        let user_update = validate_user_update(user_update)?;
+
+        // 既存のユーザーを取得
+        let existing_user = self
+            .usecase
+            .find(auth_user.user_id.clone(), inner_request.user_id)
+            .await?;
+
+        let updated_user = existing_user.update(user_update.username, user_update.email);
+
+        // 更新処理
+        let user_id = self.usecase.update(auth_user.user_id, updated_user).await?;
+
+        Ok(Response::new(UpdateUserResponse { user_id }))
+    }
+}
@@ -0,0 +1,1319 @@
+use std::collections::HashSet;
+
+use gakusai2024_proto::api::{
+    GeneratePlanRequest, GetListTasksRequest, GetNextTasksRequest, TaskRequest,
+    TaskSortKey as ProtoTaskSortKey, TaskStatus as ProtoTaskStatus, TaskUpdate, UpdateTaskRequest,
+    UserRequest, UserUpdate,
+};
+use prost_types::FieldMask;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        planner::PlanOptions,
+        recurrence::RecurrenceRule,
+        reminder::MAX_OFFSET_MINUTES,
+        task::{TaskPatch, TaskStatus, PRIORITY_MAX, PRIORITY_MIN, WEIGHT_MAX, WEIGHT_MIN},
+        task_query::{
+            SortOrder, TaskCursor, TaskFilter, TaskQuery, TaskSortKey, DEFAULT_PAGE_SIZE,
+            MAX_PAGE_SIZE,
+        },
+        urgency::UrgencyWeights,
+    },
+    error::{CustomError, FieldViolation},
+};
+
+pub const TITLE_MAX_LEN: usize = 100;
+pub const DESCRIPTION_MAX_LEN: usize = 1000;
+pub const TAG_NAME_MAX_LEN: usize = 50;
+pub const USERNAME_MAX_LEN: usize = 50;
+pub const EMAIL_MAX_LEN: usize = 254;
+pub const PLAN_HOURS_PER_DAY_MAX: i32 = 24;
+pub const PLAN_DAYS_MAX: i32 = 90;
+pub const NEXT_TASKS_DEFAULT_LIMIT: i32 = 5;
+pub const NEXT_TASKS_MAX_LIMIT: i32 = 50;
+pub const REMINDER_OFFSETS_MAX_COUNT: usize = 10;
+pub const BATCH_MAX_SIZE: usize = 100;
+// update_maskで指定できるフィールド
+pub const TASK_UPDATE_MASK_PATHS: [&str; 5] =
+    ["title", "description", "due_date", "priority", "weight"];
+
+// 検証済みのタスク作成リクエスト
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskInput {
+    pub title: String,
+    pub description: Option<String>,
+    pub due_date: OffsetDateTime,
+    pub priority: i32,
+    pub weight: i32,
+    pub parent_id: Option<Uuid>,
+    pub recurrence: Option<String>,
+}
+
+// 検証済みのタスク更新リクエスト
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskUpdateInput {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub due_date: Option<OffsetDateTime>,
+    pub priority: Option<i32>,
+    pub weight: Option<i32>,
+}
+
+// 検証済みのユーザー作成リクエスト。idを省略した場合はNone
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserInput {
+    pub id: Option<String>,
+    pub username: String,
+    pub email: String,
+}
+
+// 検証済みのGetNextTasksリクエスト
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NextTasksInput {
+    pub limit: usize,
+    pub weights: UrgencyWeights,
+}
+
+// 違反をまとめて返すため、最初のエラーで止めずに全フィールドを検査する
+#[derive(Default)]
+struct Violations(Vec<FieldViolation>);
+
+impl Violations {
+    fn add(&mut self, field: &str, description: String) {
+        self.0.push(FieldViolation::new(field, description));
+    }
+
+    fn title(&mut self, field: &str, title: &str) {
+        if title.trim().is_empty() {
+            self.add(field, "must not be empty".to_string());
+        } else if title.chars().count() > TITLE_MAX_LEN {
+            self.add(
+                field,
+                format!("must be at most {} characters", TITLE_MAX_LEN),
+            );
+        }
+    }
+
+    fn description(&mut self, field: &str, description: &str) {
+        if description.chars().count() > DESCRIPTION_MAX_LEN {
+            self.add(
+                field,
+                format!("must be at most {} characters", DESCRIPTION_MAX_LEN),
+            );
+        }
+    }
+
+    fn username(&mut self, field: &str, username: &str) {
+        if username.trim().is_empty() {
+            self.add(field, "must not be empty".to_string());
+        } else if username.chars().count() > USERNAME_MAX_LEN {
+            self.add(
+                field,
+                format!("must be at most {} characters", USERNAME_MAX_LEN),
+            );
+        }
+    }
+
+    // 形式は@の前後が空でないことだけを確認する
+    fn email(&mut self, field: &str, email: &str) {
+        let valid = email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && !domain.is_empty() && !domain.contains('@')
+        }) && !email.chars().any(char::is_whitespace);
+        if !valid {
+            self.add(field, "must be a valid email address".to_string());
+        } else if email.chars().count() > EMAIL_MAX_LEN {
+            self.add(
+                field,
+                format!("must be at most {} characters", EMAIL_MAX_LEN),
+            );
+        }
+    }
+
+    fn range(&mut self, field: &str, value: i32, min: i32, max: i32) {
+        if !(min..=max).contains(&value) {
+            self.add(field, format!("must be between {} and {}", min, max));
+        }
+    }
+
+    fn non_negative(&mut self, field: &str, value: f64) {
+        if !value.is_finite() || value < 0.0 {
+            self.add(field, "must be a non-negative number".to_string());
+        }
+    }
+
+    fn timestamp(
+        &mut self,
+        field: &str,
+        timestamp: &prost_types::Timestamp,
+    ) -> Option<OffsetDateTime> {
+        let datetime = to_datetime(timestamp);
+        if datetime.is_none() {
+            self.add(field, "invalid timestamp".to_string());
+        }
+        datetime
+    }
+
+    // 正規化したルールを返す
+    fn recurrence(&mut self, field: &str, value: &str) -> Option<String> {
+        match value.parse::<RecurrenceRule>() {
+            Ok(rule) => Some(rule.to_string()),
+            Err(err) => {
+                self.add(field, err);
+                None
+            }
+        }
+    }
+
+    fn uuid(&mut self, field: &str, value: &str) -> Option<Uuid> {
+        let uuid = Uuid::parse_str(value).ok();
+        if uuid.is_none() {
+            self.add(field, "must be a valid UUID".to_string());
+        }
+        uuid
+    }
+
+    fn finish(self) -> Result<(), CustomError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(CustomError::InvalidArgument(self.0))
+        }
+    }
+}
+
+fn to_datetime(timestamp: &prost_types::Timestamp) -> Option<OffsetDateTime> {
+    if !(0..1_000_000_000).contains(&timestamp.nanos) {
+        return None;
+    }
+    OffsetDateTime::from_unix_timestamp_nanos(
+        timestamp.seconds as i128 * 1_000_000_000 + timestamp.nanos as i128,
+    )
+    .ok()
+}
+
+pub fn parse_uuid(field: &str, value: &str) -> Result<Uuid, CustomError> {
+    Uuid::parse_str(value).map_err(|_| CustomError::invalid_argument(field, "must be a valid UUID"))
+}
+
+pub fn from_proto_status(status: i32) -> Option<TaskStatus> {
+    match ProtoTaskStatus::try_from(status) {
+        Ok(ProtoTaskStatus::Todo) => Some(TaskStatus::Todo),
+        Ok(ProtoTaskStatus::InProgress) => Some(TaskStatus::InProgress),
+        Ok(ProtoTaskStatus::Done) => Some(TaskStatus::Done),
+        Ok(ProtoTaskStatus::Cancelled) => Some(TaskStatus::Cancelled),
+        _ => None,
+    }
+}
+
+// user_idは認証済みユーザーから決めるため、ここでは検証しない
+pub fn validate_task_request(request: TaskRequest) -> Result<TaskInput, CustomError> {
+    let mut violations = Violations::default();
+
+    violations.title("task_request.title", &request.title);
+    if let Some(description) = &request.description {
+        violations.description("task_request.description", description);
+    }
+    let due_date = match &request.due_date {
+        Some(ts) => violations.timestamp("task_request.due_date", ts),
+        None => {
+            violations.add("task_request.due_date", "is required".to_string());
+            None
+        }
+    };
+    violations.range(
+        "task_request.priority",
+        request.priority,
+        PRIORITY_MIN,
+        PRIORITY_MAX,
+    );
+    violations.range(
+        "task_request.weight",
+        request.weight,
+        WEIGHT_MIN,
+        WEIGHT_MAX,
+    );
+    let parent_id = request
+        .parent_id
+        .as_deref()
+        .and_then(|id| violations.uuid("task_request.parent_id", id));
+    let recurrence = request
+        .recurrence
+        .as_deref()
+        .filter(|r| !r.is_empty())
+        .and_then(|r| violations.recurrence("task_request.recurrence", r));
+    violations.finish()?;
+    let Some(due_date) = due_date else {
+        return Err(CustomError::invalid_argument(
+            "task_request.due_date",
+            "is required",
+        ));
+    };
+
+    Ok(TaskInput {
+        title: request.title,
+        description: request.description,
+        due_date,
+        priority: request.priority,
+        weight: request.weight,
+        parent_id,
+        recurrence,
+    })
+}
+
+// user_idは認証済みユーザー。page_sizeが0の場合はデフォルト件数を使う
+pub fn validate_get_list_tasks(
+    user_id: String,
+    request: GetListTasksRequest,
+) -> Result<TaskQuery, CustomError> {
+    let mut violations = Violations::default();
+
+    let filter = request.filter.unwrap_or_default();
+    let mut statuses = Vec::with_capacity(filter.statuses.len());
+    for (i, &status) in filter.statuses.iter().enumerate() {
+        match from_proto_status(status) {
+            Some(status) => statuses.push(status),
+            None => violations.add(
+                &format!("filter.statuses[{}]", i),
+                "invalid task status".to_string(),
+            ),
+        }
+    }
+    let tag_ids: Vec<Uuid> = filter
+        .tag_ids
+        .iter()
+        .enumerate()
+        .filter_map(|(i, id)| violations.uuid(&format!("filter.tag_ids[{}]", i), id))
+        .collect();
+    let due_date_from = filter
+        .due_date_from
+        .as_ref()
+        .and_then(|ts| violations.timestamp("filter.due_date_from", ts));
+    let due_date_to = filter
+        .due_date_to
+        .as_ref()
+        .and_then(|ts| violations.timestamp("filter.due_date_to", ts));
+    if let (Some(from), Some(to)) = (due_date_from, due_date_to) {
+        if from > to {
+            violations.add(
+                "filter.due_date_from",
+                "must not be after filter.due_date_to".to_string(),
+            );
+        }
+    }
+    if let Some(min) = filter.priority_min {
+        violations.range("filter.priority_min", min, PRIORITY_MIN, PRIORITY_MAX);
+    }
+    if let Some(max) = filter.priority_max {
+        violations.range("filter.priority_max", max, PRIORITY_MIN, PRIORITY_MAX);
+    }
+    if let (Some(min), Some(max)) = (filter.priority_min, filter.priority_max) {
+        if min > max {
+            violations.add(
+                "filter.priority_min",
+                "must not be greater than filter.priority_max".to_string(),
+            );
+        }
+    }
+
+    let sort_key = match ProtoTaskSortKey::try_from(request.sort_key) {
+        Ok(ProtoTaskSortKey::DueDate) => Some(TaskSortKey::DueDate),
+        Ok(ProtoTaskSortKey::Priority) => Some(TaskSortKey::Priority),
+        Ok(ProtoTaskSortKey::Weight) => Some(TaskSortKey::Weight),
+        Ok(ProtoTaskSortKey::CreatedAt) | Ok(ProtoTaskSortKey::Unspecified) => {
+            Some(TaskSortKey::CreatedAt)
+        }
+        Err(_) => {
+            violations.add("sort_key", "invalid sort key".to_string());
+            None
+        }
+    };
+    let order = if request.descending {
+        SortOrder::Desc
+    } else {
+        SortOrder::Asc
+    };
+
+    if request.page_size < 0 {
+        violations.add("page_size", "must not be negative".to_string());
+    }
+    let page_size = match request.page_size {
+        0 => DEFAULT_PAGE_SIZE,
+        n => (n.max(0) as u64).min(MAX_PAGE_SIZE),
+    };
+
+    // ページトークンは前回と同じソート条件でのみ有効
+    let cursor = if request.page_token.is_empty() {
+        None
+    } else {
+        let cursor = TaskCursor::decode(&request.page_token)
+            .filter(|c| Some(c.sort_key) == sort_key && c.order == order);
+        if cursor.is_none() {
+            violations.add("page_token", "invalid page token".to_string());
+        }
+        cursor
+    };
+    violations.finish()?;
+    let Some(sort_key) = sort_key else {
+        return Err(CustomError::invalid_argument(
+            "sort_key",
+            "invalid sort key",
+        ));
+    };
+
+    Ok(TaskQuery {
+        user_id,
+        filter: TaskFilter {
+            due_date_from,
+            due_date_to,
+            priority_min: filter.priority_min,
+            priority_max: filter.priority_max,
+            statuses,
+            text: filter.text,
+            tag_ids,
+        },
+        sort_key,
+        order,
+        page_size,
+        cursor,
+    })
+}
+
+// update_maskが指定された場合は、マスクに含まれるフィールドだけを書き込む
+pub fn validate_task_update(
+    update: TaskUpdate,
+    update_mask: Option<FieldMask>,
+) -> Result<TaskUpdateInput, CustomError> {
+    let mut violations = Violations::default();
+
+    let update = match update_mask {
+        Some(mask) if !mask.paths.is_empty() => {
+            apply_update_mask(update, &mask.paths, &mut violations)
+        }
+        _ => update,
+    };
+
+    if let Some(title) = &update.title {
+        violations.title("task_update.title", title);
+    }
+    if let Some(description) = &update.description {
+        violations.description("task_update.description", description);
+    }
+    let due_date = update
+        .due_date
+        .as_ref()
+        .and_then(|ts| violations.timestamp("task_update.due_date", ts));
+    if let Some(priority) = update.priority {
+        violations.range("task_update.priority", priority, PRIORITY_MIN, PRIORITY_MAX);
+    }
+    if let Some(weight) = update.weight {
+        violations.range("task_update.weight", weight, WEIGHT_MIN, WEIGHT_MAX);
+    }
+    violations.finish()?;
+
+    Ok(TaskUpdateInput {
+        title: update.title,
+        description: update.description,
+        due_date,
+        priority: update.priority,
+        weight: update.weight,
+    })
+}
+
+pub fn validate_update_task_request(request: UpdateTaskRequest) -> Result<TaskPatch, CustomError> {
+    // update_maskだけで説明を消す場合はtask_updateが無くてもよい
+    let has_mask = request
+        .update_mask
+        .as_ref()
+        .is_some_and(|mask| !mask.paths.is_empty());
+    let update = request
+        .task_update
+        .or_else(|| has_mask.then(TaskUpdate::default))
+        .ok_or_else(|| CustomError::invalid_argument("task_update", "Task is required"))?;
+    let update = validate_task_update(update, request.update_mask)?;
+
+    let id = parse_uuid("task_id", &request.task_id)?;
+    if request.expected_version < 1 {
+        return Err(CustomError::invalid_argument(
+            "expected_version",
+            "is required",
+        ));
+    }
+
+    Ok(TaskPatch {
+        id,
+        version: request.expected_version,
+        title: update.title,
+        description: update.description,
+        due_date: update.due_date,
+        priority: update.priority,
+        weight: update.weight,
+    })
+}
+
+pub fn validate_user_request(request: UserRequest) -> Result<UserInput, CustomError> {
+    let mut violations = Violations::default();
+
+    violations.username("user_request.username", &request.username);
+    violations.email("user_request.email", &request.email);
+    violations.finish()?;
+
+    Ok(UserInput {
+        id: Some(request.id).filter(|id| !id.is_empty()),
+        username: request.username,
+        email: request.email,
+    })
+}
+
+// 指定されたフィールドだけを検査する
+pub fn validate_user_update(update: UserUpdate) -> Result<UserUpdate, CustomError> {
+    let mut violations = Violations::default();
+
+    if let Some(username) = &update.username {
+        violations.username("user_update.username", username);
+    }
+    if let Some(email) = &update.email {
+        violations.email("user_update.email", email);
+    }
+    violations.finish()?;
+
+    Ok(update)
+}
+
+// 一括リクエストの各要素を検証する。違反したフィールドには要素の位置を付けてまとめて返す
+pub fn validate_batch<T, U>(
+    field: &str,
+    items: Vec<T>,
+    validate: impl Fn(T) -> Result<U, CustomError>,
+) -> Result<Vec<U>, CustomError> {
+    validate_batch_size(field, items.len())?;
+
+    let mut violations = Violations::default();
+    let mut values = Vec::with_capacity(items.len());
+    for (i, item) in items.into_iter().enumerate() {
+        match validate(item) {
+            Ok(value) => values.push(value),
+            Err(CustomError::InvalidArgument(item_violations)) => {
+                for v in item_violations {
+                    violations.add(&format!("{}[{}].{}", field, i, v.field), v.description);
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    violations.finish()?;
+
+    Ok(values)
+}
+
+// IDの一覧を検証する。違反は要素の位置を付けたフィールド名で返す
+pub fn validate_batch_ids(field: &str, ids: Vec<String>) -> Result<Vec<Uuid>, CustomError> {
+    validate_batch_size(field, ids.len())?;
+
+    let mut violations = Violations::default();
+    let mut seen = HashSet::new();
+    let mut values = Vec::with_capacity(ids.len());
+    for (i, id) in ids.iter().enumerate() {
+        let path = format!("{}[{}]", field, i);
+        match Uuid::parse_str(id) {
+            Ok(uuid) if !seen.insert(uuid) => {
+                violations.add(&path, "duplicate task id".to_string());
+            }
+            Ok(uuid) => values.push(uuid),
+            Err(_) => violations.add(&path, "must be a valid UUID".to_string()),
+        }
+    }
+    violations.finish()?;
+
+    Ok(values)
+}
+
+fn validate_batch_size(field: &str, len: usize) -> Result<(), CustomError> {
+    if len == 0 {
+        return Err(CustomError::invalid_argument(field, "must not be empty"));
+    }
+    if len > BATCH_MAX_SIZE {
+        return Err(CustomError::invalid_argument(
+            field,
+            format!("must contain at most {} items", BATCH_MAX_SIZE),
+        ));
+    }
+    Ok(())
+}
+
+// 同じタスクを1つのバッチで複数回操作することはできない
+pub fn validate_unique_ids(
+    ids: impl IntoIterator<Item = Uuid>,
+    field: impl Fn(usize) -> String,
+) -> Result<(), CustomError> {
+    let mut violations = Violations::default();
+    let mut seen = HashSet::new();
+    for (i, id) in ids.into_iter().enumerate() {
+        if !seen.insert(id) {
+            violations.add(&field(i), "duplicate task id".to_string());
+        }
+    }
+    violations.finish()
+}
+
+fn apply_update_mask(
+    update: TaskUpdate,
+    paths: &[String],
+    violations: &mut Violations,
+) -> TaskUpdate {
+    for path in paths {
+        if !TASK_UPDATE_MASK_PATHS.contains(&path.as_str()) {
+            violations.add("update_mask.paths", format!("unknown field: {}", path));
+        }
+    }
+    let masked = |field: &str| paths.iter().any(|path| path == field);
+    // 説明以外のフィールドは空にできないので、マスクに含めるなら値が必要
+    for (field, present) in [
+        ("title", update.title.is_some()),
+        ("due_date", update.due_date.is_some()),
+        ("priority", update.priority.is_some()),
+        ("weight", update.weight.is_some()),
+    ] {
+        if masked(field) && !present {
+            violations.add(
+                &format!("task_update.{}", field),
+                "must be set when included in update_mask".to_string(),
+            );
+        }
+    }
+
+    TaskUpdate {
+        title: update.title.filter(|_| masked("title")),
+        // マスクに含まれていて値が無い場合は説明を消す
+        description: masked("description").then(|| update.description.unwrap_or_default()),
+        due_date: update.due_date.filter(|_| masked("due_date")),
+        priority: update.priority.filter(|_| masked("priority")),
+        weight: update.weight.filter(|_| masked("weight")),
+        user_id: update.user_id,
+    }
+}
+
+pub fn validate_tag_name(field: &str, name: String) -> Result<String, CustomError> {
+    if name.trim().is_empty() {
+        return Err(CustomError::invalid_argument(field, "must not be empty"));
+    }
+    if name.chars().count() > TAG_NAME_MAX_LEN {
+        return Err(CustomError::invalid_argument(
+            field,
+            format!("must be at most {} characters", TAG_NAME_MAX_LEN),
+        ));
+    }
+    Ok(name)
+}
+
+// start_dateが無い場合は今日(UTC)から計画する
+pub fn validate_plan_request(request: GeneratePlanRequest) -> Result<PlanOptions, CustomError> {
+    let mut violations = Violations::default();
+
+    violations.range(
+        "hours_per_day",
+        request.hours_per_day,
+        1,
+        PLAN_HOURS_PER_DAY_MAX,
+    );
+    violations.range("days", request.days, 1, PLAN_DAYS_MAX);
+    let start_date = request
+        .start_date
+        .as_ref()
+        .and_then(|ts| violations.timestamp("start_date", ts));
+    violations.finish()?;
+
+    Ok(PlanOptions {
+        start_date: start_date.unwrap_or_else(OffsetDateTime::now_utc).date(),
+        hours_per_day: request.hours_per_day as u32,
+        days: request.days as u32,
+    })
+}
+
+// limitが0の場合はデフォルト件数、weightsが無い場合はデフォルトの重みを使う
+pub fn validate_next_tasks_request(
+    request: GetNextTasksRequest,
+) -> Result<NextTasksInput, CustomError> {
+    let mut violations = Violations::default();
+
+    let limit = if request.limit == 0 {
+        NEXT_TASKS_DEFAULT_LIMIT
+    } else {
+        request.limit
+    };
+    violations.range("limit", limit, 1, NEXT_TASKS_MAX_LIMIT);
+    let weights = match request.weights {
+        Some(w) => {
+            violations.non_negative("weights.priority", w.priority);
+            violations.non_negative("weights.effort", w.effort);
+            violations.non_negative("weights.due_date", w.due_date);
+            violations.non_negative("weights.age", w.age);
+            // 個々の重みが有限でも、合計がオーバーフローするとスコアがNaNになる
+            let total = w.priority + w.effort + w.due_date + w.age;
+            let all_finite = [w.priority, w.effort, w.due_date, w.age]
+                .iter()
+                .all(|v| v.is_finite());
+            if total <= 0.0 {
+                violations.add(
+                    "weights",
+                    "at least one weight must be positive".to_string(),
+                );
+            } else if all_finite && !total.is_finite() {
+                violations.add("weights", "sum of weights must be finite".to_string());
+            }
+            UrgencyWeights {
+                priority: w.priority,
+                effort: w.effort,
+                due_date: w.due_date,
+                age: w.age,
+            }
+        }
+        None => UrgencyWeights::default(),
+    };
+    violations.finish()?;
+
+    Ok(NextTasksInput {
+        limit: limit as usize,
+        weights,
+    })
+}
+
+pub fn validate_reminder_offsets(offsets: Vec<i32>) -> Result<Vec<i32>, CustomError> {
+    let mut violations = Violations::default();
+
+    if offsets.len() > REMINDER_OFFSETS_MAX_COUNT {
+        violations.add(
+            "offset_minutes",
+            format!("must have at most {} items", REMINDER_OFFSETS_MAX_COUNT),
+        );
+    }
+    for (i, &offset) in offsets.iter().enumerate() {
+        violations.range(
+            &format!("offset_minutes[{}]", i),
+            offset,
+            1,
+            MAX_OFFSET_MINUTES,
+        );
+    }
+    violations.finish()?;
+
+    Ok(offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_request() -> TaskRequest {
+        TaskRequest {
+            title: "title".to_string(),
+            description: Some("description".to_string()),
+            due_date: Some(prost_types::Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            }),
+            priority: 1,
+            weight: 1,
+            user_id: "user".to_string(),
+            parent_id: None,
+            recurrence: None,
+        }
+    }
+
+    fn violated_fields(err: CustomError) -> Vec<String> {
+        match err {
+            CustomError::InvalidArgument(violations) => {
+                violations.into_iter().map(|v| v.field).collect()
+            }
+            err => panic!("Unexpected error type: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_validate_task_request() {
+        let input = validate_task_request(valid_request()).unwrap();
+
+        assert_eq!(input.title, "title");
+        assert_eq!(input.due_date.unix_timestamp(), 1_700_000_000);
+        assert_eq!(input.recurrence, None);
+    }
+
+    #[test]
+    fn test_validate_task_request_normalizes_recurrence() {
+        let request = TaskRequest {
+            recurrence: Some("freq=weekly;byday=fr,mo".to_string()),
+            ..valid_request()
+        };
+
+        let input = validate_task_request(request).unwrap();
+
+        assert_eq!(input.recurrence.as_deref(), Some("FREQ=WEEKLY;BYDAY=MO,FR"));
+    }
+
+    #[test]
+    fn test_validate_task_request_collects_all_violations() {
+        let request = TaskRequest {
+            title: "  ".to_string(),
+            description: Some("a".repeat(DESCRIPTION_MAX_LEN + 1)),
+            due_date: None,
+            priority: 0,
+            weight: WEIGHT_MAX + 1,
+            user_id: String::new(),
+            parent_id: Some("not-a-uuid".to_string()),
+            recurrence: Some("FREQ=YEARLY".to_string()),
+        };
+
+        let err = validate_task_request(request).unwrap_err();
+
+        assert_eq!(
+            violated_fields(err),
+            vec![
+                "task_request.title",
+                "task_request.description",
+                "task_request.due_date",
+                "task_request.priority",
+                "task_request.weight",
+                "task_request.parent_id",
+                "task_request.recurrence",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_task_request_invalid_timestamp() {
+        let request = TaskRequest {
+            due_date: Some(prost_types::Timestamp {
+                seconds: i64::MAX,
+                nanos: 0,
+            }),
+            ..valid_request()
+        };
+
+        let err = validate_task_request(request).unwrap_err();
+
+        assert_eq!(violated_fields(err), vec!["task_request.due_date"]);
+    }
+
+    #[test]
+    fn test_validate_task_request_title_length() {
+        let request = TaskRequest {
+            title: "あ".repeat(TITLE_MAX_LEN),
+            ..valid_request()
+        };
+        assert!(validate_task_request(request).is_ok());
+
+        let request = TaskRequest {
+            title: "あ".repeat(TITLE_MAX_LEN + 1),
+            ..valid_request()
+        };
+        let err = validate_task_request(request).unwrap_err();
+        assert_eq!(violated_fields(err), vec!["task_request.title"]);
+    }
+
+    #[test]
+    fn test_validate_get_list_tasks() {
+        let token = TaskCursor::from_task(
+            &crate::domain::task::test_task(),
+            TaskSortKey::Priority,
+            SortOrder::Desc,
+        )
+        .encode();
+        let query = validate_get_list_tasks(
+            "user".to_string(),
+            GetListTasksRequest {
+                filter: Some(gakusai2024_proto::api::TaskFilter {
+                    statuses: vec![ProtoTaskStatus::Done.into()],
+                    ..Default::default()
+                }),
+                sort_key: ProtoTaskSortKey::Priority.into(),
+                descending: true,
+                page_token: token,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(query.user_id, "user");
+        assert_eq!(query.filter.statuses, vec![TaskStatus::Done]);
+        assert_eq!(query.sort_key, TaskSortKey::Priority);
+        assert_eq!(query.page_size, DEFAULT_PAGE_SIZE);
+        assert!(query.cursor.is_some());
+    }
+
+    #[test]
+    fn test_validate_get_list_tasks_collects_all_violations() {
+        // 別のソート条件で発行されたページトークンは使えない
+        let token = TaskCursor::from_task(
+            &crate::domain::task::test_task(),
+            TaskSortKey::Priority,
+            SortOrder::Asc,
+        )
+        .encode();
+        let err = validate_get_list_tasks(
+            "user".to_string(),
+            GetListTasksRequest {
+                filter: Some(gakusai2024_proto::api::TaskFilter {
+                    statuses: vec![ProtoTaskStatus::Todo.into(), 99],
+                    tag_ids: vec!["invalid".to_string()],
+                    due_date_from: Some(prost_types::Timestamp {
+                        seconds: 0,
+                        nanos: -1,
+                    }),
+                    ..Default::default()
+                }),
+                page_size: -1,
+                page_token: token,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            violated_fields(err),
+            vec![
+                "filter.statuses[1]",
+                "filter.tag_ids[0]",
+                "filter.due_date_from",
+                "page_size",
+                "page_token",
+            ]
+        );
+
+        let err = validate_get_list_tasks(
+            "user".to_string(),
+            GetListTasksRequest {
+                sort_key: 99,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(violated_fields(err), vec!["sort_key"]);
+
+        let err = validate_get_list_tasks(
+            "user".to_string(),
+            GetListTasksRequest {
+                filter: Some(gakusai2024_proto::api::TaskFilter {
+                    due_date_from: Some(prost_types::Timestamp {
+                        seconds: 1_700_000_000,
+                        nanos: 0,
+                    }),
+                    due_date_to: Some(prost_types::Timestamp {
+                        seconds: 1_600_000_000,
+                        nanos: 0,
+                    }),
+                    priority_min: Some(PRIORITY_MIN - 8),
+                    priority_max: Some(PRIORITY_MAX + 1),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            violated_fields(err),
+            vec![
+                "filter.due_date_from",
+                "filter.priority_min",
+                "filter.priority_max",
+            ]
+        );
+
+        // 範囲内の値でも下限が上限を超えている場合は拒否する
+        let err = validate_get_list_tasks(
+            "user".to_string(),
+            GetListTasksRequest {
+                filter: Some(gakusai2024_proto::api::TaskFilter {
+                    priority_min: Some(PRIORITY_MAX),
+                    priority_max: Some(PRIORITY_MIN),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(violated_fields(err), vec!["filter.priority_min"]);
+    }
+
+    #[test]
+    fn test_validate_task_update() {
+        let update = TaskUpdate {
+            title: None,
+            description: Some(String::new()),
+            due_date: None,
+            priority: Some(PRIORITY_MAX),
+            weight: None,
+            user_id: None,
+        };
+
+        let input = validate_task_update(update, None).unwrap();
+
+        assert_eq!(input.description, Some(String::new()));
+        assert_eq!(input.priority, Some(PRIORITY_MAX));
+        assert_eq!(input.due_date, None);
+    }
+
+    #[test]
+    fn test_validate_task_update_violations() {
+        let update = TaskUpdate {
+            title: Some(String::new()),
+            description: None,
+            due_date: Some(prost_types::Timestamp {
+                seconds: 0,
+                nanos: -1,
+            }),
+            priority: Some(PRIORITY_MAX + 1),
+            weight: Some(0),
+            user_id: Some(String::new()),
+        };
+
+        let err = validate_task_update(update, None).unwrap_err();
+
+        assert_eq!(
+            violated_fields(err),
+            vec![
+                "task_update.title",
+                "task_update.due_date",
+                "task_update.priority",
+                "task_update.weight",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_task_update_with_mask() {
+        let update = TaskUpdate {
+            title: Some("new_title".to_string()),
+            description: None,
+            due_date: None,
+            priority: Some(PRIORITY_MAX),
+            weight: None,
+            user_id: None,
+        };
+        let mask = FieldMask {
+            paths: vec!["title".to_string(), "description".to_string()],
+        };
+
+        let input = validate_task_update(update, Some(mask)).unwrap();
+
+        // マスクに無いpriorityは書き込まず、値の無いdescriptionは消す
+        assert_eq!(input.title, Some("new_title".to_string()));
+        assert_eq!(input.description, Some(String::new()));
+        assert_eq!(input.priority, None);
+    }
+
+    #[test]
+    fn test_validate_task_update_with_empty_mask() {
+        let update = TaskUpdate {
+            priority: Some(PRIORITY_MAX),
+            ..Default::default()
+        };
+
+        let input = validate_task_update(update, Some(FieldMask::default())).unwrap();
+
+        assert_eq!(input.priority, Some(PRIORITY_MAX));
+        assert_eq!(input.description, None);
+    }
+
+    #[test]
+    fn test_validate_task_update_mask_violations() {
+        let mask = FieldMask {
+            paths: vec![
+                "title".to_string(),
+                "weight".to_string(),
+                "user_id".to_string(),
+            ],
+        };
+
+        let err = validate_task_update(TaskUpdate::default(), Some(mask)).unwrap_err();
+
+        assert_eq!(
+            violated_fields(err),
+            vec![
+                "update_mask.paths",
+                "task_update.title",
+                "task_update.weight"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_update_task_request() {
+        let request = UpdateTaskRequest {
+            task_id: "00000000-0000-0000-0000-ffff00000000".to_string(),
+            task_update: None,
+            expected_version: 2,
+            update_mask: Some(FieldMask {
+                paths: vec!["description".to_string()],
+            }),
+        };
+
+        let patch = validate_update_task_request(request).unwrap();
+
+        assert_eq!(patch.version, 2);
+        assert_eq!(patch.description, Some(String::new()));
+        assert_eq!(patch.title, None);
+
+        let request = UpdateTaskRequest {
+            task_id: "00000000-0000-0000-0000-ffff00000000".to_string(),
+            task_update: Some(TaskUpdate::default()),
+            expected_version: 0,
+            update_mask: None,
+        };
+        let err = validate_update_task_request(request).unwrap_err();
+        assert_eq!(violated_fields(err), vec!["expected_version"]);
+    }
+
+    #[test]
+    fn test_validate_batch() {
+        let requests = vec![
+            valid_request(),
+            TaskRequest {
+                title: String::new(),
+                priority: PRIORITY_MAX + 1,
+                ..valid_request()
+            },
+        ];
+
+        let err = validate_batch("requests", requests, validate_task_request).unwrap_err();
+
+        assert_eq!(
+            violated_fields(err),
+            vec![
+                "requests[1].task_request.title",
+                "requests[1].task_request.priority",
+            ]
+        );
+        assert!(validate_batch("requests", vec![valid_request()], validate_task_request).is_ok());
+    }
+
+    #[test]
+    fn test_validate_batch_size() {
+        let err = validate_batch("requests", Vec::<TaskRequest>::new(), validate_task_request)
+            .unwrap_err();
+        assert_eq!(violated_fields(err), vec!["requests"]);
+
+        let requests = vec![valid_request(); BATCH_MAX_SIZE + 1];
+        let err = validate_batch("requests", requests, validate_task_request).unwrap_err();
+        assert_eq!(violated_fields(err), vec!["requests"]);
+    }
+
+    #[test]
+    fn test_validate_unique_ids() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+
+        assert!(validate_unique_ids([a, b], |i| format!("task_ids[{}]", i)).is_ok());
+        let err = validate_unique_ids([a, b, a], |i| format!("task_ids[{}]", i)).unwrap_err();
+        assert_eq!(violated_fields(err), vec!["task_ids[2]"]);
+    }
+
+    #[test]
+    fn test_validate_batch_ids() {
+        let ids = vec![
+            "00000000-0000-0000-0000-ffff00000000".to_string(),
+            "invalid".to_string(),
+            "00000000-0000-0000-0000-ffff00000000".to_string(),
+        ];
+
+        let err = validate_batch_ids("task_ids", ids).unwrap_err();
+
+        assert_eq!(violated_fields(err), vec!["task_ids[1]", "task_ids[2]"]);
+        let err = validate_batch_ids("task_ids", vec![]).unwrap_err();
+        assert_eq!(violated_fields(err), vec!["task_ids"]);
+    }
+
+    #[test]
+    fn test_parse_uuid() {
+        assert!(parse_uuid("task_id", "00000000-0000-0000-0000-ffff00000000").is_ok());
+
+        let err = parse_uuid("task_id", "not-a-uuid").unwrap_err();
+        assert_eq!(violated_fields(err), vec!["task_id"]);
+    }
+
+    #[test]
+    fn test_validate_user_request() {
+        let input = validate_user_request(UserRequest {
+            id: String::new(),
+            username: "user".to_string(),
+            email: "user@example.com".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(input.id, None);
+        assert_eq!(input.username, "user");
+        assert_eq!(input.email, "user@example.com");
+    }
+
+    #[test]
+    fn test_validate_user_request_violations() {
+        let err = validate_user_request(UserRequest {
+            id: "user".to_string(),
+            username: " ".to_string(),
+            email: "user.example.com".to_string(),
+        })
+        .unwrap_err();
+        assert_eq!(
+            violated_fields(err),
+            vec!["user_request.username", "user_request.email"]
+        );
+
+        let err = validate_user_request(UserRequest {
+            id: "user".to_string(),
+            username: "a".repeat(USERNAME_MAX_LEN + 1),
+            email: format!("{}@example.com", "a".repeat(EMAIL_MAX_LEN)),
+        })
+        .unwrap_err();
+        assert_eq!(
+            violated_fields(err),
+            vec!["user_request.username", "user_request.email"]
+        );
+
+        for email in [
+            "",
+            "@example.com",
+            "user@",
+            "a@b@example.com",
+            "user @example.com",
+        ] {
+            let err = validate_user_request(UserRequest {
+                id: String::new(),
+                username: "user".to_string(),
+                email: email.to_string(),
+            })
+            .unwrap_err();
+            assert_eq!(violated_fields(err), vec!["user_request.email"]);
+        }
+    }
+
+    #[test]
+    fn test_validate_user_update() {
+        assert!(validate_user_update(UserUpdate {
+            username: None,
+            email: Some("new@example.com".to_string()),
+        })
+        .is_ok());
+
+        let err = validate_user_update(UserUpdate {
+            username: Some(String::new()),
+            email: Some("invalid".to_string()),
+        })
+        .unwrap_err();
+        assert_eq!(
+            violated_fields(err),
+            vec!["user_update.username", "user_update.email"]
+        );
+    }
+
+    #[test]
+    fn test_validate_plan_request() {
+        let options = validate_plan_request(GeneratePlanRequest {
+            hours_per_day: 6,
+            days: 7,
+            start_date: Some(prost_types::Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            }),
+        })
+        .unwrap();
+
+        assert_eq!(options.hours_per_day, 6);
+        assert_eq!(options.days, 7);
+        assert_eq!(
+            options.start_date,
+            OffsetDateTime::from_unix_timestamp(1_700_000_000)
+                .unwrap()
+                .date()
+        );
+
+        let err = validate_plan_request(GeneratePlanRequest {
+            hours_per_day: PLAN_HOURS_PER_DAY_MAX + 1,
+            days: 0,
+            start_date: None,
+        })
+        .unwrap_err();
+
+        assert_eq!(violated_fields(err), vec!["hours_per_day", "days"]);
+    }
+
+    #[test]
+    fn test_validate_next_tasks_request() {
+        let input = validate_next_tasks_request(GetNextTasksRequest {
+            limit: 0,
+            weights: None,
+        })
+        .unwrap();
+
+        assert_eq!(input.limit, NEXT_TASKS_DEFAULT_LIMIT as usize);
+        assert_eq!(input.weights, UrgencyWeights::default());
+
+        let err = validate_next_tasks_request(GetNextTasksRequest {
+            limit: NEXT_TASKS_MAX_LIMIT + 1,
+            weights: Some(gakusai2024_proto::api::UrgencyWeights {
+                priority: -1.0,
+                effort: f64::NAN,
+                due_date: 0.0,
+                age: 0.0,
+            }),
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            violated_fields(err),
+            vec!["limit", "weights.priority", "weights.effort"]
+        );
+
+        let err = validate_next_tasks_request(GetNextTasksRequest {
+            limit: 1,
+            weights: Some(gakusai2024_proto::api::UrgencyWeights::default()),
+        })
+        .unwrap_err();
+
+        assert_eq!(violated_fields(err), vec!["weights"]);
+
+        let err = validate_next_tasks_request(GetNextTasksRequest {
+            limit: 1,
+            weights: Some(gakusai2024_proto::api::UrgencyWeights {
+                priority: 1e308,
+                effort: 1e308,
+                due_date: 0.0,
+                age: 0.0,
+            }),
+        })
+        .unwrap_err();
+
+        assert_eq!(violated_fields(err), vec!["weights"]);
+    }
+
+    #[test]
+    fn test_validate_tag_name() {
+        assert_eq!(
+            validate_tag_name("name", "work".to_string()).unwrap(),
+            "work"
+        );
+        assert_eq!(
+            violated_fields(validate_tag_name("name", " ".to_string()).unwrap_err()),
+            vec!["name"]
+        );
+        assert!(validate_tag_name("name", "a".repeat(TAG_NAME_MAX_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_validate_reminder_offsets() {
+        assert_eq!(
+            validate_reminder_offsets(vec![1440, 60]).unwrap(),
+            vec![1440, 60]
+        );
+        assert!(validate_reminder_offsets(vec![]).unwrap().is_empty());
+        assert_eq!(
+            violated_fields(
+                validate_reminder_offsets(vec![60, 0, MAX_OFFSET_MINUTES + 1]).unwrap_err()
+            ),
+            vec!["offset_minutes[1]", "offset_minutes[2]"]
+        );
+        assert!(validate_reminder_offsets(vec![60; REMINDER_OFFSETS_MAX_COUNT + 1]).is_err());
+    }
+}
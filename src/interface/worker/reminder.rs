@@ -0,0 +1,93 @@
+use std::{env, time::Duration};
+
+use time::OffsetDateTime;
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+use crate::{
+    domain::{notifier::Notifier, repository::reminder::ReminderRepositoryTrait},
+    usecase::reminder::ReminderUsecaseTrait,
+};
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+// REMINDER_INTERVAL_SECSが0の場合はワーカーを起動しない
+pub fn interval_from_env() -> Option<Duration> {
+    let secs = env::var("REMINDER_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+// 一定間隔で期限が近いタスクを調べ、リマインドを送るバックグラウンドワーカー
+pub struct ReminderWorker<RU, RR, N>
+where
+    RU: ReminderUsecaseTrait<RR, N>,
+    RR: ReminderRepositoryTrait + 'static,
+    N: Notifier + 'static,
+{
+    usecase: Box<RU>,
+    interval: Duration,
+    _phantom: std::marker::PhantomData<(RR, N)>,
+}
+
+impl<RU, RR, N> ReminderWorker<RU, RR, N>
+where
+    RU: ReminderUsecaseTrait<RR, N> + Send + Sync + 'static,
+    RR: ReminderRepositoryTrait + Send + Sync + 'static,
+    N: Notifier + Send + Sync + 'static,
+{
+    pub fn new(usecase: Box<RU>, interval: Duration) -> Self {
+        Self {
+            usecase,
+            interval,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move { self.run().await })
+    }
+
+    async fn run(self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            // 一時的なDBエラーなどで止まらないよう、失敗してもログに出して次の周期で再試行する
+            match self.usecase.fire_due(OffsetDateTime::now_utc()).await {
+                Ok(sent) if !sent.is_empty() => log::info!("Sent {} reminders", sent.len()),
+                Ok(_) => {}
+                Err(err) => log::error!("Failed to process reminders: {}", err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{
+        domain::{notifier::MockNotifier, repository::reminder::MockReminderRepositoryTrait},
+        error::CustomError,
+        usecase::reminder::MockReminderUsecaseTrait,
+    };
+
+    #[tokio::test]
+    async fn test_worker_keeps_running_after_error() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut usecase =
+            MockReminderUsecaseTrait::<MockReminderRepositoryTrait, MockNotifier>::default();
+        usecase.expect_fire_due().returning(move |_| {
+            tx.send(()).unwrap();
+            Box::pin(async move { Err(CustomError::Unavailable("db is down".to_string())) })
+        });
+
+        let handle = ReminderWorker::new(Box::new(usecase), Duration::from_millis(10)).spawn();
+        rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+        handle.abort();
+    }
+}
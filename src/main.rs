@@ -1,21 +1,42 @@
-use std::{env, sync::Arc};
+use std::{env, net::SocketAddr};
 
 use dotenv::dotenv;
 use gakusai2024_backend::domain::repository::hello::HelloRepositoryTrait;
+use gakusai2024_backend::domain::repository::reminder::ReminderRepositoryTrait;
+use gakusai2024_backend::domain::repository::tag::TagRepositoryTrait;
 use gakusai2024_backend::domain::repository::task::TaskRepositoryTrait;
+use gakusai2024_backend::domain::repository::unit_of_work::UnitOfWorkTrait;
+use gakusai2024_backend::domain::repository::user::UserRepositoryTrait;
 use gakusai2024_proto::api::hello_service_server::HelloServiceServer;
+use gakusai2024_proto::api::reminder_service_server::ReminderServiceServer;
+use gakusai2024_proto::api::tag_service_server::TagServiceServer;
 use gakusai2024_proto::api::task_service_server::TaskServiceServer;
-use sea_orm::Database;
-use tokio::sync::Mutex;
+use gakusai2024_proto::api::user_service_server::UserServiceServer;
 use tonic::transport::Server;
 
 use gakusai2024_backend::infrastructure;
+use gakusai2024_backend::infrastructure::memory::hello::InMemoryHelloPersistence;
+use gakusai2024_backend::infrastructure::memory::reminder::InMemoryReminderPersistence;
+use gakusai2024_backend::infrastructure::memory::tag::InMemoryTagPersistence;
+use gakusai2024_backend::infrastructure::memory::task::InMemoryTaskPersistence;
+use gakusai2024_backend::infrastructure::memory::unit_of_work::InMemoryUnitOfWork;
+use gakusai2024_backend::infrastructure::memory::user::InMemoryUserPersistence;
+use gakusai2024_backend::infrastructure::memory::InMemoryStore;
+use gakusai2024_backend::infrastructure::RepositoryBackend;
 use gakusai2024_backend::interface;
+use gakusai2024_backend::interface::auth::AuthInterceptor;
 use gakusai2024_backend::interface::handler::hello::HelloHandlerTrait;
+use gakusai2024_backend::interface::handler::reminder::ReminderHandlerTrait;
+use gakusai2024_backend::interface::handler::tag::TagHandlerTrait;
 use gakusai2024_backend::interface::handler::task::TaskHandlerTrait;
+use gakusai2024_backend::interface::handler::user::UserHandlerTrait;
+use gakusai2024_backend::interface::worker::reminder::ReminderWorker;
 use gakusai2024_backend::usecase;
 use gakusai2024_backend::usecase::hello::HelloUsecaseTrait;
+use gakusai2024_backend::usecase::reminder::ReminderUsecaseTrait;
+use gakusai2024_backend::usecase::tag::TagUsecaseTrait;
 use gakusai2024_backend::usecase::task::TaskUsecaseTrait;
+use gakusai2024_backend::usecase::user::UserUsecaseTrait;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -24,24 +45,118 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let addr = env::var("SERVER_ADDR")
         .expect("SERVER_ADDR must be set")
         .parse()?;
-    let db_addr = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-
-    let conn = Arc::new(Mutex::new(Database::connect(db_addr).await?));
 
     // Dependency Injection
-    let hello_persistence = infrastructure::db::hello::HelloPersistence::new(conn.clone());
-    let hello_usecase = usecase::hello::HelloUsecase::new(Box::new(hello_persistence));
+    match RepositoryBackend::from_env() {
+        RepositoryBackend::Database => {
+            let db_config = infrastructure::db::DbConfig::from_env();
+            let conn = db_config.connect().await?;
+            if db_config.auto_migrate {
+                infrastructure::db::migrate::run_migrations(&conn).await?;
+            }
+            serve(
+                addr,
+                infrastructure::db::hello::HelloPersistence::new(conn.clone()),
+                infrastructure::db::task::TaskPersistence::new(conn.clone()),
+                infrastructure::db::unit_of_work::UnitOfWork::new(conn.clone()),
+                infrastructure::db::tag::TagPersistence::new(conn.clone()),
+                infrastructure::db::reminder::ReminderPersistence::new(conn.clone()),
+                infrastructure::db::reminder::ReminderPersistence::new(conn.clone()),
+                infrastructure::db::user::UserPersistence::new(conn),
+            )
+            .await
+        }
+        RepositoryBackend::Memory => {
+            log::warn!("Using in-memory repositories. All data will be lost on shutdown");
+            let store = InMemoryStore::new();
+            serve(
+                addr,
+                InMemoryHelloPersistence::new(store.clone()),
+                InMemoryTaskPersistence::new(store.clone()),
+                InMemoryUnitOfWork::new(store.clone()),
+                InMemoryTagPersistence::new(store.clone()),
+                InMemoryReminderPersistence::new(store.clone()),
+                InMemoryReminderPersistence::new(store.clone()),
+                InMemoryUserPersistence::new(store),
+            )
+            .await
+        }
+    }
+}
+
+// リマインドのワーカーはgRPCサーバーとは別にリポジトリを持つ
+#[allow(clippy::too_many_arguments)]
+async fn serve<HR, TR, UW, TgR, RR, UR>(
+    addr: SocketAddr,
+    hello_repository: HR,
+    task_repository: TR,
+    unit_of_work: UW,
+    tag_repository: TgR,
+    reminder_repository: RR,
+    worker_reminder_repository: RR,
+    user_repository: UR,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    HR: HelloRepositoryTrait + Send + Sync + 'static,
+    TR: TaskRepositoryTrait + Send + Sync + 'static,
+    UW: UnitOfWorkTrait + Send + Sync + 'static,
+    TgR: TagRepositoryTrait + Send + Sync + 'static,
+    RR: ReminderRepositoryTrait + Send + Sync + 'static,
+    UR: UserRepositoryTrait + Send + Sync + 'static,
+{
+    let auth_interceptor = AuthInterceptor::from_env();
+
+    let hello_usecase = usecase::hello::HelloUsecase::new(Box::new(hello_repository));
     let hello_handler = interface::handler::hello::HelloHandler::new(Box::new(hello_usecase));
 
-    let task_persistence = infrastructure::db::task::TaskPersistence::new(conn);
-    let task_usecase = usecase::task::TaskUsecase::new(Box::new(task_persistence));
+    let task_usecase =
+        usecase::task::TaskUsecase::new(Box::new(task_repository), Box::new(unit_of_work));
     let task_handler = interface::handler::task::TaskHandler::new(Box::new(task_usecase));
 
+    let tag_usecase = usecase::tag::TagUsecase::new(Box::new(tag_repository));
+    let tag_handler = interface::handler::tag::TagHandler::new(Box::new(tag_usecase));
+
+    let notifier = infrastructure::notifier::LogNotifier;
+    let reminder_usecase = usecase::reminder::ReminderUsecase::new(
+        Box::new(reminder_repository),
+        Box::new(notifier.clone()),
+    );
+    let reminder_handler =
+        interface::handler::reminder::ReminderHandler::new(Box::new(reminder_usecase));
+
+    // リマインドの送信はgRPCサーバーとは別のタスクで動かす
+    if let Some(interval) = interface::worker::reminder::interval_from_env() {
+        let worker_usecase = usecase::reminder::ReminderUsecase::new(
+            Box::new(worker_reminder_repository),
+            Box::new(notifier),
+        );
+        ReminderWorker::new(Box::new(worker_usecase), interval).spawn();
+        log::info!("Reminder worker started (interval: {:?})", interval);
+    }
+
+    let user_usecase = usecase::user::UserUsecase::new(Box::new(user_repository));
+    let user_handler = interface::handler::user::UserHandler::new(Box::new(user_usecase));
+
     log::info!("GreeterServer listening on {}", addr);
 
     Server::builder()
         .add_service(HelloServiceServer::new(hello_handler))
-        .add_service(TaskServiceServer::new(task_handler))
+        .add_service(TaskServiceServer::with_interceptor(
+            task_handler,
+            auth_interceptor.clone(),
+        ))
+        .add_service(UserServiceServer::with_interceptor(
+            user_handler,
+            auth_interceptor.clone(),
+        ))
+        .add_service(TagServiceServer::with_interceptor(
+            tag_handler,
+            auth_interceptor.clone(),
+        ))
+        .add_service(ReminderServiceServer::with_interceptor(
+            reminder_handler,
+            auth_interceptor,
+        ))
         .serve(addr)
         .await?;
 
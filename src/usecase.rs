@@ -1,2 +1,7 @@
 pub mod hello;
+pub mod reminder;
+pub mod tag;
 pub mod task;
+pub mod task_policy;
+pub mod task_watch;
+pub mod user;
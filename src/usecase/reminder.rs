@@ -0,0 +1,323 @@
+use std::future::Future;
+
+use mockall::automock;
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    domain::{
+        notifier::Notifier,
+        reminder::{
+            self, FiredReminder, Reminder, ReminderOffset, DEFAULT_OFFSET_MINUTES,
+            MAX_OFFSET_MINUTES,
+        },
+        repository::reminder::ReminderRepositoryTrait,
+    },
+    error::CustomError,
+};
+
+#[automock]
+pub trait ReminderUsecaseTrait<RR: ReminderRepositoryTrait + 'static, N: Notifier + 'static> {
+    fn new(repository: Box<RR>, notifier: Box<N>) -> Self
+    where
+        Self: Sized;
+    // 未設定の場合はデフォルトのオフセットを返す
+    fn get_offsets(
+        &self,
+        user_id: String,
+    ) -> impl Future<Output = Result<Vec<i32>, CustomError>> + Send;
+    // 空のリストを渡すとデフォルトに戻る
+    fn set_offsets(
+        &self,
+        user_id: String,
+        offsets: Vec<i32>,
+    ) -> impl Future<Output = Result<Vec<i32>, CustomError>> + Send;
+    // 通知時刻を過ぎたリマインドを送信し、送信したものを返す
+    fn fire_due(
+        &self,
+        now: OffsetDateTime,
+    ) -> impl Future<Output = Result<Vec<Reminder>, CustomError>> + Send;
+}
+
+pub struct ReminderUsecase<RR: ReminderRepositoryTrait, N: Notifier> {
+    repository: Box<RR>,
+    notifier: Box<N>,
+}
+
+impl<RR, N> ReminderUsecaseTrait<RR, N> for ReminderUsecase<RR, N>
+where
+    RR: ReminderRepositoryTrait + Sync + 'static,
+    N: Notifier + Sync + 'static,
+{
+    fn new(repository: Box<RR>, notifier: Box<N>) -> Self {
+        Self {
+            repository,
+            notifier,
+        }
+    }
+
+    async fn get_offsets(&self, user_id: String) -> Result<Vec<i32>, CustomError> {
+        let offsets: Vec<i32> = self
+            .repository
+            .find_offsets(vec![user_id])
+            .await?
+            .into_iter()
+            .map(|o| o.offset_minutes)
+            .collect();
+        if offsets.is_empty() {
+            return Ok(DEFAULT_OFFSET_MINUTES.to_vec());
+        }
+        Ok(offsets)
+    }
+
+    async fn set_offsets(
+        &self,
+        user_id: String,
+        mut offsets: Vec<i32>,
+    ) -> Result<Vec<i32>, CustomError> {
+        offsets.sort_unstable_by(|a, b| b.cmp(a));
+        offsets.dedup();
+        let now = OffsetDateTime::now_utc();
+        let models = offsets
+            .iter()
+            .map(|&offset_minutes| ReminderOffset {
+                user_id: user_id.clone(),
+                offset_minutes,
+                created_at: now,
+            })
+            .collect();
+        self.repository
+            .replace_offsets(user_id.clone(), models)
+            .await?;
+        self.get_offsets(user_id).await
+    }
+
+    async fn fire_due(&self, now: OffsetDateTime) -> Result<Vec<Reminder>, CustomError> {
+        let tasks = self
+            .repository
+            .find_upcoming_tasks(now, now + Duration::minutes(MAX_OFFSET_MINUTES as i64))
+            .await?;
+        if tasks.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut user_ids: Vec<String> = tasks.iter().map(|t| t.user_id.clone()).collect();
+        user_ids.sort();
+        user_ids.dedup();
+        let offsets = self.repository.find_offsets(user_ids).await?;
+        let fired = self
+            .repository
+            .find_fired(tasks.iter().map(|t| t.id).collect())
+            .await?;
+
+        let mut sent = vec![];
+        for due in reminder::due_reminders(&tasks, &offsets, &fired, now) {
+            let fired = FiredReminder {
+                task_id: due.task_id,
+                due_date: due.due_date,
+                offset_minutes: due.offset_minutes,
+                fired_at: now,
+            };
+            // 先に記録してから送ることで、再起動や複数ワーカーでも重複して送らない
+            if !self.repository.mark_fired(fired.clone()).await? {
+                continue;
+            }
+            match self.notifier.notify(due.clone()).await {
+                Ok(()) => sent.push(due),
+                Err(err) => {
+                    log::error!("Failed to send reminder for task {}: {}", due.task_id, err);
+                    // 送れなかったものは記録を取り消し、次の実行で再送する
+                    if let Err(err) = self.repository.unmark_fired(fired).await {
+                        log::error!(
+                            "Failed to unmark reminder for task {}: {}",
+                            due.task_id,
+                            err
+                        );
+                    }
+                }
+            }
+        }
+        Ok(sent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::eq;
+    use uuid::{uuid, Uuid};
+
+    use super::*;
+    use crate::{
+        domain::{
+            notifier::MockNotifier,
+            repository::reminder::MockReminderRepositoryTrait,
+            task::{Task, TaskStatus},
+        },
+        infrastructure::notifier::InMemoryNotifier,
+    };
+
+    const TASK1: Uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+    const TASK2: Uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+
+    #[tokio::test]
+    async fn test_get_offsets_default() {
+        let mut mock = MockReminderRepositoryTrait::default();
+        mock.expect_find_offsets()
+            .with(eq(vec!["testuserid".to_string()]))
+            .returning(|_| Box::pin(async move { Ok(vec![]) }));
+
+        let usecase = ReminderUsecase::new(Box::new(mock), Box::new(InMemoryNotifier::default()));
+        let result = usecase.get_offsets("testuserid".to_string()).await;
+        assert_eq!(result.unwrap(), DEFAULT_OFFSET_MINUTES.to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_set_offsets_dedups() {
+        let mut mock = MockReminderRepositoryTrait::default();
+        mock.expect_replace_offsets()
+            .withf(|user_id, offsets| {
+                user_id == "testuserid"
+                    && offsets.iter().map(|o| o.offset_minutes).collect::<Vec<_>>() == [120, 30]
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(async move { Ok(()) }));
+        mock.expect_find_offsets().returning(|_| {
+            Box::pin(async move { Ok(vec![create_test_offset(120), create_test_offset(30)]) })
+        });
+
+        let usecase = ReminderUsecase::new(Box::new(mock), Box::new(InMemoryNotifier::default()));
+        let result = usecase
+            .set_offsets("testuserid".to_string(), vec![30, 120, 30])
+            .await;
+        assert_eq!(result.unwrap(), vec![120, 30]);
+    }
+
+    #[tokio::test]
+    async fn test_fire_due() {
+        let now = OffsetDateTime::now_utc();
+
+        let mut mock = MockReminderRepositoryTrait::default();
+        mock.expect_find_upcoming_tasks().returning(move |_, _| {
+            Box::pin(async move {
+                Ok(vec![
+                    create_test_task(TASK1, now + Duration::minutes(30)),
+                    create_test_task(TASK2, now + Duration::days(3)),
+                ])
+            })
+        });
+        mock.expect_find_offsets()
+            .returning(|_| Box::pin(async move { Ok(vec![]) }));
+        mock.expect_find_fired()
+            .returning(|_| Box::pin(async move { Ok(vec![]) }));
+        mock.expect_mark_fired()
+            .withf(move |f| {
+                f.task_id == TASK1
+                    && f.due_date == now + Duration::minutes(30)
+                    && f.offset_minutes == 60
+            })
+            .times(1)
+            .returning(|_| Box::pin(async move { Ok(true) }));
+        mock.expect_unmark_fired().never();
+
+        let notifier = InMemoryNotifier::default();
+        let usecase = ReminderUsecase::new(Box::new(mock), Box::new(notifier.clone()));
+        let result = usecase.fire_due(now).await.unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(notifier.sent(), result);
+        assert_eq!(notifier.sent()[0].task_id, TASK1);
+    }
+
+    #[tokio::test]
+    async fn test_fire_due_already_recorded() {
+        let now = OffsetDateTime::now_utc();
+
+        let mut mock = MockReminderRepositoryTrait::default();
+        mock.expect_find_upcoming_tasks().returning(move |_, _| {
+            Box::pin(async move { Ok(vec![create_test_task(TASK1, now + Duration::minutes(30))]) })
+        });
+        mock.expect_find_offsets()
+            .returning(|_| Box::pin(async move { Ok(vec![]) }));
+        mock.expect_find_fired()
+            .returning(|_| Box::pin(async move { Ok(vec![]) }));
+        // 別のワーカーが先に記録した
+        mock.expect_mark_fired()
+            .returning(|_| Box::pin(async move { Ok(false) }));
+        let mut notifier = MockNotifier::default();
+        notifier.expect_notify().never();
+
+        let usecase = ReminderUsecase::new(Box::new(mock), Box::new(notifier));
+        let result = usecase.fire_due(now).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fire_due_send_failed() {
+        let now = OffsetDateTime::now_utc();
+
+        let mut mock = MockReminderRepositoryTrait::default();
+        mock.expect_find_upcoming_tasks().returning(move |_, _| {
+            Box::pin(async move { Ok(vec![create_test_task(TASK1, now + Duration::minutes(30))]) })
+        });
+        mock.expect_find_offsets()
+            .returning(|_| Box::pin(async move { Ok(vec![]) }));
+        mock.expect_find_fired()
+            .returning(|_| Box::pin(async move { Ok(vec![]) }));
+        mock.expect_mark_fired()
+            .returning(|_| Box::pin(async move { Ok(true) }));
+        // 送信に失敗したら記録を取り消して次回に再送する
+        mock.expect_unmark_fired()
+            .withf(|f| f.task_id == TASK1 && f.offset_minutes == 60)
+            .times(1)
+            .returning(|_| Box::pin(async move { Ok(()) }));
+        let mut notifier = MockNotifier::default();
+        notifier.expect_notify().times(1).returning(|_| {
+            Box::pin(async move { Err(CustomError::Unavailable("smtp".to_string())) })
+        });
+
+        let usecase = ReminderUsecase::new(Box::new(mock), Box::new(notifier));
+        let result = usecase.fire_due(now).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fire_due_no_tasks() {
+        let mut mock = MockReminderRepositoryTrait::default();
+        mock.expect_find_upcoming_tasks()
+            .returning(|_, _| Box::pin(async move { Ok(vec![]) }));
+        mock.expect_find_offsets().never();
+        mock.expect_find_fired().never();
+
+        let usecase = ReminderUsecase::new(Box::new(mock), Box::new(InMemoryNotifier::default()));
+        let result = usecase.fire_due(OffsetDateTime::now_utc()).await;
+        assert!(result.unwrap().is_empty());
+    }
+
+    fn create_test_offset(offset_minutes: i32) -> ReminderOffset {
+        ReminderOffset {
+            user_id: "testuserid".to_string(),
+            offset_minutes,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn create_test_task(id: Uuid, due_date: OffsetDateTime) -> Task {
+        Task {
+            id,
+            title: "test_title".to_string(),
+            description: "test_description".to_string(),
+            due_date,
+            priority: 1,
+            weight: 1,
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+            deleted_at: None,
+            status: TaskStatus::Todo,
+            completed_at: None,
+            parent_id: None,
+            recurrence: None,
+            version: 1,
+            user_id: "testuserid".to_string(),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::future::Future;
+
+use mockall::automock;
+use uuid::Uuid;
+
+use crate::{
+    domain::{repository::tag::TagRepositoryTrait, tag::Tag},
+    error::CustomError,
+};
+
+// タグは作成したユーザーだけが参照・変更できる
+#[automock]
+pub trait TagUsecaseTrait<TR: TagRepositoryTrait + 'static> {
+    fn new(repository: Box<TR>) -> Self
+    where
+        Self: Sized;
+    fn insert(
+        &self,
+        user_id: String,
+        tag: Tag,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn find(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Tag, CustomError>> + Send;
+    fn find_from_user_id(
+        &self,
+        user_id: String,
+    ) -> impl Future<Output = Result<Vec<Tag>, CustomError>> + Send;
+    fn update(
+        &self,
+        user_id: String,
+        tag: Tag,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn delete(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+}
+
+pub struct TagUsecase<TR: TagRepositoryTrait> {
+    repository: Box<TR>,
+}
+
+impl<TR: TagRepositoryTrait + Sync> TagUsecase<TR> {
+    async fn find_owned(&self, user_id: &str, id: Uuid) -> Result<Tag, CustomError> {
+        let tag = self.repository.find(id).await?;
+        if tag.user_id != user_id {
+            return Err(CustomError::PermissionDenied(format!(
+                "user {} does not own tag {}",
+                user_id, id
+            )));
+        }
+        Ok(tag)
+    }
+}
+
+impl<TR: TagRepositoryTrait + Sync + 'static> TagUsecaseTrait<TR> for TagUsecase<TR> {
+    fn new(repository: Box<TR>) -> Self {
+        Self { repository }
+    }
+
+    fn insert(
+        &self,
+        user_id: String,
+        tag: Tag,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send {
+        self.repository.insert(Tag { user_id, ..tag })
+    }
+
+    async fn find(&self, user_id: String, id: Uuid) -> Result<Tag, CustomError> {
+        self.find_owned(&user_id, id).await
+    }
+
+    fn find_from_user_id(
+        &self,
+        user_id: String,
+    ) -> impl Future<Output = Result<Vec<Tag>, CustomError>> + Send {
+        self.repository.find_from_user_id(user_id)
+    }
+
+    async fn update(&self, user_id: String, tag: Tag) -> Result<Uuid, CustomError> {
+        self.find_owned(&user_id, tag.id).await?;
+        self.repository.update(Tag { user_id, ..tag }).await
+    }
+
+    async fn delete(&self, user_id: String, id: Uuid) -> Result<Uuid, CustomError> {
+        self.find_owned(&user_id, id).await?;
+        self.repository.delete(id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::eq;
+    use time::OffsetDateTime;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::domain::repository::tag::MockTagRepositoryTrait;
+
+    const TAG_ID: Uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+    #[tokio::test]
+    async fn test_tag_insert_sets_owner() {
+        let mut mock = MockTagRepositoryTrait::default();
+        mock.expect_insert()
+            .withf(|t| t.user_id == "testuserid")
+            .returning(|t| Box::pin(async move { Ok(t.id) }));
+
+        let usecase = TagUsecase::new(Box::new(mock));
+        let mut tag = create_test_tag(TAG_ID);
+        tag.user_id = String::new();
+        let result = usecase.insert("testuserid".to_string(), tag).await;
+        assert_eq!(result.unwrap(), TAG_ID);
+    }
+
+    #[tokio::test]
+    async fn test_tag_find() {
+        let mut mock = MockTagRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(TAG_ID))
+            .returning(|id| Box::pin(async move { Ok(create_test_tag(id)) }));
+
+        let usecase = TagUsecase::new(Box::new(mock));
+        let result = usecase.find("testuserid".to_string(), TAG_ID).await;
+        assert_eq!(result.unwrap().name, "test_tag");
+    }
+
+    #[tokio::test]
+    async fn test_tag_find_other_user() {
+        let mut mock = MockTagRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(TAG_ID))
+            .returning(|id| Box::pin(async move { Ok(create_test_tag(id)) }));
+
+        let usecase = TagUsecase::new(Box::new(mock));
+        let result = usecase.find("otheruser".to_string(), TAG_ID).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_tag_update() {
+        let mut mock = MockTagRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(TAG_ID))
+            .returning(|id| Box::pin(async move { Ok(create_test_tag(id)) }));
+        mock.expect_update()
+            .withf(|t| t.name == "renamed" && t.user_id == "testuserid")
+            .returning(|t| Box::pin(async move { Ok(t.id) }));
+
+        let usecase = TagUsecase::new(Box::new(mock));
+        let mut tag = create_test_tag(TAG_ID);
+        tag.name = "renamed".to_string();
+        let result = usecase.update("testuserid".to_string(), tag).await;
+        assert_eq!(result.unwrap(), TAG_ID);
+    }
+
+    #[tokio::test]
+    async fn test_tag_delete_other_user() {
+        let mut mock = MockTagRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(TAG_ID))
+            .returning(|id| Box::pin(async move { Ok(create_test_tag(id)) }));
+        mock.expect_delete().never();
+
+        let usecase = TagUsecase::new(Box::new(mock));
+        let result = usecase.delete("otheruser".to_string(), TAG_ID).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    fn create_test_tag(id: Uuid) -> Tag {
+        Tag {
+            id,
+            user_id: "testuserid".to_string(),
+            name: "test_tag".to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -1,42 +1,366 @@
-use std::future::Future;
+use std::{collections::HashSet, future::Future};
 
 use mockall::automock;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
 use crate::{
-    domain::{repository::task::TaskRepositoryTrait, task::Task},
-    error::CustomError,
+    domain::{
+        planner::{self, Plan, PlanOptions},
+        repository::{
+            task::TaskRepositoryTrait,
+            unit_of_work::{TransactionScopeTrait, UnitOfWorkTrait},
+        },
+        task::{Task, TaskExt, TaskPatch, TaskStatus, TaskStatusExt},
+        task_dependency,
+        task_event::{TaskEvent, TaskEventBus, TaskEventKind},
+        task_history::{self, FieldChange, TaskHistory, TaskHistoryAction, TaskHistoryExt},
+        task_query::{TaskCursor, TaskPage, TaskQuery},
+        task_tree::TaskNode,
+        urgency::{self, ScoredTask, UrgencyWeights},
+    },
+    error::{BatchItemError, CustomError},
+    usecase::{
+        task_policy::{TaskAccess, TaskAction},
+        task_watch::TaskWatch,
+    },
 };
 
+// user_idは操作するユーザー。対象タスクへの権限が無ければPermissionDeniedを返す
 #[automock]
-pub trait TaskUsecaseTrait<TR: TaskRepositoryTrait + 'static> {
-    fn new(repository: Box<TR>) -> Self
+pub trait TaskUsecaseTrait<TR: TaskRepositoryTrait + 'static, UW: UnitOfWorkTrait + 'static> {
+    fn new(repository: Box<TR>, unit_of_work: Box<UW>) -> Self
     where
         Self: Sized;
-    fn insert(&self, task: Task) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
-    fn find(&self, id: Uuid) -> impl Future<Output = Result<Task, CustomError>> + Send;
+    fn insert(
+        &self,
+        user_id: String,
+        task: Task,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn find(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Task, CustomError>> + Send;
     fn find_from_user_id(
         &self,
         user_id: String,
     ) -> impl Future<Output = Result<Vec<Task>, CustomError>> + Send;
-    fn update(&self, task: Task) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn list(&self, query: TaskQuery) -> impl Future<Output = Result<TaskPage, CustomError>> + Send;
+    fn subtree(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<TaskNode, CustomError>> + Send;
+    // バージョンを進めた更新後のタスクを返す
+    fn update(
+        &self,
+        user_id: String,
+        patch: TaskPatch,
+    ) -> impl Future<Output = Result<Task, CustomError>> + Send;
+    fn delete(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    // 一括処理は1件でも失敗した場合は何も書き込まず、失敗した全ての要素をBatchFailedで返す
+    fn batch_insert(
+        &self,
+        user_id: String,
+        tasks: Vec<Task>,
+    ) -> impl Future<Output = Result<Vec<Uuid>, CustomError>> + Send;
+    fn batch_update(
+        &self,
+        user_id: String,
+        patches: Vec<TaskPatch>,
+    ) -> impl Future<Output = Result<Vec<Task>, CustomError>> + Send;
+    fn batch_delete(
+        &self,
+        user_id: String,
+        ids: Vec<Uuid>,
+    ) -> impl Future<Output = Result<Vec<Uuid>, CustomError>> + Send;
+    fn restore(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    // 物理削除は管理者だけが行える。所有者でも論理削除までしかできない
+    fn purge(
+        &self,
+        user_id: String,
+        is_admin: bool,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn change_status(
+        &self,
+        user_id: String,
+        id: Uuid,
+        status: TaskStatus,
+    ) -> impl Future<Output = Result<Task, CustomError>> + Send;
+    fn complete(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Task, CustomError>> + Send;
+    fn reopen(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Task, CustomError>> + Send;
+    fn share(
+        &self,
+        user_id: String,
+        id: Uuid,
+        target_user_id: String,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn unshare(
+        &self,
+        user_id: String,
+        id: Uuid,
+        target_user_id: String,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn add_dependency(
+        &self,
+        user_id: String,
+        id: Uuid,
+        depends_on_id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn remove_dependency(
+        &self,
+        user_id: String,
+        id: Uuid,
+        depends_on_id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn dependency_order(
+        &self,
+        user_id: String,
+    ) -> impl Future<Output = Result<Vec<Task>, CustomError>> + Send;
+    fn plan(
+        &self,
+        user_id: String,
+        options: PlanOptions,
+    ) -> impl Future<Output = Result<Plan, CustomError>> + Send;
+    fn next_tasks(
+        &self,
+        user_id: String,
+        limit: usize,
+        weights: UrgencyWeights,
+    ) -> impl Future<Output = Result<Vec<ScoredTask>, CustomError>> + Send;
+    fn attach_tag(
+        &self,
+        user_id: String,
+        id: Uuid,
+        tag_id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn detach_tag(
+        &self,
+        user_id: String,
+        id: Uuid,
+        tag_id: Uuid,
+    ) -> impl Future<Output = Result<Uuid, CustomError>> + Send;
+    fn history(
+        &self,
+        user_id: String,
+        id: Uuid,
+    ) -> impl Future<Output = Result<Vec<TaskHistory>, CustomError>> + Send;
+    // 書き込みに成功したタスクの作成・更新・削除を受け取る
+    fn subscribe(&self) -> broadcast::Receiver<TaskEvent>;
+    // subscribeのうち、user_idが閲覧できるタスクのものだけを受け取る
+    fn watch(&self, user_id: String)
+        -> impl Future<Output = Result<TaskWatch, CustomError>> + Send;
 }
 
-pub struct TaskUsecase<TR: TaskRepositoryTrait> {
+// 値が変わらなかった更新は記録しない
+fn histories(
+    task: &Task,
+    actor_id: &str,
+    action: TaskHistoryAction,
+    changes: Vec<FieldChange>,
+) -> Vec<TaskHistory> {
+    if action == TaskHistoryAction::Updated && changes.is_empty() {
+        return vec![];
+    }
+    vec![TaskHistory::record(
+        task,
+        actor_id.to_string(),
+        action,
+        changes,
+    )]
+}
+
+pub struct TaskUsecase<TR: TaskRepositoryTrait, UW: UnitOfWorkTrait> {
     repository: Box<TR>,
+    // 複数の書き込みを1つのトランザクションで行う
+    unit_of_work: Box<UW>,
+    events: TaskEventBus,
+}
+
+impl<TR: TaskRepositoryTrait + Sync, UW: UnitOfWorkTrait + Sync> TaskUsecase<TR, UW> {
+    async fn authorize(
+        &self,
+        user_id: &str,
+        task: &Task,
+        action: TaskAction,
+    ) -> Result<(), CustomError> {
+        self.authorize_owner(user_id, &task.user_id, task.id, action)
+            .await
+    }
+
+    // タスクが既に完全に削除されている場合も、所有者が分かれば権限を確認できる
+    async fn authorize_owner(
+        &self,
+        user_id: &str,
+        owner_id: &str,
+        task_id: Uuid,
+        action: TaskAction,
+    ) -> Result<(), CustomError> {
+        // 所有者の場合は共有設定を確認しない
+        let is_shared = owner_id != user_id
+            && action != TaskAction::Manage
+            && self
+                .repository
+                .is_shared_with(task_id, user_id.to_string())
+                .await?;
+        if !TaskAccess::of(owner_id, user_id, is_shared).allows(action) {
+            return Err(CustomError::PermissionDenied(format!(
+                "user {} cannot {:?} task {}",
+                user_id, action, task_id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn find_authorized(
+        &self,
+        user_id: &str,
+        id: Uuid,
+        action: TaskAction,
+    ) -> Result<Task, CustomError> {
+        let task = self.repository.find(id).await?;
+        self.authorize(user_id, &task, action).await?;
+        Ok(task)
+    }
+
+    // 子タスクは親タスクの所有者のタスクとして作成する
+    async fn assign_owner(&self, user_id: &str, task: Task) -> Result<Task, CustomError> {
+        let owner = match task.parent_id {
+            Some(parent_id) => {
+                self.find_authorized(user_id, parent_id, TaskAction::Edit)
+                    .await?
+                    .user_id
+            }
+            None => user_id.to_string(),
+        };
+        Ok(Task {
+            user_id: owner,
+            ..task
+        })
+    }
+
+    // 更新前と更新後のタスクを返す
+    async fn apply_patch(
+        &self,
+        user_id: &str,
+        patch: TaskPatch,
+    ) -> Result<(Task, Task), CustomError> {
+        let current = self
+            .find_authorized(user_id, patch.id, TaskAction::Edit)
+            .await?;
+        check_version(patch.id, patch.version, &current)?;
+        // 所有者は更新では変更できない
+        let updated = current.update(
+            patch.title,
+            patch.description,
+            None,
+            patch.due_date,
+            patch.priority,
+            patch.weight,
+        );
+        Ok((current, updated))
+    }
+
+    // 保存に成功したら、バージョンを進めたタスクを返す
+    async fn save(&self, task: Task, histories: Vec<TaskHistory>) -> Result<Task, CustomError> {
+        self.repository.update(task.clone(), histories).await?;
+        Ok(Task {
+            version: task.version + 1,
+            ..task
+        })
+    }
+
+    async fn ensure_prerequisites_completed(&self, id: Uuid) -> Result<(), CustomError> {
+        let open: Vec<String> = self
+            .repository
+            .find_prerequisites(id)
+            .await?
+            .into_iter()
+            .filter(|t| !t.status.is_closed())
+            .map(|t| t.id.to_string())
+            .collect();
+        if !open.is_empty() {
+            return Err(CustomError::PrerequisitesNotCompleted(format!(
+                "task {} depends on open tasks {}",
+                id,
+                open.join(", ")
+            )));
+        }
+        Ok(())
+    }
+    // 全て同じトランザクションで書き込む。失敗した場合は何も書き込まず、失敗した全ての要素をBatchFailedで返す
+    async fn write_batch(
+        &self,
+        writes: Vec<(TaskWrite, Vec<TaskHistory>)>,
+    ) -> Result<(), CustomError> {
+        let txn = self.unit_of_work.begin().await?;
+        let mut errors = Vec::new();
+        for (index, (write, histories)) in writes.into_iter().enumerate() {
+            // 各書き込みはセーブポイント内で行われるので、失敗しても残りの要素は続けて書き込める
+            let result = match write {
+                TaskWrite::Insert(task) => txn.tasks().insert(task, histories).await,
+                TaskWrite::Update(task) => txn.tasks().update(task, histories).await,
+                TaskWrite::Delete(id) => txn.tasks().delete(id, histories).await,
+            };
+            if let Err(error) = result {
+                errors.push(BatchItemError::new(index, error));
+            }
+        }
+        // commitせずに戻るとそれまでの書き込みはロールバックされる
+        if !errors.is_empty() {
+            return Err(CustomError::BatchFailed(errors));
+        }
+        txn.commit().await
+    }
 }
 
-impl<TR: TaskRepositoryTrait + 'static> TaskUsecaseTrait<TR> for TaskUsecase<TR> {
-    fn new(repository: Box<TR>) -> Self {
-        Self { repository }
+impl<TR, UW> TaskUsecaseTrait<TR, UW> for TaskUsecase<TR, UW>
+where
+    TR: TaskRepositoryTrait + Sync + 'static,
+    UW: UnitOfWorkTrait + Sync + 'static,
+{
+    fn new(repository: Box<TR>, unit_of_work: Box<UW>) -> Self {
+        Self {
+            repository,
+            unit_of_work,
+            events: TaskEventBus::default(),
+        }
     }
 
-    fn insert(&self, task: Task) -> impl Future<Output = Result<Uuid, CustomError>> + Send {
-        self.repository.insert(task)
+    async fn insert(&self, user_id: String, task: Task) -> Result<Uuid, CustomError> {
+        let task = self.assign_owner(&user_id, task).await?;
+        let histories = histories(
+            &task,
+            &user_id,
+            TaskHistoryAction::Created,
+            task_history::diff(None, &task),
+        );
+        let id = self.repository.insert(task.clone(), histories).await?;
+        self.events.publish(TaskEventKind::Created, task);
+        Ok(id)
     }
 
-    fn find(&self, id: Uuid) -> impl Future<Output = Result<Task, CustomError>> + Send {
-        self.repository.find(id)
+    async fn find(&self, user_id: String, id: Uuid) -> Result<Task, CustomError> {
+        self.find_authorized(&user_id, id, TaskAction::View).await
     }
 
     fn find_from_user_id(
@@ -46,20 +370,507 @@ impl<TR: TaskRepositoryTrait + 'static> TaskUsecaseTrait<TR> for TaskUsecase<TR>
         self.repository.find_from_user_id(user_id)
     }
 
-    fn update(&self, task: Task) -> impl Future<Output = Result<Uuid, CustomError>> + Send {
-        self.repository.update(task)
+    async fn list(&self, query: TaskQuery) -> Result<TaskPage, CustomError> {
+        let page_size = query.page_size;
+        let sort_key = query.sort_key;
+        let order = query.order;
+
+        // 次のページが存在するか判定するために1件多く取得する
+        let mut tasks = self.repository.search(query, page_size + 1).await?;
+        let next_cursor = if tasks.len() as u64 > page_size {
+            tasks.truncate(page_size as usize);
+            tasks
+                .last()
+                .map(|t| TaskCursor::from_task(t, sort_key, order))
+        } else {
+            None
+        };
+
+        Ok(TaskPage { tasks, next_cursor })
+    }
+
+    async fn subtree(&self, user_id: String, id: Uuid) -> Result<TaskNode, CustomError> {
+        let root = self.find_authorized(&user_id, id, TaskAction::View).await?;
+
+        // 階層ごとに子タスクを取得し、同じタスクを二度辿らないようにする
+        let mut visited = HashSet::from([root.id]);
+        let mut frontier = vec![root.id];
+        let mut descendants = Vec::new();
+        while !frontier.is_empty() {
+            let children: Vec<Task> = self
+                .repository
+                .find_children(frontier)
+                .await?
+                .into_iter()
+                .filter(|t| visited.insert(t.id))
+                .collect();
+            frontier = children.iter().map(|t| t.id).collect();
+            descendants.extend(children);
+        }
+
+        Ok(TaskNode::build(root, descendants))
+    }
+
+    async fn update(&self, user_id: String, patch: TaskPatch) -> Result<Task, CustomError> {
+        let (current, task) = self.apply_patch(&user_id, patch).await?;
+        let histories = histories(
+            &task,
+            &user_id,
+            TaskHistoryAction::Updated,
+            task_history::diff(Some(&current), &task),
+        );
+        let task = self.save(task, histories).await?;
+        self.events.publish(TaskEventKind::Updated, task.clone());
+        Ok(task)
+    }
+
+    async fn delete(&self, user_id: String, id: Uuid) -> Result<Uuid, CustomError> {
+        let task = self
+            .find_authorized(&user_id, id, TaskAction::Manage)
+            .await?;
+        let histories = histories(&task, &user_id, TaskHistoryAction::Deleted, vec![]);
+        self.repository.delete(id, histories).await?;
+        self.events.publish(TaskEventKind::Deleted, task);
+        Ok(id)
+    }
+
+    async fn batch_insert(
+        &self,
+        user_id: String,
+        tasks: Vec<Task>,
+    ) -> Result<Vec<Uuid>, CustomError> {
+        let mut results: Vec<Result<Task, CustomError>> = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            // 同じバッチで先に作成するタスクも親に指定できる
+            let batch_parent = task
+                .parent_id
+                .and_then(|parent_id| results.iter().flatten().find(|t| t.id == parent_id));
+            let result = match batch_parent {
+                Some(parent)
+                    if TaskAccess::of(&parent.user_id, &user_id, false)
+                        .allows(TaskAction::Edit) =>
+                {
+                    Ok(Task {
+                        user_id: parent.user_id.clone(),
+                        ..task
+                    })
+                }
+                Some(parent) => Err(CustomError::PermissionDenied(format!(
+                    "user {} cannot {:?} task {}",
+                    user_id,
+                    TaskAction::Edit,
+                    parent.id
+                ))),
+                None => self.assign_owner(&user_id, task).await,
+            };
+            results.push(result);
+        }
+        let tasks = collect_batch(results)?;
+
+        let writes = tasks
+            .iter()
+            .map(|task| {
+                let histories = histories(
+                    task,
+                    &user_id,
+                    TaskHistoryAction::Created,
+                    task_history::diff(None, task),
+                );
+                (TaskWrite::Insert(task.clone()), histories)
+            })
+            .collect();
+        self.write_batch(writes).await?;
+
+        let ids = tasks.iter().map(|task| task.id).collect();
+        for task in tasks {
+            self.events.publish(TaskEventKind::Created, task);
+        }
+        Ok(ids)
+    }
+
+    async fn batch_update(
+        &self,
+        user_id: String,
+        patches: Vec<TaskPatch>,
+    ) -> Result<Vec<Task>, CustomError> {
+        let mut results = Vec::with_capacity(patches.len());
+        for patch in patches {
+            results.push(self.apply_patch(&user_id, patch).await);
+        }
+        let updates = collect_batch(results)?;
+
+        let writes = updates
+            .iter()
+            .map(|(current, task)| {
+                let histories = histories(
+                    task,
+                    &user_id,
+                    TaskHistoryAction::Updated,
+                    task_history::diff(Some(current), task),
+                );
+                (TaskWrite::Update(task.clone()), histories)
+            })
+            .collect();
+        self.write_batch(writes).await?;
+
+        let tasks: Vec<Task> = updates
+            .into_iter()
+            .map(|(_, task)| Task {
+                version: task.version + 1,
+                ..task
+            })
+            .collect();
+
+        for task in &tasks {
+            self.events.publish(TaskEventKind::Updated, task.clone());
+        }
+        Ok(tasks)
+    }
+
+    async fn batch_delete(
+        &self,
+        user_id: String,
+        ids: Vec<Uuid>,
+    ) -> Result<Vec<Uuid>, CustomError> {
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            results.push(self.find_authorized(&user_id, id, TaskAction::Manage).await);
+        }
+        let tasks = collect_batch(results)?;
+
+        let writes = tasks
+            .iter()
+            .map(|task| {
+                let histories = histories(task, &user_id, TaskHistoryAction::Deleted, vec![]);
+                (TaskWrite::Delete(task.id), histories)
+            })
+            .collect();
+        self.write_batch(writes).await?;
+
+        let ids = tasks.iter().map(|task| task.id).collect();
+        for task in tasks {
+            self.events.publish(TaskEventKind::Deleted, task);
+        }
+        Ok(ids)
+    }
+
+    async fn restore(&self, user_id: String, id: Uuid) -> Result<Uuid, CustomError> {
+        // 論理削除済みのタスクも所有者を確認する
+        let task = self.repository.find_with_deleted(id).await?;
+        self.authorize(&user_id, &task, TaskAction::Manage).await?;
+        let histories = histories(&task, &user_id, TaskHistoryAction::Restored, vec![]);
+        self.repository.restore(id, histories).await?;
+        // 復元したタスクは一覧に再び現れるため作成として通知する
+        self.events.publish(
+            TaskEventKind::Created,
+            Task {
+                deleted_at: None,
+                ..task
+            },
+        );
+        Ok(id)
+    }
+
+    async fn purge(&self, user_id: String, is_admin: bool, id: Uuid) -> Result<Uuid, CustomError> {
+        if !is_admin {
+            return Err(CustomError::PermissionDenied(format!(
+                "user {} cannot purge task {}: administrator only",
+                user_id, id
+            )));
+        }
+        let task = self.repository.find_with_deleted(id).await?;
+        // 子タスクが残ったまま親だけを消すことはしない
+        if self.repository.has_children(id).await? {
+            return Err(CustomError::HasSubtasks(id.to_string()));
+        }
+        // 履歴は完全に削除した後も閲覧できるように残す
+        let histories = histories(&task, &user_id, TaskHistoryAction::Purged, vec![]);
+        self.repository.purge(id, histories).await?;
+        // 論理削除済みのタスクは既に削除として通知している
+        if task.deleted_at.is_none() {
+            self.events.publish(TaskEventKind::Deleted, task);
+        }
+        Ok(id)
+    }
+
+    async fn change_status(
+        &self,
+        user_id: String,
+        id: Uuid,
+        status: TaskStatus,
+    ) -> Result<Task, CustomError> {
+        let task = self.find_authorized(&user_id, id, TaskAction::Edit).await?;
+        let mut updated_task = task.transition(status)?;
+        let mut next_task = None;
+        if status == TaskStatus::Done {
+            // 依存先のタスクが全て終わるまで完了にできない
+            self.ensure_prerequisites_completed(id).await?;
+            // 繰り返しタスクは次の回を作り、完了したタスクからはルールを外す
+            next_task = updated_task.next_occurrence();
+            if next_task.is_some() {
+                updated_task.recurrence = None;
+            }
+        }
+        let mut changes = histories(
+            &updated_task,
+            &user_id,
+            TaskHistoryAction::Updated,
+            task_history::diff(Some(&task), &updated_task),
+        );
+        match next_task {
+            Some(next_task) => {
+                changes.extend(histories(
+                    &next_task,
+                    &user_id,
+                    TaskHistoryAction::Created,
+                    task_history::diff(None, &next_task),
+                ));
+                self.repository
+                    .update_with_next_occurrence(updated_task.clone(), next_task.clone(), changes)
+                    .await?;
+                updated_task.version += 1;
+                self.events
+                    .publish(TaskEventKind::Updated, updated_task.clone());
+                self.events.publish(TaskEventKind::Created, next_task);
+            }
+            None => {
+                updated_task = self.save(updated_task, changes).await?;
+                self.events
+                    .publish(TaskEventKind::Updated, updated_task.clone());
+            }
+        }
+        Ok(updated_task)
+    }
+
+    async fn complete(&self, user_id: String, id: Uuid) -> Result<Task, CustomError> {
+        self.change_status(user_id, id, TaskStatus::Done).await
+    }
+
+    async fn reopen(&self, user_id: String, id: Uuid) -> Result<Task, CustomError> {
+        let task = self.find_authorized(&user_id, id, TaskAction::Edit).await?;
+        // 完了またはキャンセル済みのタスクのみ再開できる
+        if !task.status.is_closed() {
+            return Err(CustomError::InvalidStatusTransition(format!(
+                "{:?} task cannot be reopened",
+                task.status
+            )));
+        }
+        let updated_task = task.transition(TaskStatus::Todo)?;
+        let histories = histories(
+            &updated_task,
+            &user_id,
+            TaskHistoryAction::Updated,
+            task_history::diff(Some(&task), &updated_task),
+        );
+        let updated_task = self.save(updated_task, histories).await?;
+        self.events
+            .publish(TaskEventKind::Updated, updated_task.clone());
+        Ok(updated_task)
+    }
+
+    async fn share(
+        &self,
+        user_id: String,
+        id: Uuid,
+        target_user_id: String,
+    ) -> Result<Uuid, CustomError> {
+        let task = self
+            .find_authorized(&user_id, id, TaskAction::Manage)
+            .await?;
+        if task.user_id == target_user_id {
+            return Err(CustomError::invalid_argument(
+                "user_id",
+                "task cannot be shared with its owner",
+            ));
+        }
+        self.repository.share(id, target_user_id.clone()).await?;
+        self.events.publish_share(id, target_user_id, true);
+        Ok(id)
+    }
+
+    async fn unshare(
+        &self,
+        user_id: String,
+        id: Uuid,
+        target_user_id: String,
+    ) -> Result<Uuid, CustomError> {
+        self.find_authorized(&user_id, id, TaskAction::Manage)
+            .await?;
+        self.repository.unshare(id, target_user_id.clone()).await?;
+        self.events.publish_share(id, target_user_id, false);
+        Ok(id)
+    }
+
+    async fn add_dependency(
+        &self,
+        user_id: String,
+        id: Uuid,
+        depends_on_id: Uuid,
+    ) -> Result<Uuid, CustomError> {
+        if id == depends_on_id {
+            return Err(CustomError::invalid_argument(
+                "depends_on_task_id",
+                "task cannot depend on itself",
+            ));
+        }
+        self.find_authorized(&user_id, id, TaskAction::Edit).await?;
+        self.find_authorized(&user_id, depends_on_id, TaskAction::View)
+            .await?;
+        self.repository.add_dependency(id, depends_on_id).await
+    }
+
+    async fn remove_dependency(
+        &self,
+        user_id: String,
+        id: Uuid,
+        depends_on_id: Uuid,
+    ) -> Result<Uuid, CustomError> {
+        self.find_authorized(&user_id, id, TaskAction::Edit).await?;
+        self.repository.remove_dependency(id, depends_on_id).await
+    }
+
+    async fn dependency_order(&self, user_id: String) -> Result<Vec<Task>, CustomError> {
+        let tasks = self.repository.find_from_user_id(user_id).await?;
+        let dependencies = self
+            .repository
+            .find_dependencies(tasks.iter().map(|t| t.id).collect())
+            .await?;
+        task_dependency::topological_sort(tasks, &dependencies)
+    }
+
+    async fn plan(&self, user_id: String, options: PlanOptions) -> Result<Plan, CustomError> {
+        let tasks = self.repository.find_from_user_id(user_id).await?;
+        Ok(planner::generate_plan(&tasks, options))
+    }
+
+    async fn next_tasks(
+        &self,
+        user_id: String,
+        limit: usize,
+        weights: UrgencyWeights,
+    ) -> Result<Vec<ScoredTask>, CustomError> {
+        let tasks = self.repository.find_from_user_id(user_id).await?;
+        Ok(urgency::rank(
+            tasks,
+            OffsetDateTime::now_utc(),
+            &weights,
+            limit,
+        ))
+    }
+
+    async fn attach_tag(
+        &self,
+        user_id: String,
+        id: Uuid,
+        tag_id: Uuid,
+    ) -> Result<Uuid, CustomError> {
+        let task = self.find_authorized(&user_id, id, TaskAction::Edit).await?;
+        // タグはタスクの所有者のものだけ付けられる
+        let tag = self.repository.find_tag(tag_id).await?;
+        if tag.user_id != task.user_id {
+            return Err(CustomError::invalid_argument(
+                "tag_id",
+                "tag does not belong to the task owner",
+            ));
+        }
+        self.repository.attach_tag(id, tag_id).await
+    }
+
+    async fn detach_tag(
+        &self,
+        user_id: String,
+        id: Uuid,
+        tag_id: Uuid,
+    ) -> Result<Uuid, CustomError> {
+        self.find_authorized(&user_id, id, TaskAction::Edit).await?;
+        self.repository.detach_tag(id, tag_id).await
+    }
+
+    async fn history(&self, user_id: String, id: Uuid) -> Result<Vec<TaskHistory>, CustomError> {
+        // 完全に削除したタスクの履歴も閲覧できるように、権限は履歴に記録した所有者で確認する
+        let histories = self.repository.find_history(id).await?;
+        let Some(owner_id) = histories.last().map(|h| h.owner_id.clone()) else {
+            // 履歴の記録を始める前から変更されていないタスクには履歴が無い
+            let task = self.repository.find_with_deleted(id).await?;
+            self.authorize(&user_id, &task, TaskAction::View).await?;
+            return Ok(histories);
+        };
+        self.authorize_owner(&user_id, &owner_id, id, TaskAction::View)
+            .await?;
+        Ok(histories)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<TaskEvent> {
+        self.events.subscribe()
+    }
+
+    async fn watch(&self, user_id: String) -> Result<TaskWatch, CustomError> {
+        // 読み込んでいる間の共有の変更を取りこぼさないように、先に購読する
+        let events = self.events.subscribe();
+        let shares = self.events.subscribe_shares();
+        let shared_task_ids = self
+            .repository
+            .find_shared_task_ids(user_id.clone())
+            .await?
+            .into_iter()
+            .collect();
+        Ok(TaskWatch::new(user_id, events, shares, shared_task_ids))
+    }
+}
+
+// 読み込んだ後に他のクライアントが更新していれば上書きしない
+fn check_version(id: Uuid, expected: i32, current: &Task) -> Result<(), CustomError> {
+    if expected != current.version {
+        return Err(CustomError::VersionMismatch(format!(
+            "key: {}, expected version {}, found {}",
+            id, expected, current.version
+        )));
+    }
+    Ok(())
+}
+
+// 一括処理の1件分の書き込み
+enum TaskWrite {
+    Insert(Task),
+    // task.versionは読み込んだ時点のバージョン
+    Update(Task),
+    Delete(Uuid),
+}
+
+// 要素ごとの結果をまとめ、1件でも失敗していれば失敗した要素を全て返す
+fn collect_batch<T>(results: Vec<Result<T, CustomError>>) -> Result<Vec<T>, CustomError> {
+    let mut values = Vec::with_capacity(results.len());
+    let mut errors = Vec::new();
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(value) => values.push(value),
+            Err(error) => errors.push(BatchItemError::new(index, error)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(CustomError::BatchFailed(errors));
     }
+    Ok(values)
 }
 
 #[cfg(test)]
 mod tests {
 
-    use mockall::predicate::eq;
+    use mockall::predicate::{always, eq};
     use time::OffsetDateTime;
     use uuid::uuid;
 
     use super::*;
-    use crate::domain::{repository::task::MockTaskRepositoryTrait, task::Task};
+    use crate::domain::{
+        repository::{
+            task::MockTaskRepositoryTrait,
+            unit_of_work::{MockTransactionScopeTrait, MockUnitOfWorkTrait},
+        },
+        tag::Tag,
+        task::{test_task, Task},
+        task_dependency::TaskDependency,
+        task_query::{CursorValue, TaskSortKey},
+    };
 
     #[tokio::test]
     async fn test_task_insert() {
@@ -67,21 +878,21 @@ mod tests {
 
         let mut mock = MockTaskRepositoryTrait::default();
         mock.expect_insert()
-            .returning(|_| Box::pin(async { Ok(uuid!("00000000-0000-0000-0000-ffff00000000")) }));
+            .withf(|_, histories| {
+                matches!(histories.as_slice(), [h]
+                    if h.action == TaskHistoryAction::Created && h.actor_id == "testuserid")
+            })
+            .times(1)
+            .returning(|_, _| {
+                Box::pin(async { Ok(uuid!("00000000-0000-0000-0000-ffff00000000")) })
+            });
 
-        let usecase = TaskUsecase::new(Box::new(mock));
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
         let task = Task {
             id: test_uuid,
-            title: "test_title".to_string(),
-            description: "test_description".to_string(),
-            due_date: OffsetDateTime::now_utc(),
-            priority: 1,
-            weight: 1,
-            created_at: OffsetDateTime::now_utc(),
-            updated_at: OffsetDateTime::now_utc(),
-            user_id: "testuserid".to_string(),
+            ..test_task()
         };
-        let result = usecase.insert(task).await;
+        let result = usecase.insert("testuserid".to_string(), task).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), test_uuid);
     }
@@ -93,14 +904,7 @@ mod tests {
         let mut mock = MockTaskRepositoryTrait::default();
         let except_task = Task {
             id: test_uuid,
-            title: "test_title".to_string(),
-            description: "test_description".to_string(),
-            due_date: OffsetDateTime::now_utc(),
-            priority: 1,
-            weight: 1,
-            created_at: OffsetDateTime::now_utc(),
-            updated_at: OffsetDateTime::now_utc(),
-            user_id: "testuserid".to_string(),
+            ..test_task()
         };
         mock.expect_find()
             .with(eq(uuid!("00000000-0000-0000-0000-ffff00000000")))
@@ -111,9 +915,12 @@ mod tests {
                 })
             });
 
-        let usecase = TaskUsecase::new(Box::new(mock));
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
         let result = usecase
-            .find(uuid!("00000000-0000-0000-0000-ffff00000000"))
+            .find(
+                "testuserid".to_string(),
+                uuid!("00000000-0000-0000-0000-ffff00000000"),
+            )
             .await;
         assert!(result.is_ok());
     }
@@ -127,25 +934,13 @@ mod tests {
         let except_tasks = vec![
             Task {
                 id: test_uuid,
-                title: "test_title".to_string(),
-                description: "test_description".to_string(),
-                due_date: OffsetDateTime::now_utc(),
-                priority: 1,
-                weight: 1,
-                created_at: OffsetDateTime::now_utc(),
-                updated_at: OffsetDateTime::now_utc(),
                 user_id: "harukun".to_string(),
+                ..test_task()
             },
             Task {
                 id: test_uuid2,
-                title: "test_title".to_string(),
-                description: "test_description".to_string(),
-                due_date: OffsetDateTime::now_utc(),
-                priority: 1,
-                weight: 1,
-                created_at: OffsetDateTime::now_utc(),
-                updated_at: OffsetDateTime::now_utc(),
                 user_id: "harukun".to_string(),
+                ..test_task()
             },
         ];
         mock.expect_find_from_user_id()
@@ -157,35 +952,109 @@ mod tests {
                 })
             });
 
-        let usecase = TaskUsecase::new(Box::new(mock));
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
         let result = usecase.find_from_user_id("harukun".to_string()).await;
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_task_list_has_next_page() {
+        let tasks = vec![
+            create_test_task(uuid!("00000000-0000-0000-0000-ffff00000000")),
+            create_test_task(uuid!("00000000-0000-0000-0000-ffff00000001")),
+            create_test_task(uuid!("00000000-0000-0000-0000-ffff00000002")),
+        ];
+        let mut query = TaskQuery::new("testuserid".to_string());
+        query.sort_key = TaskSortKey::Priority;
+        query.page_size = 2;
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_search()
+            .with(eq(query.clone()), eq(3))
+            .returning(move |_, _| {
+                Box::pin({
+                    let value = tasks.clone();
+                    async move { Ok(value) }
+                })
+            });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let page = usecase.list(query).await.unwrap();
+        assert_eq!(page.tasks.len(), 2);
+        let cursor = page.next_cursor.unwrap();
+        assert_eq!(cursor.id, uuid!("00000000-0000-0000-0000-ffff00000001"));
+        assert_eq!(cursor.sort_key, TaskSortKey::Priority);
+        assert_eq!(cursor.value, CursorValue::Int(1));
+    }
+
+    #[tokio::test]
+    async fn test_task_list_last_page() {
+        let tasks = vec![
+            create_test_task(uuid!("00000000-0000-0000-0000-ffff00000000")),
+            create_test_task(uuid!("00000000-0000-0000-0000-ffff00000001")),
+        ];
+        let mut query = TaskQuery::new("testuserid".to_string());
+        query.page_size = 2;
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_search()
+            .with(eq(query.clone()), eq(3))
+            .returning(move |_, _| {
+                Box::pin({
+                    let value = tasks.clone();
+                    async move { Ok(value) }
+                })
+            });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let page = usecase.list(query).await.unwrap();
+        assert_eq!(page.tasks.len(), 2);
+        assert!(page.next_cursor.is_none());
+    }
+
     #[tokio::test]
     async fn test_task_update_success() {
         // テストデータの準備
         let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
         let original_task = create_test_task(test_uuid);
-        let mut updated_task = original_task.clone();
-        updated_task.title = "updated_title".to_string();
-        updated_task.description = "updated_description".to_string();
-        updated_task.priority = 2;
-        updated_task.weight = 2;
+        let patch = TaskPatch {
+            title: Some("updated_title".to_string()),
+            description: Some("updated_description".to_string()),
+            priority: Some(2),
+            ..empty_patch(test_uuid)
+        };
 
         // モックの設定
         let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = original_task.clone();
+                async move { Ok(value) }
+            })
+        });
+        // 指定しなかったフィールドは変更しない
         mock.expect_update()
-            .with(eq(updated_task.clone()))
-            .returning(move |_| Box::pin(async move { Ok(test_uuid) }));
+            .withf(|t, histories| {
+                t.title == "updated_title"
+                    && t.description == "updated_description"
+                    && t.priority == 2
+                    && t.weight == 1
+                    && t.version == 1
+                    && matches!(histories.as_slice(), [h]
+                        if h.action == TaskHistoryAction::Updated
+                            && h.changes().iter().any(|c| c.field == "title"))
+            })
+            .times(1)
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
 
         // テストの実行
-        let usecase = TaskUsecase::new(Box::new(mock));
-        let result = usecase.update(updated_task).await;
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.update("testuserid".to_string(), patch).await;
 
         // 結果の検証
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), test_uuid);
+        let task = result.unwrap();
+        assert_eq!(task.id, test_uuid);
+        assert_eq!(task.version, 2);
     }
 
     #[tokio::test]
@@ -196,19 +1065,26 @@ mod tests {
 
         // モックの設定
         let mut mock = MockTaskRepositoryTrait::default();
-        mock.expect_update()
-            .with(eq(task.clone()))
-            .returning(move |_| {
-                Box::pin(async move {
-                    Err(CustomError::Db(sea_orm::DbErr::RecordNotFound(
-                        "Update failed".to_string(),
-                    )))
-                })
-            });
+        let existing_task = task.clone();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = existing_task.clone();
+                async move { Ok(value) }
+            })
+        });
+        mock.expect_update().returning(move |_, _| {
+            Box::pin(async move {
+                Err(CustomError::Db(sea_orm::DbErr::RecordNotFound(
+                    "Update failed".to_string(),
+                )))
+            })
+        });
 
         // テストの実行
-        let usecase = TaskUsecase::new(Box::new(mock));
-        let result = usecase.update(task).await;
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .update("testuserid".to_string(), empty_patch(test_uuid))
+            .await;
 
         // 結果の検証
         assert!(result.is_err());
@@ -220,17 +1096,1437 @@ mod tests {
         }
     }
 
-    fn create_test_task(id: Uuid) -> Task {
-        Task {
-            id,
-            title: "test_title".to_string(),
-            description: "test_description".to_string(),
-            due_date: OffsetDateTime::now_utc(),
-            priority: 1,
-            weight: 1,
-            created_at: OffsetDateTime::now_utc(),
-            updated_at: OffsetDateTime::now_utc(),
-            user_id: "testuserid".to_string(),
+    #[tokio::test]
+    async fn test_task_delete() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_delete()
+            .with(eq(test_uuid), always())
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.delete("testuserid".to_string(), test_uuid).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), test_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_delete_not_found() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |id| {
+            Box::pin(async move { Err(CustomError::NotFound(format!("key: {}", id))) })
+        });
+        mock.expect_delete().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.delete("testuserid".to_string(), test_uuid).await;
+        assert!(matches!(result, Err(CustomError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_restore() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_with_deleted()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_restore()
+            .with(eq(test_uuid), always())
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.restore("testuserid".to_string(), test_uuid).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), test_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_purge() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_with_deleted()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_has_children()
+            .with(eq(test_uuid))
+            .returning(|_| Box::pin(async { Ok(false) }));
+        mock.expect_purge()
+            .withf(move |id, histories| {
+                *id == test_uuid
+                    && matches!(histories.as_slice(), [h]
+                        if h.action == TaskHistoryAction::Purged
+                            && h.owner_id == "testuserid"
+                            && h.actor_id == "adminuser")
+            })
+            .times(1)
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .purge("adminuser".to_string(), true, test_uuid)
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), test_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_purge_with_subtasks() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_with_deleted()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_has_children()
+            .with(eq(test_uuid))
+            .returning(|_| Box::pin(async { Ok(true) }));
+        mock.expect_purge().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .purge("adminuser".to_string(), true, test_uuid)
+            .await;
+        assert!(matches!(result, Err(CustomError::HasSubtasks(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_complete() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let task = create_test_task(test_uuid);
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = task.clone();
+                async move { Ok(value) }
+            })
+        });
+        // 完了済みの依存先は完了を妨げない
+        mock.expect_find_prerequisites()
+            .with(eq(test_uuid))
+            .returning(|_| {
+                Box::pin(async {
+                    let mut prerequisite =
+                        create_test_task(uuid!("00000000-0000-0000-0000-ffff00000001"));
+                    prerequisite.status = TaskStatus::Done;
+                    Ok(vec![prerequisite])
+                })
+            });
+        mock.expect_update()
+            .withf(|t, _| t.status == TaskStatus::Done && t.completed_at.is_some())
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.complete("testuserid".to_string(), test_uuid).await;
+        assert!(result.is_ok());
+        let completed_task = result.unwrap();
+        assert_eq!(completed_task.status, TaskStatus::Done);
+        assert!(completed_task.completed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_task_complete_already_done() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let mut task = create_test_task(test_uuid);
+        task.status = TaskStatus::Done;
+        task.completed_at = Some(OffsetDateTime::now_utc());
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = task.clone();
+                async move { Ok(value) }
+            })
+        });
+        mock.expect_update().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.complete("testuserid".to_string(), test_uuid).await;
+        assert!(matches!(
+            result,
+            Err(CustomError::InvalidStatusTransition(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_task_reopen() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let mut task = create_test_task(test_uuid);
+        task.status = TaskStatus::Done;
+        task.completed_at = Some(OffsetDateTime::now_utc());
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = task.clone();
+                async move { Ok(value) }
+            })
+        });
+        mock.expect_update()
+            .withf(|t, _| t.status == TaskStatus::Todo && t.completed_at.is_none())
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.reopen("testuserid".to_string(), test_uuid).await;
+        assert!(result.is_ok());
+        let reopened_task = result.unwrap();
+        assert_eq!(reopened_task.status, TaskStatus::Todo);
+        assert!(reopened_task.completed_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_task_reopen_open_task() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let mut task = create_test_task(test_uuid);
+        task.status = TaskStatus::InProgress;
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = task.clone();
+                async move { Ok(value) }
+            })
+        });
+        mock.expect_update().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.reopen("testuserid".to_string(), test_uuid).await;
+        assert!(matches!(
+            result,
+            Err(CustomError::InvalidStatusTransition(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_task_change_status() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let task = create_test_task(test_uuid);
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = task.clone();
+                async move { Ok(value) }
+            })
+        });
+        mock.expect_update()
+            .withf(|t, _| t.status == TaskStatus::InProgress)
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .change_status("testuserid".to_string(), test_uuid, TaskStatus::InProgress)
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().status, TaskStatus::InProgress);
+    }
+
+    #[tokio::test]
+    async fn test_task_change_status_invalid_transition() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let mut task = create_test_task(test_uuid);
+        task.status = TaskStatus::Cancelled;
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = task.clone();
+                async move { Ok(value) }
+            })
+        });
+        mock.expect_update().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .change_status("testuserid".to_string(), test_uuid, TaskStatus::Done)
+            .await;
+        assert!(matches!(
+            result,
+            Err(CustomError::InvalidStatusTransition(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_task_find_other_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .with(eq(test_uuid), eq("otheruser".to_string()))
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.find("otheruser".to_string(), test_uuid).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_find_shared_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .with(eq(test_uuid), eq("shareduser".to_string()))
+            .returning(|_, _| Box::pin(async { Ok(true) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.find("shareduser".to_string(), test_uuid).await;
+        assert_eq!(result.unwrap().user_id, "testuserid");
+    }
+
+    #[tokio::test]
+    async fn test_task_update_other_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let patch = TaskPatch {
+            title: Some("hijacked".to_string()),
+            ..empty_patch(test_uuid)
+        };
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+        mock.expect_update().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.update("otheruser".to_string(), patch).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_update_version_mismatch() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let patch = TaskPatch {
+            title: Some("stale".to_string()),
+            ..empty_patch(test_uuid)
+        };
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |id| {
+            Box::pin(async move {
+                Ok(Task {
+                    version: 2,
+                    ..create_test_task(id)
+                })
+            })
+        });
+        mock.expect_update().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.update("testuserid".to_string(), patch).await;
+        assert!(matches!(result, Err(CustomError::VersionMismatch(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_update_shared_user_keeps_owner() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let patch = TaskPatch {
+            title: Some("updated_title".to_string()),
+            ..empty_patch(test_uuid)
+        };
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(true) }));
+        mock.expect_update()
+            .withf(|t, _| t.title == "updated_title" && t.user_id == "testuserid")
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.update("shareduser".to_string(), patch).await;
+        assert_eq!(result.unwrap().user_id, "testuserid");
+    }
+
+    #[tokio::test]
+    async fn test_task_delete_shared_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        // 削除は共有されていても所有者のみ
+        mock.expect_is_shared_with().never();
+        mock.expect_delete().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.delete("shareduser".to_string(), test_uuid).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_restore_other_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_with_deleted()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_restore().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.restore("otheruser".to_string(), test_uuid).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_purge_not_admin() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        // 所有者であっても管理者でなければ完全に削除できない
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_with_deleted().never();
+        mock.expect_purge().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .purge("testuserid".to_string(), false, test_uuid)
+            .await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_complete_other_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+        mock.expect_update().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.complete("otheruser".to_string(), test_uuid).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_share() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_share()
+            .with(eq(test_uuid), eq("shareduser".to_string()))
+            .returning(move |id, _| Box::pin(async move { Ok(id) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .share(
+                "testuserid".to_string(),
+                test_uuid,
+                "shareduser".to_string(),
+            )
+            .await;
+        assert_eq!(result.unwrap(), test_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_share_with_owner() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_share().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .share(
+                "testuserid".to_string(),
+                test_uuid,
+                "testuserid".to_string(),
+            )
+            .await;
+        assert!(matches!(result, Err(CustomError::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_unshare_shared_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_unshare().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .unshare(
+                "shareduser".to_string(),
+                test_uuid,
+                "shareduser".to_string(),
+            )
+            .await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_insert_subtask() {
+        let parent_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let child_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+        let mut child = create_test_task(child_uuid);
+        child.parent_id = Some(parent_uuid);
+        child.user_id = "shareduser".to_string();
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(parent_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(true) }));
+        // 子タスクの所有者は親タスクの所有者になる
+        mock.expect_insert()
+            .withf(move |t, _| t.parent_id == Some(parent_uuid) && t.user_id == "testuserid")
+            .returning(move |_, _| Box::pin(async move { Ok(child_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.insert("shareduser".to_string(), child).await;
+        assert_eq!(result.unwrap(), child_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_insert_subtask_other_user() {
+        let parent_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let mut child = create_test_task(uuid!("00000000-0000-0000-0000-ffff00000001"));
+        child.parent_id = Some(parent_uuid);
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(parent_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+        mock.expect_insert().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.insert("otheruser".to_string(), child).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_subtree() {
+        let root_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let child_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+        let grandchild_uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+        let mut child = create_test_task(child_uuid);
+        child.parent_id = Some(root_uuid);
+        child.weight = 3;
+        let mut grandchild = create_test_task(grandchild_uuid);
+        grandchild.parent_id = Some(child_uuid);
+        grandchild.weight = 2;
+        grandchild.status = TaskStatus::Done;
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(root_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_find_children()
+            .with(eq(vec![root_uuid]))
+            .returning(move |_| {
+                Box::pin({
+                    let value = vec![child.clone()];
+                    async move { Ok(value) }
+                })
+            });
+        mock.expect_find_children()
+            .with(eq(vec![child_uuid]))
+            .returning(move |_| {
+                Box::pin({
+                    let value = vec![grandchild.clone()];
+                    async move { Ok(value) }
+                })
+            });
+        mock.expect_find_children()
+            .with(eq(vec![grandchild_uuid]))
+            .returning(|_| Box::pin(async { Ok(vec![]) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let node = usecase
+            .subtree("testuserid".to_string(), root_uuid)
+            .await
+            .unwrap();
+        assert_eq!(node.children.len(), 1);
+        assert_eq!(node.children[0].children[0].task.id, grandchild_uuid);
+        // 子タスクの重みは孫タスクから集計される
+        assert_eq!((node.total_weight, node.completed_weight), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_task_subtree_other_user() {
+        let root_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(root_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+        mock.expect_find_children().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.subtree("otheruser".to_string(), root_uuid).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_complete_recurring() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let mut task = create_test_task(test_uuid);
+        task.recurrence = Some("FREQ=WEEKLY;COUNT=3".to_string());
+        let due_date = task.due_date;
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = task.clone();
+                async move { Ok(value) }
+            })
+        });
+        mock.expect_find_prerequisites()
+            .returning(|_| Box::pin(async { Ok(vec![]) }));
+        mock.expect_update().never();
+        mock.expect_insert().never();
+        mock.expect_update_with_next_occurrence()
+            .withf(move |t, next, histories| {
+                histories.len() == 2
+                    && t.status == TaskStatus::Done
+                    && t.recurrence.is_none()
+                    && next.id != test_uuid
+                    && next.status == TaskStatus::Todo
+                    && next.completed_at.is_none()
+                    && next.due_date == due_date + time::Duration::weeks(1)
+                    && next.recurrence.as_deref() == Some("FREQ=WEEKLY;COUNT=2")
+                    && next.user_id == "testuserid"
+            })
+            .times(1)
+            .returning(move |_, _, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.complete("testuserid".to_string(), test_uuid).await;
+        assert_eq!(result.unwrap().status, TaskStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn test_task_complete_last_occurrence() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let mut task = create_test_task(test_uuid);
+        task.recurrence = Some("FREQ=DAILY;COUNT=1".to_string());
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            Box::pin({
+                let value = task.clone();
+                async move { Ok(value) }
+            })
+        });
+        mock.expect_find_prerequisites()
+            .returning(|_| Box::pin(async { Ok(vec![]) }));
+        mock.expect_update()
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+        mock.expect_update_with_next_occurrence().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.complete("testuserid".to_string(), test_uuid).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_task_complete_blocked_by_prerequisite() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let prerequisite_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_find_prerequisites()
+            .with(eq(test_uuid))
+            .returning(move |_| {
+                Box::pin(async move {
+                    let mut done = create_test_task(uuid!("00000000-0000-0000-0000-ffff00000002"));
+                    done.status = TaskStatus::Done;
+                    Ok(vec![create_test_task(prerequisite_uuid), done])
+                })
+            });
+        mock.expect_update().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.complete("testuserid".to_string(), test_uuid).await;
+        match result {
+            Err(CustomError::PrerequisitesNotCompleted(message)) => {
+                assert!(message.contains(&prerequisite_uuid.to_string()));
+                assert!(!message.contains("ffff00000002"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_task_add_dependency() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let prerequisite_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_add_dependency()
+            .with(eq(test_uuid), eq(prerequisite_uuid))
+            .times(1)
+            .returning(|id, _| Box::pin(async move { Ok(id) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .add_dependency("testuserid".to_string(), test_uuid, prerequisite_uuid)
+            .await;
+        assert_eq!(result.unwrap(), test_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_add_dependency_self() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_add_dependency().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .add_dependency("testuserid".to_string(), test_uuid, test_uuid)
+            .await;
+        assert!(matches!(result, Err(CustomError::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_add_dependency_cycle() {
+        // 循環の確認は追加と同じトランザクションでリポジトリが行う
+        let a = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let b = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_add_dependency()
+            .with(eq(a), eq(b))
+            .returning(|id, depends_on_id| {
+                Box::pin(async move {
+                    Err(CustomError::DependencyCycle(format!(
+                        "task {} already depends on task {}",
+                        depends_on_id, id
+                    )))
+                })
+            });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.add_dependency("testuserid".to_string(), a, b).await;
+        assert!(matches!(result, Err(CustomError::DependencyCycle(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_add_dependency_other_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let prerequisite_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+        mock.expect_add_dependency().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .add_dependency("otheruser".to_string(), test_uuid, prerequisite_uuid)
+            .await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_dependency_order() {
+        let first = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let second = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_from_user_id()
+            .with(eq("testuserid".to_string()))
+            .returning(move |_| {
+                Box::pin(async move { Ok(vec![create_test_task(first), create_test_task(second)]) })
+            });
+        // firstはsecondの完了を待つ
+        mock.expect_find_dependencies().returning(move |_| {
+            Box::pin(async move { Ok(vec![create_dependency(first, second)]) })
+        });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.dependency_order("testuserid".to_string()).await;
+        let ids: Vec<Uuid> = result.unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![second, first]);
+    }
+
+    #[tokio::test]
+    async fn test_task_plan() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let closed_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_from_user_id()
+            .with(eq("testuserid".to_string()))
+            .returning(move |_| {
+                Box::pin(async move {
+                    let mut closed = create_test_task(closed_uuid);
+                    closed.status = TaskStatus::Cancelled;
+                    Ok(vec![create_test_task(test_uuid), closed])
+                })
+            });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let options = PlanOptions {
+            start_date: OffsetDateTime::now_utc().date(),
+            hours_per_day: 8,
+            days: 1,
+        };
+        let plan = usecase
+            .plan("testuserid".to_string(), options)
+            .await
+            .unwrap();
+        assert_eq!(plan.days.len(), 1);
+        assert_eq!(plan.days[0].entries.len(), 1);
+        assert_eq!(plan.days[0].entries[0].task_id, test_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_next_tasks() {
+        let urgent_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let later_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+        let low_uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_from_user_id()
+            .with(eq("testuserid".to_string()))
+            .returning(move |_| {
+                Box::pin(async move {
+                    let mut urgent = create_test_task(urgent_uuid);
+                    urgent.priority = 5;
+                    let mut later = create_test_task(later_uuid);
+                    later.priority = 5;
+                    later.due_date += time::Duration::days(30);
+                    let mut low = create_test_task(low_uuid);
+                    low.due_date += time::Duration::days(30);
+                    Ok(vec![low, later, urgent])
+                })
+            });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .next_tasks("testuserid".to_string(), 2, UrgencyWeights::default())
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = result.iter().map(|s| s.task.id).collect();
+        assert_eq!(ids, vec![urgent_uuid, later_uuid]);
+    }
+
+    #[tokio::test]
+    async fn test_task_attach_tag() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let tag_uuid = uuid!("00000000-0000-0000-0000-eeee00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_find_tag()
+            .with(eq(tag_uuid))
+            .returning(|id| Box::pin(async move { Ok(create_test_tag(id, "testuserid")) }));
+        mock.expect_attach_tag()
+            .with(eq(test_uuid), eq(tag_uuid))
+            .times(1)
+            .returning(|id, _| Box::pin(async move { Ok(id) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .attach_tag("testuserid".to_string(), test_uuid, tag_uuid)
+            .await;
+        assert_eq!(result.unwrap(), test_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_attach_tag_of_other_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let tag_uuid = uuid!("00000000-0000-0000-0000-eeee00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_find_tag()
+            .with(eq(tag_uuid))
+            .returning(|id| Box::pin(async move { Ok(create_test_tag(id, "otheruser")) }));
+        mock.expect_attach_tag().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .attach_tag("testuserid".to_string(), test_uuid, tag_uuid)
+            .await;
+        assert!(matches!(result, Err(CustomError::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_detach_tag_other_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let tag_uuid = uuid!("00000000-0000-0000-0000-eeee00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+        mock.expect_detach_tag().never();
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .detach_tag("otheruser".to_string(), test_uuid, tag_uuid)
+            .await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_events_published() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_insert()
+            .returning(|t, _| Box::pin(async move { Ok(t.id) }));
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_delete()
+            .with(eq(test_uuid), always())
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let mut events = usecase.subscribe();
+        usecase
+            .insert("testuserid".to_string(), create_test_task(test_uuid))
+            .await
+            .unwrap();
+        usecase
+            .delete("testuserid".to_string(), test_uuid)
+            .await
+            .unwrap();
+
+        let created = events.try_recv().unwrap();
+        assert_eq!(created.kind, TaskEventKind::Created);
+        assert_eq!(created.task.id, test_uuid);
+        let deleted = events.try_recv().unwrap();
+        assert_eq!(deleted.kind, TaskEventKind::Deleted);
+        assert_eq!(deleted.task.id, test_uuid);
+    }
+
+    #[tokio::test]
+    async fn test_task_event_not_published_on_failure() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_update().returning(|t, _| {
+            Box::pin(async move { Err(CustomError::NotFound(format!("key: {}", t.id))) })
+        });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let mut events = usecase.subscribe();
+        let result = usecase
+            .update("testuserid".to_string(), empty_patch(test_uuid))
+            .await;
+        assert!(result.is_err());
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_task_watch() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        // 共有されたタスクは購読開始時に一度だけ読み込み、イベントごとには確認しない
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_shared_task_ids()
+            .with(eq("shareduser".to_string()))
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(vec![]) }));
+        mock.expect_find_shared_task_ids()
+            .with(eq("otheruser".to_string()))
+            .times(1)
+            .returning(|_| Box::pin(async { Ok(vec![]) }));
+        mock.expect_is_shared_with().never();
+        mock.expect_find()
+            .with(eq(test_uuid))
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_share()
+            .returning(move |id, _| Box::pin(async move { Ok(id) }));
+        mock.expect_update()
+            .returning(|t, _| Box::pin(async move { Ok(t.id) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let mut shared_watch = usecase.watch("shareduser".to_string()).await.unwrap();
+        let mut other_watch = usecase.watch("otheruser".to_string()).await.unwrap();
+        // 購読を始めた後に共有されたタスクのイベントも届く
+        usecase
+            .share(
+                "testuserid".to_string(),
+                test_uuid,
+                "shareduser".to_string(),
+            )
+            .await
+            .unwrap();
+        usecase
+            .update(
+                "testuserid".to_string(),
+                TaskPatch {
+                    title: Some("updated".to_string()),
+                    ..empty_patch(test_uuid)
+                },
+            )
+            .await
+            .unwrap();
+
+        let event = shared_watch.recv().await.unwrap();
+        assert_eq!(event.kind, TaskEventKind::Updated);
+        assert_eq!(event.task.id, test_uuid);
+        // 所有者でも共有先でもないユーザーには届かない
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(50), other_watch.recv())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_update_unchanged_skips_history() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let task = create_test_task(test_uuid);
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        let current = task.clone();
+        mock.expect_find().with(eq(test_uuid)).returning(move |_| {
+            let value = current.clone();
+            Box::pin(async move { Ok(value) })
+        });
+        mock.expect_update()
+            .withf(|_, histories| histories.is_empty())
+            .times(1)
+            .returning(move |_, _| Box::pin(async move { Ok(test_uuid) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .update("testuserid".to_string(), empty_patch(test_uuid))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    fn create_test_histories(id: Uuid) -> Vec<TaskHistory> {
+        vec![TaskHistory::record(
+            &create_test_task(id),
+            "testuserid".to_string(),
+            TaskHistoryAction::Created,
+            vec![],
+        )]
+    }
+
+    #[tokio::test]
+    async fn test_task_history() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_is_shared_with()
+            .with(eq(test_uuid), eq("frienduser".to_string()))
+            .returning(|_, _| Box::pin(async { Ok(true) }));
+        mock.expect_find_history()
+            .with(eq(test_uuid))
+            .returning(|id| Box::pin(async move { Ok(create_test_histories(id)) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.history("frienduser".to_string(), test_uuid).await;
+        assert_eq!(result.unwrap()[0].action, TaskHistoryAction::Created);
+    }
+
+    #[tokio::test]
+    async fn test_task_history_other_user() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+        mock.expect_find_history()
+            .with(eq(test_uuid))
+            .returning(|id| Box::pin(async move { Ok(create_test_histories(id)) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.history("otheruser".to_string(), test_uuid).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_history_not_found() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_history()
+            .with(eq(test_uuid))
+            .returning(|_| Box::pin(async { Ok(vec![]) }));
+        mock.expect_find_with_deleted()
+            .with(eq(test_uuid))
+            .returning(|id| {
+                Box::pin(async move { Err(CustomError::NotFound(format!("key: {}", id))) })
+            });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.history("testuserid".to_string(), test_uuid).await;
+        assert!(matches!(result, Err(CustomError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_history_without_records() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        // 履歴の記録を始める前に作成され、その後変更されていないタスク
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find_history()
+            .with(eq(test_uuid))
+            .returning(|_| Box::pin(async { Ok(vec![]) }));
+        mock.expect_find_with_deleted()
+            .with(eq(test_uuid))
+            .returning(|id| Box::pin(async move { Ok(create_test_task(id)) }));
+        mock.expect_is_shared_with()
+            .returning(|_, _| Box::pin(async { Ok(false) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase.history("testuserid".to_string(), test_uuid).await;
+        assert!(result.unwrap().is_empty());
+        let result = usecase.history("otheruser".to_string(), test_uuid).await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_task_batch_insert() {
+        let parent_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let child_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+        let parent = create_test_task(parent_uuid);
+        let mut child = create_test_task(child_uuid);
+        child.parent_id = Some(parent_uuid);
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        // 同じバッチの親タスクはDBから読み込まない
+        mock.expect_find().never();
+        let mut txn_tasks = MockTaskRepositoryTrait::default();
+        txn_tasks
+            .expect_insert()
+            .withf(|_, histories| {
+                histories.len() == 1 && histories[0].action == TaskHistoryAction::Created
+            })
+            .times(2)
+            .returning(|task, _| Box::pin(async move { Ok(task.id) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), mock_unit_of_work(txn_tasks, 1));
+        let result = usecase
+            .batch_insert("testuserid".to_string(), vec![parent, child])
+            .await;
+        assert_eq!(result.unwrap(), vec![parent_uuid, child_uuid]);
+    }
+
+    #[tokio::test]
+    async fn test_task_batch_insert_reports_all_failures() {
+        let parent_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let mut first = create_test_task(uuid!("00000000-0000-0000-0000-ffff00000001"));
+        first.parent_id = Some(parent_uuid);
+        let second = create_test_task(uuid!("00000000-0000-0000-0000-ffff00000002"));
+        let mut third = create_test_task(uuid!("00000000-0000-0000-0000-ffff00000003"));
+        third.parent_id = Some(parent_uuid);
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().with(eq(parent_uuid)).returning(|id| {
+            Box::pin(async move { Err(CustomError::NotFound(format!("key: {}", id))) })
+        });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .batch_insert("testuserid".to_string(), vec![first, second, third])
+            .await;
+        match result {
+            Err(CustomError::BatchFailed(items)) => {
+                assert_eq!(
+                    items.iter().map(|item| item.index).collect::<Vec<_>>(),
+                    vec![0, 2]
+                );
+                assert!(matches!(items[0].error, CustomError::NotFound(_)));
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_task_batch_update() {
+        let first_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let second_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        let mut txn_tasks = MockTaskRepositoryTrait::default();
+        txn_tasks
+            .expect_update()
+            .withf(move |task, histories| {
+                task.id == first_uuid
+                    && task.title == "new_title"
+                    && task.version == 1
+                    && histories.len() == 1
+            })
+            .times(1)
+            .returning(|task, _| Box::pin(async move { Ok(task.id) }));
+        // 値が変わらなかった2件目は履歴に残さない
+        txn_tasks
+            .expect_update()
+            .withf(move |task, histories| task.id == second_uuid && histories.is_empty())
+            .times(1)
+            .returning(|task, _| Box::pin(async move { Ok(task.id) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), mock_unit_of_work(txn_tasks, 1));
+        let result = usecase
+            .batch_update(
+                "testuserid".to_string(),
+                vec![
+                    TaskPatch {
+                        id: first_uuid,
+                        version: 1,
+                        title: Some("new_title".to_string()),
+                        ..Default::default()
+                    },
+                    TaskPatch {
+                        id: second_uuid,
+                        version: 1,
+                        ..Default::default()
+                    },
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(result[0].title, "new_title");
+        assert_eq!(
+            result.iter().map(|t| t.version).collect::<Vec<_>>(),
+            vec![2, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_batch_update_version_mismatch() {
+        let test_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .batch_update(
+                "testuserid".to_string(),
+                vec![TaskPatch {
+                    id: test_uuid,
+                    version: 3,
+                    ..Default::default()
+                }],
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(CustomError::BatchFailed(items))
+                if matches!(items[0].error, CustomError::VersionMismatch(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_task_batch_delete() {
+        let own_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let other_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find().returning(move |id| {
+            Box::pin(async move {
+                let mut task = create_test_task(id);
+                if id == other_uuid {
+                    task.user_id = "otheruser".to_string();
+                }
+                Ok(task)
+            })
+        });
+
+        let usecase = TaskUsecase::new(Box::new(mock), Box::new(MockUnitOfWorkTrait::default()));
+        let result = usecase
+            .batch_delete("testuserid".to_string(), vec![own_uuid, other_uuid])
+            .await;
+        assert!(matches!(
+            result,
+            Err(CustomError::BatchFailed(items))
+                if items.len() == 1
+                    && items[0].index == 1
+                    && matches!(items[0].error, CustomError::PermissionDenied(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_task_batch_delete_rolls_back_on_write_failure() {
+        let first_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let second_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        let mut txn_tasks = MockTaskRepositoryTrait::default();
+        txn_tasks
+            .expect_delete()
+            .with(eq(first_uuid), always())
+            .returning(|id, _| Box::pin(async move { Ok(id) }));
+        txn_tasks
+            .expect_delete()
+            .with(eq(second_uuid), always())
+            .returning(|id, _| {
+                Box::pin(async move { Err(CustomError::NotFound(format!("key: {}", id))) })
+            });
+
+        // 2件目で失敗した場合はcommitしない
+        let usecase = TaskUsecase::new(Box::new(mock), mock_unit_of_work(txn_tasks, 0));
+        let result = usecase
+            .batch_delete("testuserid".to_string(), vec![first_uuid, second_uuid])
+            .await;
+        assert!(matches!(
+            result,
+            Err(CustomError::BatchFailed(items))
+                if items.len() == 1
+                    && items[0].index == 1
+                    && matches!(items[0].error, CustomError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_task_batch_update_reports_all_write_failures() {
+        let first_uuid = uuid!("00000000-0000-0000-0000-ffff00000000");
+        let second_uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+        let third_uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+
+        let mut mock = MockTaskRepositoryTrait::default();
+        mock.expect_find()
+            .returning(move |id| Box::pin(async move { Ok(create_test_task(id)) }));
+        // 1件目と3件目は他の書き込みと競合し、書き込み時に失敗する
+        let mut txn_tasks = MockTaskRepositoryTrait::default();
+        txn_tasks
+            .expect_update()
+            .withf(move |task, _| task.id == second_uuid)
+            .times(1)
+            .returning(|task, _| Box::pin(async move { Ok(task.id) }));
+        txn_tasks
+            .expect_update()
+            .withf(move |task, _| task.id == first_uuid || task.id == third_uuid)
+            .times(2)
+            .returning(|task, _| {
+                Box::pin(
+                    async move { Err(CustomError::VersionMismatch(format!("key: {}", task.id))) },
+                )
+            });
+
+        let usecase = TaskUsecase::new(Box::new(mock), mock_unit_of_work(txn_tasks, 0));
+        let patches = [first_uuid, second_uuid, third_uuid]
+            .into_iter()
+            .map(|id| TaskPatch {
+                id,
+                version: 1,
+                title: Some("new_title".to_string()),
+                ..Default::default()
+            })
+            .collect();
+        let result = usecase
+            .batch_update("testuserid".to_string(), patches)
+            .await;
+        match result {
+            Err(CustomError::BatchFailed(items)) => {
+                assert_eq!(
+                    items.iter().map(|item| item.index).collect::<Vec<_>>(),
+                    vec![0, 2]
+                );
+                assert!(items
+                    .iter()
+                    .all(|item| matches!(item.error, CustomError::VersionMismatch(_))));
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    fn mock_unit_of_work(
+        tasks: MockTaskRepositoryTrait,
+        commits: usize,
+    ) -> Box<MockUnitOfWorkTrait> {
+        let mut txn = MockTransactionScopeTrait::default();
+        txn.expect_tasks().return_const(tasks);
+        txn.expect_commit()
+            .times(commits)
+            .returning(|| Box::pin(async { Ok(()) }));
+        let mut unit_of_work = MockUnitOfWorkTrait::default();
+        unit_of_work
+            .expect_begin()
+            .return_once(move || Box::pin(async move { Ok(txn) }));
+        Box::new(unit_of_work)
+    }
+
+    fn create_test_tag(id: Uuid, user_id: &str) -> Tag {
+        Tag {
+            id,
+            user_id: user_id.to_string(),
+            name: "test_tag".to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn create_dependency(task_id: Uuid, depends_on_id: Uuid) -> TaskDependency {
+        TaskDependency {
+            task_id,
+            depends_on_id,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn create_test_task(id: Uuid) -> Task {
+        Task { id, ..test_task() }
+    }
+
+    // 何も変更しないパッチ
+    fn empty_patch(id: Uuid) -> TaskPatch {
+        TaskPatch {
+            id,
+            version: 1,
+            ..TaskPatch::default()
         }
     }
 }
@@ -0,0 +1,85 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskAction {
+    // 閲覧
+    View,
+    // 内容・ステータスの変更
+    Edit,
+    // 削除・復元・共有設定
+    Manage,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskAccess {
+    Owner,
+    Shared,
+    None,
+}
+
+impl TaskAccess {
+    pub fn of(owner_id: &str, user_id: &str, is_shared: bool) -> Self {
+        if owner_id == user_id {
+            TaskAccess::Owner
+        } else if is_shared {
+            TaskAccess::Shared
+        } else {
+            TaskAccess::None
+        }
+    }
+
+    // 所有者は全ての操作ができ、共有されたユーザーは閲覧と編集のみできる
+    pub fn allows(&self, action: TaskAction) -> bool {
+        match self {
+            TaskAccess::Owner => true,
+            TaskAccess::Shared => matches!(action, TaskAction::View | TaskAction::Edit),
+            TaskAccess::None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::task::{test_task, Task};
+
+    fn create_test_task() -> Task {
+        Task {
+            user_id: "owner".to_string(),
+            ..test_task()
+        }
+    }
+
+    #[test]
+    fn test_task_access_of() {
+        let task = create_test_task();
+
+        assert_eq!(
+            TaskAccess::of(&task.user_id, "owner", false),
+            TaskAccess::Owner
+        );
+        assert_eq!(
+            TaskAccess::of(&task.user_id, "owner", true),
+            TaskAccess::Owner
+        );
+        assert_eq!(
+            TaskAccess::of(&task.user_id, "friend", true),
+            TaskAccess::Shared
+        );
+        assert_eq!(
+            TaskAccess::of(&task.user_id, "stranger", false),
+            TaskAccess::None
+        );
+    }
+
+    #[test]
+    fn test_task_access_allows() {
+        let actions = [TaskAction::View, TaskAction::Edit, TaskAction::Manage];
+
+        for action in actions {
+            assert!(TaskAccess::Owner.allows(action));
+            assert!(!TaskAccess::None.allows(action));
+        }
+        assert!(TaskAccess::Shared.allows(TaskAction::View));
+        assert!(TaskAccess::Shared.allows(TaskAction::Edit));
+        assert!(!TaskAccess::Shared.allows(TaskAction::Manage));
+    }
+}
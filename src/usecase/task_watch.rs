@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        task::Task,
+        task_event::{TaskEvent, TaskShareEvent},
+    },
+    usecase::task_policy::{TaskAccess, TaskAction},
+};
+
+// 購読したユーザーが閲覧できるタスクのイベントだけを受け取る。
+// 共有されたタスクは購読開始時に読み込み、以降は共有の変更を受け取って更新するため、
+// イベントごとにリポジトリを参照しない
+pub struct TaskWatch {
+    user_id: String,
+    events: broadcast::Receiver<TaskEvent>,
+    shares: broadcast::Receiver<TaskShareEvent>,
+    shared_task_ids: HashSet<Uuid>,
+}
+
+impl TaskWatch {
+    pub fn new(
+        user_id: String,
+        events: broadcast::Receiver<TaskEvent>,
+        shares: broadcast::Receiver<TaskShareEvent>,
+        shared_task_ids: HashSet<Uuid>,
+    ) -> Self {
+        Self {
+            user_id,
+            events,
+            shares,
+            shared_task_ids,
+        }
+    }
+
+    // 取りこぼした場合は共有の変更も含めてLaggedを返す
+    pub async fn recv(&mut self) -> Result<TaskEvent, RecvError> {
+        loop {
+            tokio::select! {
+                // 届いている共有の変更を先に反映する
+                biased;
+                share = self.shares.recv() => self.apply_share(share?),
+                event = self.events.recv() => {
+                    let event = event?;
+                    if self.can_view(&event.task) {
+                        return Ok(event);
+                    }
+                }
+            }
+        }
+    }
+
+    fn apply_share(&mut self, share: TaskShareEvent) {
+        if share.user_id != self.user_id {
+            return;
+        }
+        if share.shared {
+            self.shared_task_ids.insert(share.task_id);
+        } else {
+            self.shared_task_ids.remove(&share.task_id);
+        }
+    }
+
+    fn can_view(&self, task: &Task) -> bool {
+        let is_shared = self.shared_task_ids.contains(&task.id);
+        TaskAccess::of(&task.user_id, &self.user_id, is_shared).allows(TaskAction::View)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use uuid::uuid;
+
+    use super::*;
+    use crate::domain::{
+        task::test_task,
+        task_event::{TaskEventBus, TaskEventKind},
+    };
+
+    const OWNED: Uuid = uuid!("00000000-0000-0000-0000-ffff00000001");
+    const SHARED: Uuid = uuid!("00000000-0000-0000-0000-ffff00000002");
+
+    fn create_test_task(id: Uuid, user_id: &str) -> Task {
+        Task {
+            id,
+            user_id: user_id.to_string(),
+            ..test_task()
+        }
+    }
+
+    fn watch(bus: &TaskEventBus, user_id: &str, shared_task_ids: &[Uuid]) -> TaskWatch {
+        TaskWatch::new(
+            user_id.to_string(),
+            bus.subscribe(),
+            bus.subscribe_shares(),
+            shared_task_ids.iter().copied().collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_watch_owned_and_shared() {
+        let bus = TaskEventBus::default();
+        let mut owner = watch(&bus, "owner", &[]);
+        let mut friend = watch(&bus, "friend", &[SHARED]);
+
+        bus.publish(TaskEventKind::Created, create_test_task(OWNED, "owner"));
+        bus.publish(TaskEventKind::Created, create_test_task(SHARED, "owner"));
+
+        assert_eq!(owner.recv().await.unwrap().task.id, OWNED);
+        assert_eq!(owner.recv().await.unwrap().task.id, SHARED);
+        // 共有されていないタスクのイベントは飛ばす
+        assert_eq!(friend.recv().await.unwrap().task.id, SHARED);
+    }
+
+    #[tokio::test]
+    async fn test_watch_not_owned_nor_shared() {
+        let bus = TaskEventBus::default();
+        let mut stranger = watch(&bus, "stranger", &[]);
+
+        bus.publish(TaskEventKind::Created, create_test_task(OWNED, "owner"));
+        bus.publish_share(SHARED, "friend".to_string(), true);
+        bus.publish(TaskEventKind::Updated, create_test_task(SHARED, "owner"));
+
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), stranger.recv())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_share_changes() {
+        let bus = TaskEventBus::default();
+        let mut friend = watch(&bus, "friend", &[]);
+
+        bus.publish_share(SHARED, "friend".to_string(), true);
+        bus.publish(TaskEventKind::Updated, create_test_task(SHARED, "owner"));
+        assert_eq!(friend.recv().await.unwrap().task.id, SHARED);
+
+        // 共有を解除した後のイベントは届かない
+        bus.publish_share(SHARED, "friend".to_string(), false);
+        bus.publish(TaskEventKind::Updated, create_test_task(SHARED, "owner"));
+        bus.publish(TaskEventKind::Created, create_test_task(OWNED, "friend"));
+        assert_eq!(friend.recv().await.unwrap().task.id, OWNED);
+    }
+}
@@ -0,0 +1,234 @@
+use std::future::Future;
+
+use mockall::automock;
+
+use crate::{
+    domain::{repository::user::UserRepositoryTrait, user::User},
+    error::CustomError,
+};
+
+// user_idは操作するユーザー。自分以外のユーザーを参照・更新しようとした場合はPermissionDeniedを返す
+#[automock]
+pub trait UserUsecaseTrait<UR: UserRepositoryTrait + 'static> {
+    fn new(repository: Box<UR>) -> Self
+    where
+        Self: Sized;
+    fn insert(
+        &self,
+        user_id: String,
+        user: User,
+    ) -> impl Future<Output = Result<String, CustomError>> + Send;
+    fn find(
+        &self,
+        user_id: String,
+        id: String,
+    ) -> impl Future<Output = Result<User, CustomError>> + Send;
+    // 他のユーザーのメールアドレスは返さないため、自分のユーザーだけを返す(未登録の場合は空)
+    fn find_self(
+        &self,
+        user_id: String,
+    ) -> impl Future<Output = Result<Vec<User>, CustomError>> + Send;
+    fn update(
+        &self,
+        user_id: String,
+        user: User,
+    ) -> impl Future<Output = Result<String, CustomError>> + Send;
+}
+
+pub struct UserUsecase<UR: UserRepositoryTrait> {
+    repository: Box<UR>,
+}
+
+impl<UR: UserRepositoryTrait + Sync + 'static> UserUsecaseTrait<UR> for UserUsecase<UR> {
+    fn new(repository: Box<UR>) -> Self {
+        Self { repository }
+    }
+
+    async fn insert(&self, user_id: String, user: User) -> Result<String, CustomError> {
+        authorize(&user_id, &user.id)?;
+        self.repository.insert(user).await
+    }
+
+    async fn find(&self, user_id: String, id: String) -> Result<User, CustomError> {
+        authorize(&user_id, &id)?;
+        self.repository.find(id).await
+    }
+
+    async fn find_self(&self, user_id: String) -> Result<Vec<User>, CustomError> {
+        match self.repository.find(user_id).await {
+            Ok(user) => Ok(vec![user]),
+            Err(CustomError::NotFound(_)) => Ok(vec![]),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn update(&self, user_id: String, user: User) -> Result<String, CustomError> {
+        authorize(&user_id, &user.id)?;
+        self.repository.update(user).await
+    }
+}
+
+fn authorize(user_id: &str, id: &str) -> Result<(), CustomError> {
+    if user_id != id {
+        return Err(CustomError::PermissionDenied(format!(
+            "user {} cannot access user {}",
+            user_id, id
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use mockall::predicate::eq;
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::domain::{repository::user::MockUserRepositoryTrait, user::User};
+
+    #[tokio::test]
+    async fn test_user_insert() {
+        let mut mock = MockUserRepositoryTrait::default();
+        mock.expect_insert()
+            .returning(|_| Box::pin(async { Ok("testuserid".to_string()) }));
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase
+            .insert("testuserid".to_string(), create_test_user("testuserid"))
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "testuserid".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_user_insert_other_user() {
+        let mut mock = MockUserRepositoryTrait::default();
+        mock.expect_insert().never();
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase
+            .insert("testuserid".to_string(), create_test_user("otheruser"))
+            .await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_user_find() {
+        let mut mock = MockUserRepositoryTrait::default();
+        let except_user = create_test_user("testuserid");
+        mock.expect_find()
+            .with(eq("testuserid".to_string()))
+            .returning(move |_| {
+                Box::pin({
+                    let value = except_user.clone();
+                    async move { Ok(value) }
+                })
+            });
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase
+            .find("testuserid".to_string(), "testuserid".to_string())
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().id, "testuserid".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_user_find_not_found() {
+        let mut mock = MockUserRepositoryTrait::default();
+        mock.expect_find()
+            .with(eq("unknown".to_string()))
+            .returning(|id| {
+                Box::pin(async move { Err(CustomError::NotFound(format!("key: {}", id))) })
+            });
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase
+            .find("unknown".to_string(), "unknown".to_string())
+            .await;
+        assert!(matches!(result, Err(CustomError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_user_find_other_user() {
+        let mut mock = MockUserRepositoryTrait::default();
+        mock.expect_find().never();
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase
+            .find("testuserid".to_string(), "otheruser".to_string())
+            .await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_user_find_self() {
+        let mut mock = MockUserRepositoryTrait::default();
+        let except_user = create_test_user("harukun");
+        mock.expect_find()
+            .with(eq("harukun".to_string()))
+            .returning(move |_| {
+                Box::pin({
+                    let value = except_user.clone();
+                    async move { Ok(value) }
+                })
+            });
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase.find_self("harukun".to_string()).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "harukun".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_user_find_self_not_registered() {
+        let mut mock = MockUserRepositoryTrait::default();
+        mock.expect_find().returning(|id| {
+            Box::pin(async move { Err(CustomError::NotFound(format!("key: {}", id))) })
+        });
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase.find_self("bunbun".to_string()).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_user_update() {
+        let mut updated_user = create_test_user("testuserid");
+        updated_user.username = "updated_name".to_string();
+        updated_user.email = "updated@example.com".to_string();
+
+        let mut mock = MockUserRepositoryTrait::default();
+        mock.expect_update()
+            .with(eq(updated_user.clone()))
+            .returning(|_| Box::pin(async { Ok("testuserid".to_string()) }));
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase.update("testuserid".to_string(), updated_user).await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), "testuserid".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_user_update_other_user() {
+        let mut mock = MockUserRepositoryTrait::default();
+        mock.expect_update().never();
+
+        let usecase = UserUsecase::new(Box::new(mock));
+        let result = usecase
+            .update("otheruser".to_string(), create_test_user("testuserid"))
+            .await;
+        assert!(matches!(result, Err(CustomError::PermissionDenied(_))));
+    }
+
+    fn create_test_user(id: &str) -> User {
+        User {
+            id: id.to_string(),
+            username: "test_name".to_string(),
+            email: "test@example.com".to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -6,15 +6,19 @@ use gakusai2024_proto::api::{
     CreateHelloRequest,
 };
 use hyper_util::rt::TokioIo;
+#[cfg(feature = "sqlite")]
+use migration::{Migrator, MigratorTrait};
 use sea_orm::Database;
-use tokio::sync::Mutex;
 use tonic::transport::{Endpoint, Server, Uri};
 use tower::service_fn;
 use uuid::Uuid;
 
 use gakusai2024_backend::{
     domain::repository::hello::HelloRepositoryTrait,
-    infrastructure,
+    infrastructure::{
+        self,
+        memory::{hello::InMemoryHelloPersistence, InMemoryStore},
+    },
     interface::{self, handler::hello::HelloHandlerTrait},
     usecase::{self, hello::HelloUsecaseTrait},
 };
@@ -24,11 +28,36 @@ use gakusai2024_backend::{
 async fn test_hello() {
     dotenv().ok();
     let db_url = std::env::var("DATABASE_URL").unwrap();
-    let (client, server) = tokio::io::duplex(1024);
 
     let db = Database::connect(db_url).await.unwrap();
-    let hello_persistence =
-        infrastructure::db::hello::HelloPersistence::new(Arc::new(Mutex::new(db)));
+    let hello_persistence = infrastructure::db::hello::HelloPersistence::new(Arc::new(db));
+    run_hello_scenario(hello_persistence).await;
+}
+
+// DBを使わずに同じシナリオを実行する
+#[tokio::test]
+async fn test_hello_in_memory() {
+    let hello_persistence = InMemoryHelloPersistence::new(InMemoryStore::new());
+    run_hello_scenario(hello_persistence).await;
+}
+
+// SQLiteのインメモリDBにマイグレーションを適用し、同じシナリオを実行する
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_hello_sqlite() {
+    let db = infrastructure::db::DbConfig::new("sqlite::memory:".to_string())
+        .connect()
+        .await
+        .unwrap();
+    Migrator::up(db.as_ref(), None).await.unwrap();
+    run_hello_scenario(infrastructure::db::hello::HelloPersistence::new(db)).await;
+}
+
+async fn run_hello_scenario<HR>(hello_persistence: HR)
+where
+    HR: HelloRepositoryTrait + Send + Sync + 'static,
+{
+    let (client, server) = tokio::io::duplex(1024);
     let hello_usecase = usecase::hello::HelloUsecase::new(Box::new(hello_persistence));
     let hello_handler = interface::handler::hello::HelloHandler::new(Box::new(hello_usecase));
 
@@ -51,10 +80,7 @@ async fn test_hello() {
                 if let Some(client) = client {
                     Ok(TokioIo::new(client))
                 } else {
-                    Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Client already taken",
-                    ))
+                    Err(std::io::Error::other("Client already taken"))
                 }
             }
         }))
@@ -97,4 +123,13 @@ async fn test_hello() {
             .message,
         message
     );
+
+    // 存在しない名前はNotFoundになる
+    let not_found = client
+        .read_hello(gakusai2024_proto::api::ReadHelloRequest {
+            name: Uuid::new_v4().to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(not_found.code(), tonic::Code::NotFound);
 }
@@ -0,0 +1,141 @@
+use dotenv::dotenv;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{
+    sea_query::{Alias, Expr, Query},
+    ConnectionTrait, Database, DatabaseConnection, DbErr,
+};
+
+use gakusai2024_backend::infrastructure::db::migrate::run_migrations;
+
+const NEWER_VERSION: &str = "m29991231_000000_from_newer_binary";
+
+#[ignore]
+#[tokio::test]
+async fn test_migrate() {
+    dotenv().ok();
+    let db_url = std::env::var("DATABASE_URL").unwrap();
+    let conn = Database::connect(db_url).await.unwrap();
+    run_migrate_scenario(&conn).await;
+}
+
+// 空のSQLiteのインメモリDBに対して全てのマイグレーションを適用し、全て戻せることを確認する
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_migrate_sqlite() {
+    let conn =
+        gakusai2024_backend::infrastructure::db::DbConfig::new("sqlite::memory:".to_string())
+            .connect()
+            .await
+            .unwrap();
+    let all: Vec<String> = Migrator::migrations()
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+
+    // tasksを作り直すマイグレーションの前にデータを入れておき、移されることを確認する
+    Migrator::up(conn.as_ref(), Some(3)).await.unwrap();
+    conn.execute_unprepared(
+        r#"INSERT INTO "users" ("user_id", "name", "email", "created_at", "updated_at")
+        VALUES ('migrate_user', 'Migrate User', 'migrate@example.com',
+            '2024-10-08 00:00:00+00:00', '2024-10-08 00:00:00+00:00')"#,
+    )
+    .await
+    .unwrap();
+    conn.execute_unprepared(
+        r#"INSERT INTO "tasks" ("id", "title", "description", "due_date", "priority", "weight",
+            "created_at", "updated_at", "user_id")
+        VALUES ('00000000-0000-0000-0000-ffff00000000', 'migrated', 'migrated task',
+            '2024-10-09 00:00:00+00:00', 1, 1,
+            '2024-10-08 00:00:00+00:00', '2024-10-08 00:00:00+00:00', 'migrate_user')"#,
+    )
+    .await
+    .unwrap();
+    run_migrate_scenario(&conn).await;
+
+    let migrated = conn
+        .query_one(sea_orm::Statement::from_string(
+            sea_orm::DbBackend::Sqlite,
+            r#"SELECT "title", "user_id" FROM "tasks""#,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(migrated.try_get::<String>("", "title").unwrap(), "migrated");
+    assert_eq!(
+        migrated.try_get::<String>("", "user_id").unwrap(),
+        "migrate_user"
+    );
+    let applied: Vec<String> = Migrator::get_applied_migrations(conn.as_ref())
+        .await
+        .unwrap()
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+    assert_eq!(applied, all);
+
+    // 存在しないユーザーのタスクは外部キー制約で作れない
+    assert!(conn
+        .execute_unprepared(
+            r#"INSERT INTO "tasks" ("id", "title", "description", "due_date", "priority",
+                "weight", "created_at", "updated_at", "user_id")
+            VALUES ('00000000-0000-0000-0000-ffff00000001', 'orphan', '',
+                '2024-10-09 00:00:00+00:00', 1, 1,
+                '2024-10-08 00:00:00+00:00', '2024-10-08 00:00:00+00:00', 'unknown_user')"#,
+        )
+        .await
+        .is_err());
+
+    Migrator::down(conn.as_ref(), None).await.unwrap();
+    assert!(Migrator::get_applied_migrations(conn.as_ref())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+async fn run_migrate_scenario(conn: &DatabaseConnection) {
+    // 未適用のものだけが適用され、そのバージョンが返される
+    let pending: Vec<String> = Migrator::get_pending_migrations(conn)
+        .await
+        .unwrap()
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+    assert_eq!(run_migrations(conn).await.unwrap(), pending);
+    assert!(Migrator::get_pending_migrations(conn)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // 2回目は何も適用しない
+    assert!(run_migrations(conn).await.unwrap().is_empty());
+
+    // バイナリが知らないマイグレーションが適用済みの場合は新しいスキーマとみなして失敗する
+    let backend = conn.get_database_backend();
+    conn.execute(
+        backend.build(
+            Query::insert()
+                .into_table(Alias::new("seaql_migrations"))
+                .columns([Alias::new("version"), Alias::new("applied_at")])
+                .values_panic([NEWER_VERSION.into(), 0i64.into()]),
+        ),
+    )
+    .await
+    .unwrap();
+    let result = run_migrations(conn).await;
+
+    // テスト後に追加したバージョンを削除する
+    conn.execute(
+        backend.build(
+            Query::delete()
+                .from_table(Alias::new("seaql_migrations"))
+                .and_where(Expr::col(Alias::new("version")).eq(NEWER_VERSION)),
+        ),
+    )
+    .await
+    .unwrap();
+
+    match result {
+        Err(DbErr::Migration(message)) => assert!(message.contains(NEWER_VERSION)),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
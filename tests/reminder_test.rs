@@ -0,0 +1,292 @@
+use std::{sync::Arc, time::Duration};
+
+use dotenv::dotenv;
+use gakusai2024_proto::api::{
+    reminder_service_client::ReminderServiceClient, reminder_service_server::ReminderServiceServer,
+    GetReminderSettingsRequest, UpdateReminderSettingsRequest,
+};
+use hyper_util::rt::TokioIo;
+#[cfg(feature = "sqlite")]
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, Database, Statement};
+use time::OffsetDateTime;
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::Interceptor,
+    transport::{Endpoint, Server, Uri},
+    Status,
+};
+use tower::service_fn;
+use uuid::Uuid;
+
+use gakusai2024_backend::{
+    domain::{
+        repository::{
+            reminder::ReminderRepositoryTrait, task::TaskRepositoryTrait, user::UserRepositoryTrait,
+        },
+        task::{Task, TaskStatus},
+        user::User,
+    },
+    infrastructure::{
+        self,
+        memory::{
+            reminder::InMemoryReminderPersistence, task::InMemoryTaskPersistence,
+            user::InMemoryUserPersistence, InMemoryStore,
+        },
+        notifier::InMemoryNotifier,
+    },
+    interface::{
+        self,
+        auth::{issue_token, AuthInterceptor},
+        handler::reminder::ReminderHandlerTrait,
+    },
+    usecase::{self, reminder::ReminderUsecaseTrait},
+};
+
+const TEST_AUTH_SECRET: &[u8] = b"test-secret";
+
+// クライアント側でAuthorizationヘッダを付与する
+#[derive(Clone)]
+struct BearerToken(MetadataValue<Ascii>);
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert("authorization", self.0.clone());
+        Ok(request)
+    }
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_reminder() {
+    dotenv().ok();
+    let db_url = std::env::var("DATABASE_URL").unwrap();
+    let db_for_cleanup = Database::connect(db_url.clone()).await.unwrap();
+    let db = Arc::new(Database::connect(db_url).await.unwrap());
+
+    let users = infrastructure::db::user::UserPersistence::new(db.clone());
+    let test_user_id = create_test_user(&users).await;
+
+    run_reminder_scenario(
+        || infrastructure::db::reminder::ReminderPersistence::new(db.clone()),
+        infrastructure::db::task::TaskPersistence::new(db.clone()),
+        test_user_id.clone(),
+    )
+    .await;
+
+    // テスト後にデータベースをクリーンアップ
+    let cleanup_stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM tasks WHERE user_id = $1"#,
+        vec![test_user_id.clone().into()],
+    );
+    db_for_cleanup.execute(cleanup_stmt).await.unwrap();
+
+    let cleanup_user_stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM users WHERE user_id = $1"#,
+        vec![test_user_id.clone().into()],
+    );
+    db_for_cleanup.execute(cleanup_user_stmt).await.unwrap();
+}
+
+// DBを使わずに同じシナリオを実行する
+#[tokio::test]
+async fn test_reminder_in_memory() {
+    let store = InMemoryStore::new();
+    let users = InMemoryUserPersistence::new(store.clone());
+    let test_user_id = create_test_user(&users).await;
+
+    run_reminder_scenario(
+        || InMemoryReminderPersistence::new(store.clone()),
+        InMemoryTaskPersistence::new(store.clone()),
+        test_user_id,
+    )
+    .await;
+}
+
+// SQLiteのインメモリDBにマイグレーションを適用し、同じシナリオを実行する
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_reminder_sqlite() {
+    let db = infrastructure::db::DbConfig::new("sqlite::memory:".to_string())
+        .connect()
+        .await
+        .unwrap();
+    Migrator::up(db.as_ref(), None).await.unwrap();
+    let users = infrastructure::db::user::UserPersistence::new(db.clone());
+    let test_user_id = create_test_user(&users).await;
+
+    run_reminder_scenario(
+        || infrastructure::db::reminder::ReminderPersistence::new(db.clone()),
+        infrastructure::db::task::TaskPersistence::new(db.clone()),
+        test_user_id,
+    )
+    .await;
+}
+
+async fn create_test_user<UR: UserRepositoryTrait>(users: &UR) -> String {
+    let test_user_id = format!("test_user_{}", Uuid::new_v4());
+    users
+        .insert(User {
+            id: test_user_id.clone(),
+            username: "Test User".to_string(),
+            email: "test@example.com".to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        })
+        .await
+        .unwrap();
+    test_user_id
+}
+
+// リマインドのリポジトリはハンドラと各ワーカーで別々に作る
+async fn run_reminder_scenario<RR, TR>(
+    new_reminders: impl Fn() -> RR,
+    task_persistence: TR,
+    test_user_id: String,
+) where
+    RR: ReminderRepositoryTrait + Send + Sync + 'static,
+    TR: TaskRepositoryTrait,
+{
+    let (client, server) = tokio::io::duplex(1024);
+
+    let reminder_usecase = usecase::reminder::ReminderUsecase::new(
+        Box::new(new_reminders()),
+        Box::new(InMemoryNotifier::default()),
+    );
+    let reminder_handler =
+        interface::handler::reminder::ReminderHandler::new(Box::new(reminder_usecase));
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(ReminderServiceServer::with_interceptor(
+                reminder_handler,
+                AuthInterceptor::new(TEST_AUTH_SECRET),
+            ))
+            .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
+            .await
+    });
+
+    // Move client to an option so we can _move_ the inner value
+    // on the first attempt to connect. All other attempts will fail.
+    let mut client = Some(client);
+    let channel = Endpoint::try_from("http://[::]:50051")
+        .unwrap()
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let client = client.take();
+
+            async move {
+                if let Some(client) = client {
+                    Ok(TokioIo::new(client))
+                } else {
+                    Err(std::io::Error::other("Client already taken"))
+                }
+            }
+        }))
+        .await
+        .unwrap();
+
+    let token = issue_token(TEST_AUTH_SECRET, &test_user_id, Duration::from_secs(600)).unwrap();
+    let bearer = BearerToken(format!("Bearer {}", token).parse().unwrap());
+    let mut client = ReminderServiceClient::with_interceptor(channel, bearer);
+
+    // GetReminderSettingsのテスト(未設定の場合はデフォルト)
+    let response = client
+        .get_reminder_settings(GetReminderSettingsRequest {})
+        .await
+        .unwrap();
+    assert_eq!(response.into_inner().offset_minutes, vec![1440, 60]);
+
+    // UpdateReminderSettingsのテスト
+    let response = client
+        .update_reminder_settings(UpdateReminderSettingsRequest {
+            offset_minutes: vec![30, 120, 30],
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.into_inner().offset_minutes, vec![120, 30]);
+    assert_eq!(
+        client
+            .update_reminder_settings(UpdateReminderSettingsRequest {
+                offset_minutes: vec![0],
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::InvalidArgument
+    );
+
+    // 期限が20分後のタスクには30分前のリマインドが送られる
+    let now = OffsetDateTime::now_utc();
+    let task_id = Uuid::new_v4();
+    task_persistence
+        .insert(
+            Task {
+                id: task_id,
+                title: "reminder".to_string(),
+                description: String::new(),
+                user_id: test_user_id.clone(),
+                due_date: now + time::Duration::minutes(20),
+                priority: 1,
+                weight: 1,
+                created_at: now,
+                updated_at: now,
+                deleted_at: None,
+                status: TaskStatus::Todo,
+                completed_at: None,
+                parent_id: None,
+                recurrence: None,
+                version: 1,
+            },
+            vec![],
+        )
+        .await
+        .unwrap();
+
+    let notifier = InMemoryNotifier::default();
+    let worker_usecase = usecase::reminder::ReminderUsecase::new(
+        Box::new(new_reminders()),
+        Box::new(notifier.clone()),
+    );
+    worker_usecase.fire_due(now).await.unwrap();
+    let sent: Vec<_> = notifier
+        .sent()
+        .into_iter()
+        .filter(|r| r.user_id == test_user_id)
+        .collect();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].task_id, task_id);
+    assert_eq!(sent[0].offset_minutes, 30);
+
+    // 再起動しても送信済みのリマインドは送られない
+    let restarted_notifier = InMemoryNotifier::default();
+    let restarted_usecase = usecase::reminder::ReminderUsecase::new(
+        Box::new(new_reminders()),
+        Box::new(restarted_notifier.clone()),
+    );
+    restarted_usecase.fire_due(now).await.unwrap();
+    assert!(restarted_notifier
+        .sent()
+        .iter()
+        .all(|r| r.user_id != test_user_id));
+
+    // 期限を変更した場合は同じオフセットでも改めて送られる
+    let mut task = task_persistence.find(task_id).await.unwrap();
+    task.due_date = now + time::Duration::minutes(25);
+    task_persistence.update(task, vec![]).await.unwrap();
+    restarted_usecase.fire_due(now).await.unwrap();
+    let resent: Vec<_> = restarted_notifier
+        .sent()
+        .into_iter()
+        .filter(|r| r.user_id == test_user_id)
+        .collect();
+    assert_eq!(resent.len(), 1);
+    assert_eq!(resent[0].offset_minutes, 30);
+    assert_eq!(
+        resent[0].due_date,
+        task_persistence.find(task_id).await.unwrap().due_date
+    );
+}
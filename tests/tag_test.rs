@@ -0,0 +1,379 @@
+use std::{sync::Arc, time::Duration};
+
+use dotenv::dotenv;
+use gakusai2024_proto::api::{
+    tag_service_client::TagServiceClient, tag_service_server::TagServiceServer,
+    task_service_client::TaskServiceClient, task_service_server::TaskServiceServer,
+    AttachTagRequest, CreateTagRequest, CreateTaskRequest, DeleteTagRequest, DetachTagRequest,
+    GetListTagsRequest, GetListTasksRequest, GetTagRequest, TaskFilter, TaskRequest,
+    UpdateTagRequest,
+};
+use hyper_util::rt::TokioIo;
+#[cfg(feature = "sqlite")]
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, Database, Statement};
+use time::OffsetDateTime;
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::Interceptor,
+    transport::{Endpoint, Server, Uri},
+    Status,
+};
+use tower::service_fn;
+use uuid::Uuid;
+
+use gakusai2024_backend::{
+    domain::{
+        repository::{
+            tag::TagRepositoryTrait, task::TaskRepositoryTrait, unit_of_work::UnitOfWorkTrait,
+            user::UserRepositoryTrait,
+        },
+        user::User,
+    },
+    infrastructure::{
+        self,
+        memory::{
+            tag::InMemoryTagPersistence, task::InMemoryTaskPersistence,
+            unit_of_work::InMemoryUnitOfWork, user::InMemoryUserPersistence, InMemoryStore,
+        },
+    },
+    interface::{
+        self,
+        auth::{issue_token, AuthInterceptor},
+        handler::{tag::TagHandlerTrait, task::TaskHandlerTrait},
+    },
+    usecase::{self, tag::TagUsecaseTrait, task::TaskUsecaseTrait},
+};
+
+const TEST_AUTH_SECRET: &[u8] = b"test-secret";
+
+// クライアント側でAuthorizationヘッダを付与する
+#[derive(Clone)]
+struct BearerToken(MetadataValue<Ascii>);
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert("authorization", self.0.clone());
+        Ok(request)
+    }
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_tag() {
+    dotenv().ok();
+    let db_url = std::env::var("DATABASE_URL").unwrap();
+
+    let db_for_cleanup = Database::connect(db_url.clone()).await.unwrap();
+    let db = Arc::new(Database::connect(db_url).await.unwrap());
+
+    let users = infrastructure::db::user::UserPersistence::new(db.clone());
+    let test_user_id = create_test_user(&users).await;
+
+    run_tag_scenario(
+        infrastructure::db::tag::TagPersistence::new(db.clone()),
+        infrastructure::db::task::TaskPersistence::new(db.clone()),
+        infrastructure::db::unit_of_work::UnitOfWork::new(db),
+        test_user_id.clone(),
+    )
+    .await;
+
+    // テスト後にデータベースをクリーンアップ
+    let cleanup_stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM tasks WHERE user_id = $1"#,
+        vec![test_user_id.clone().into()],
+    );
+    db_for_cleanup.execute(cleanup_stmt).await.unwrap();
+
+    let cleanup_user_stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM users WHERE user_id = $1"#,
+        vec![test_user_id.clone().into()],
+    );
+    db_for_cleanup.execute(cleanup_user_stmt).await.unwrap();
+}
+
+// DBを使わずに同じシナリオを実行する
+#[tokio::test]
+async fn test_tag_in_memory() {
+    let store = InMemoryStore::new();
+    let users = InMemoryUserPersistence::new(store.clone());
+    let test_user_id = create_test_user(&users).await;
+
+    run_tag_scenario(
+        InMemoryTagPersistence::new(store.clone()),
+        InMemoryTaskPersistence::new(store.clone()),
+        InMemoryUnitOfWork::new(store),
+        test_user_id,
+    )
+    .await;
+}
+
+// SQLiteのインメモリDBにマイグレーションを適用し、同じシナリオを実行する
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_tag_sqlite() {
+    let db = infrastructure::db::DbConfig::new("sqlite::memory:".to_string())
+        .connect()
+        .await
+        .unwrap();
+    Migrator::up(db.as_ref(), None).await.unwrap();
+    let users = infrastructure::db::user::UserPersistence::new(db.clone());
+    let test_user_id = create_test_user(&users).await;
+
+    run_tag_scenario(
+        infrastructure::db::tag::TagPersistence::new(db.clone()),
+        infrastructure::db::task::TaskPersistence::new(db.clone()),
+        infrastructure::db::unit_of_work::UnitOfWork::new(db),
+        test_user_id,
+    )
+    .await;
+}
+
+// テスト用のユーザーを作成する
+async fn create_test_user<UR: UserRepositoryTrait>(users: &UR) -> String {
+    let test_user_id = format!("test_user_{}", Uuid::new_v4());
+    users
+        .insert(User {
+            id: test_user_id.clone(),
+            username: "Test User".to_string(),
+            email: "test@example.com".to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        })
+        .await
+        .unwrap();
+    test_user_id
+}
+
+async fn run_tag_scenario<TgR, TR, UW>(
+    tag_persistence: TgR,
+    task_persistence: TR,
+    unit_of_work: UW,
+    test_user_id: String,
+) where
+    TgR: TagRepositoryTrait + Send + Sync + 'static,
+    TR: TaskRepositoryTrait + Send + Sync + 'static,
+    UW: UnitOfWorkTrait + Send + Sync + 'static,
+{
+    let (client, server) = tokio::io::duplex(1024);
+    let tag_usecase = usecase::tag::TagUsecase::new(Box::new(tag_persistence));
+    let tag_handler = interface::handler::tag::TagHandler::new(Box::new(tag_usecase));
+
+    let task_usecase =
+        usecase::task::TaskUsecase::new(Box::new(task_persistence), Box::new(unit_of_work));
+    let task_handler = interface::handler::task::TaskHandler::new(Box::new(task_usecase));
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(TagServiceServer::with_interceptor(
+                tag_handler,
+                AuthInterceptor::new(TEST_AUTH_SECRET),
+            ))
+            .add_service(TaskServiceServer::with_interceptor(
+                task_handler,
+                AuthInterceptor::new(TEST_AUTH_SECRET),
+            ))
+            .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
+            .await
+    });
+
+    // Move client to an option so we can _move_ the inner value
+    // on the first attempt to connect. All other attempts will fail.
+    let mut client = Some(client);
+    let channel = Endpoint::try_from("http://[::]:50051")
+        .unwrap()
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let client = client.take();
+
+            async move {
+                if let Some(client) = client {
+                    Ok(TokioIo::new(client))
+                } else {
+                    Err(std::io::Error::other("Client already taken"))
+                }
+            }
+        }))
+        .await
+        .unwrap();
+
+    let token = issue_token(TEST_AUTH_SECRET, &test_user_id, Duration::from_secs(600)).unwrap();
+    let bearer = BearerToken(format!("Bearer {}", token).parse().unwrap());
+    let mut tag_client = TagServiceClient::with_interceptor(channel.clone(), bearer.clone());
+    let mut task_client = TaskServiceClient::with_interceptor(channel, bearer);
+
+    // CreateTagのテスト
+    let work_tag_id = tag_client
+        .create_tag(CreateTagRequest {
+            name: "work".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .tag_id;
+    let home_tag_id = tag_client
+        .create_tag(CreateTagRequest {
+            name: "home".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .tag_id;
+
+    // 同じ名前のタグは作成できない
+    assert_eq!(
+        tag_client
+            .create_tag(CreateTagRequest {
+                name: "work".to_string(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::AlreadyExists
+    );
+
+    // GetListTagsのテスト(名前順)
+    let list_tags_response = tag_client
+        .get_list_tags(GetListTagsRequest {})
+        .await
+        .unwrap();
+    let names: Vec<String> = list_tags_response
+        .get_ref()
+        .tags
+        .iter()
+        .map(|t| t.name.clone())
+        .collect();
+    assert_eq!(names, vec!["home", "work"]);
+
+    // UpdateTagのテスト
+    tag_client
+        .update_tag(UpdateTagRequest {
+            tag_id: home_tag_id.clone(),
+            name: "private".to_string(),
+        })
+        .await
+        .unwrap();
+    let get_tag_response = tag_client
+        .get_tag(GetTagRequest {
+            tag_id: home_tag_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        get_tag_response.get_ref().tag.as_ref().unwrap().name,
+        "private"
+    );
+
+    // タスクにタグを付ける
+    let mut task_ids = Vec::new();
+    for title in ["task1", "task2", "task3"] {
+        let response = task_client
+            .create_task(CreateTaskRequest {
+                task_request: Some(TaskRequest {
+                    title: title.to_string(),
+                    description: None,
+                    due_date: Some(prost_types::Timestamp::default()),
+                    priority: 1,
+                    weight: 1,
+                    user_id: String::new(),
+                    parent_id: None,
+                    recurrence: None,
+                }),
+            })
+            .await
+            .unwrap();
+        task_ids.push(response.into_inner().task_id);
+    }
+    for (task_id, tag_id) in [
+        (&task_ids[0], &work_tag_id),
+        (&task_ids[1], &work_tag_id),
+        (&task_ids[1], &home_tag_id),
+    ] {
+        task_client
+            .attach_tag(AttachTagRequest {
+                task_id: task_id.clone(),
+                tag_id: tag_id.clone(),
+            })
+            .await
+            .unwrap();
+    }
+
+    // タグでの絞り込みのテスト
+    let list_client = task_client.clone();
+    let list_by_tags = move |tag_ids: Vec<String>| {
+        let mut task_client = list_client.clone();
+        async move {
+            let mut titles: Vec<String> = task_client
+                .get_list_tasks(GetListTasksRequest {
+                    filter: Some(TaskFilter {
+                        tag_ids,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .into_inner()
+                .tasks
+                .into_iter()
+                .map(|t| t.title)
+                .collect();
+            titles.sort();
+            titles
+        }
+    };
+    assert_eq!(
+        list_by_tags(vec![work_tag_id.clone()]).await,
+        vec!["task1", "task2"]
+    );
+    assert_eq!(
+        list_by_tags(vec![work_tag_id.clone(), home_tag_id.clone()]).await,
+        vec!["task2"]
+    );
+    assert_eq!(list_by_tags(vec![]).await.len(), 3);
+
+    // DetachTagのテスト
+    task_client
+        .detach_tag(DetachTagRequest {
+            task_id: task_ids[1].clone(),
+            tag_id: home_tag_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert!(list_by_tags(vec![work_tag_id.clone(), home_tag_id.clone()])
+        .await
+        .is_empty());
+    assert_eq!(
+        task_client
+            .detach_tag(DetachTagRequest {
+                task_id: task_ids[1].clone(),
+                tag_id: home_tag_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::NotFound
+    );
+
+    // DeleteTagのテスト
+    tag_client
+        .delete_tag(DeleteTagRequest {
+            tag_id: work_tag_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        tag_client
+            .get_tag(GetTagRequest {
+                tag_id: work_tag_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::NotFound
+    );
+    assert!(list_by_tags(vec![work_tag_id.clone()]).await.is_empty());
+}
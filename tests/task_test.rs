@@ -1,35 +1,73 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use dotenv::dotenv;
 use entity::user;
 use gakusai2024_proto::api::{
     task_service_client::TaskServiceClient, task_service_server::TaskServiceServer,
-    CreateTaskRequest, GetListTasksRequest, GetTaskRequest, TaskRequest, TaskUpdate,
-    UpdateTaskRequest,
+    AddTaskDependencyRequest, BatchCreateTasksRequest, BatchDeleteTasksRequest,
+    BatchUpdateTasksRequest, CompleteTaskRequest, CreateTaskRequest, DeleteTaskRequest,
+    GeneratePlanRequest, GetListTasksRequest, GetNextTasksRequest, GetTaskHistoryRequest,
+    GetTaskRequest, GetTaskSubtreeRequest, GetTasksInDependencyOrderRequest, PurgeTaskRequest,
+    RemoveTaskDependencyRequest, ReopenTaskRequest, RestoreTaskRequest, ShareTaskRequest,
+    TaskEventType, TaskFilter, TaskHistoryAction, TaskRequest, TaskSortKey, TaskStatus, TaskUpdate,
+    UnshareTaskRequest, UpdateTaskRequest, WatchTasksRequest,
 };
 use hyper_util::rt::TokioIo;
-use sea_orm::{
-    ActiveModelTrait, ColumnTrait, ConnectionTrait, Database, EntityTrait, QueryFilter, Set,
-    Statement,
+#[cfg(feature = "sqlite")]
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, Set, Statement};
+use time::OffsetDateTime;
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::Interceptor,
+    transport::{Endpoint, Server, Uri},
+    Status,
 };
-use tokio::sync::Mutex;
-use tonic::transport::{Endpoint, Server, Uri};
 use tower::service_fn;
 use uuid::Uuid;
 
 use gakusai2024_backend::{
-    domain::repository::task::TaskRepositoryTrait,
-    infrastructure,
-    interface::{self, handler::task::TaskHandlerTrait},
+    domain::{
+        repository::{
+            task::TaskRepositoryTrait, unit_of_work::UnitOfWorkTrait, user::UserRepositoryTrait,
+        },
+        user::User,
+    },
+    infrastructure::{
+        self,
+        memory::{
+            task::InMemoryTaskPersistence, unit_of_work::InMemoryUnitOfWork,
+            user::InMemoryUserPersistence, InMemoryStore,
+        },
+    },
+    interface::{
+        self,
+        auth::{issue_token, issue_token_with_role, AuthInterceptor, Role},
+        handler::task::TaskHandlerTrait,
+    },
     usecase::{self, task::TaskUsecaseTrait},
 };
 
+const TEST_AUTH_SECRET: &[u8] = b"test-secret";
+
+// クライアント側でAuthorizationヘッダを付与する
+#[derive(Clone)]
+struct BearerToken(MetadataValue<Ascii>);
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert("authorization", self.0.clone());
+        Ok(request)
+    }
+}
+
 #[ignore]
 #[tokio::test]
 async fn test_task() {
     dotenv().ok();
     let db_url = std::env::var("DATABASE_URL").unwrap();
-    let (client, server) = tokio::io::duplex(1024);
 
     let db_for_cleanup = Database::connect(db_url.clone()).await.unwrap();
     let db = Database::connect(db_url).await.unwrap();
@@ -61,13 +99,130 @@ async fn test_task() {
     };
     user.insert(&db).await.unwrap();
 
-    let task_persistence = infrastructure::db::task::TaskPersistence::new(Arc::new(Mutex::new(db)));
-    let task_usecase = usecase::task::TaskUsecase::new(Box::new(task_persistence));
+    // 共有・権限確認用のユーザーを作成
+    let other_user_id = format!("test_other_user_{}", Uuid::new_v4());
+    let other_user = user::ActiveModel {
+        id: Set(other_user_id.clone()),
+        username: Set("Other User".to_string()),
+        email: Set("other@example.com".to_string()),
+        ..Default::default()
+    };
+    other_user.insert(&db).await.unwrap();
+
+    let db = Arc::new(db);
+    run_task_scenario(
+        infrastructure::db::task::TaskPersistence::new(db.clone()),
+        infrastructure::db::unit_of_work::UnitOfWork::new(db),
+        test_user_id.clone(),
+        other_user_id.clone(),
+    )
+    .await;
+
+    // テスト後にデータベースをクリーンアップ
+    let cleanup_stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM tasks WHERE user_id = $1"#,
+        vec![test_user_id.clone().into()],
+    );
+    db_for_cleanup.execute(cleanup_stmt).await.unwrap();
+
+    let cleanup_user_stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM users WHERE user_id = $1"#,
+        vec![test_user_id.clone().into()],
+    );
+    db_for_cleanup.execute(cleanup_user_stmt).await.unwrap();
+
+    let cleanup_other_user_stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM users WHERE user_id = $1"#,
+        vec![other_user_id.clone().into()],
+    );
+    db_for_cleanup
+        .execute(cleanup_other_user_stmt)
+        .await
+        .unwrap();
+}
+
+// DBを使わずに同じシナリオを実行する
+#[tokio::test]
+async fn test_task_in_memory() {
+    let store = InMemoryStore::new();
+    let users = InMemoryUserPersistence::new(store.clone());
+    let (test_user_id, other_user_id) = create_test_users(&users).await;
+
+    run_task_scenario(
+        InMemoryTaskPersistence::new(store.clone()),
+        InMemoryUnitOfWork::new(store),
+        test_user_id,
+        other_user_id,
+    )
+    .await;
+}
+
+// SQLiteのインメモリDBにマイグレーションを適用し、同じシナリオを実行する
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_task_sqlite() {
+    let db = infrastructure::db::DbConfig::new("sqlite::memory:".to_string())
+        .connect()
+        .await
+        .unwrap();
+    Migrator::up(db.as_ref(), None).await.unwrap();
+    let users = infrastructure::db::user::UserPersistence::new(db.clone());
+    let (test_user_id, other_user_id) = create_test_users(&users).await;
+
+    run_task_scenario(
+        infrastructure::db::task::TaskPersistence::new(db.clone()),
+        infrastructure::db::unit_of_work::UnitOfWork::new(db),
+        test_user_id,
+        other_user_id,
+    )
+    .await;
+}
+
+// テスト用のユーザーと、共有・権限確認用のユーザーを作成する
+async fn create_test_users<UR: UserRepositoryTrait>(users: &UR) -> (String, String) {
+    let test_user_id = format!("test_user_{}", Uuid::new_v4());
+    let other_user_id = format!("test_other_user_{}", Uuid::new_v4());
+    for (id, username, email) in [
+        (&test_user_id, "Test User", "test@example.com"),
+        (&other_user_id, "Other User", "other@example.com"),
+    ] {
+        users
+            .insert(User {
+                id: id.clone(),
+                username: username.to_string(),
+                email: email.to_string(),
+                created_at: OffsetDateTime::now_utc(),
+                updated_at: OffsetDateTime::now_utc(),
+            })
+            .await
+            .unwrap();
+    }
+    (test_user_id, other_user_id)
+}
+
+async fn run_task_scenario<TR, UW>(
+    task_persistence: TR,
+    unit_of_work: UW,
+    test_user_id: String,
+    other_user_id: String,
+) where
+    TR: TaskRepositoryTrait + Send + Sync + 'static,
+    UW: UnitOfWorkTrait + Send + Sync + 'static,
+{
+    let (client, server) = tokio::io::duplex(1024);
+    let task_usecase =
+        usecase::task::TaskUsecase::new(Box::new(task_persistence), Box::new(unit_of_work));
     let task_handler = interface::handler::task::TaskHandler::new(Box::new(task_usecase));
 
     tokio::spawn(async move {
         Server::builder()
-            .add_service(TaskServiceServer::new(task_handler))
+            .add_service(TaskServiceServer::with_interceptor(
+                task_handler,
+                AuthInterceptor::new(TEST_AUTH_SECRET),
+            ))
             .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
             .await
     });
@@ -84,17 +239,28 @@ async fn test_task() {
                 if let Some(client) = client {
                     Ok(TokioIo::new(client))
                 } else {
-                    Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Client already taken",
-                    ))
+                    Err(std::io::Error::other("Client already taken"))
                 }
             }
         }))
         .await
         .unwrap();
 
-    let mut client = TaskServiceClient::new(channel);
+    // トークンが無いリクエストは拒否される
+    assert_eq!(
+        TaskServiceClient::new(channel.clone())
+            .get_list_tasks(GetListTasksRequest::default())
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::Unauthenticated
+    );
+
+    let token = issue_token(TEST_AUTH_SECRET, &test_user_id, Duration::from_secs(600)).unwrap();
+    let mut client = TaskServiceClient::with_interceptor(
+        channel.clone(),
+        BearerToken(format!("Bearer {}", token).parse().unwrap()),
+    );
 
     // CreateTaskとGetTaskのためのTaskRequest
     let task_request = TaskRequest {
@@ -104,6 +270,8 @@ async fn test_task() {
         priority: 1,
         weight: 1,
         user_id: test_user_id.clone(),
+        parent_id: None,
+        recurrence: None,
     };
 
     let request = tonic::Request::new(CreateTaskRequest {
@@ -128,6 +296,8 @@ async fn test_task() {
         priority: 1,
         weight: 1,
         user_id: test_user_id.clone(),
+        parent_id: None,
+        recurrence: None,
     };
 
     let requests = vec![
@@ -142,6 +312,8 @@ async fn test_task() {
                 priority: 1,
                 weight: 1,
                 user_id: test_user_id.clone(),
+                parent_id: None,
+                recurrence: None,
             }),
         }),
         tonic::Request::new(CreateTaskRequest {
@@ -152,6 +324,8 @@ async fn test_task() {
                 priority: 1,
                 weight: 1,
                 user_id: test_user_id.clone(),
+                parent_id: None,
+                recurrence: None,
             }),
         }),
     ];
@@ -164,6 +338,7 @@ async fn test_task() {
     let get_list_tasks_response = client
         .get_list_tasks(GetListTasksRequest {
             user_id: test_user_id.clone(),
+            ..Default::default()
         })
         .await
         .unwrap();
@@ -214,6 +389,89 @@ async fn test_task() {
     assert_eq!(found_task.weight, one_of_tasks.weight);
     assert_eq!(found_task.user_id, one_of_tasks.user_id);
 
+    // ページングのテスト
+    let first_page = client
+        .get_list_tasks(GetListTasksRequest {
+            user_id: test_user_id.clone(),
+            page_size: 3,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(first_page.get_ref().tasks.len(), 3);
+    assert!(!first_page.get_ref().next_page_token.is_empty());
+
+    // リクエストのuser_idではなくトークンのユーザーで絞り込まれる
+    let other_user_list = client
+        .get_list_tasks(GetListTasksRequest {
+            user_id: "other_user".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(other_user_list.get_ref().tasks.len(), 4);
+
+    let second_page = client
+        .get_list_tasks(GetListTasksRequest {
+            user_id: test_user_id.clone(),
+            page_size: 3,
+            page_token: first_page.get_ref().next_page_token.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(second_page.get_ref().tasks.len(), 1);
+    assert!(second_page.get_ref().next_page_token.is_empty());
+    assert!(first_page
+        .get_ref()
+        .tasks
+        .iter()
+        .all(|t| t.id != second_page.get_ref().tasks[0].id));
+
+    // ソート条件が異なるページトークンは受け付けない
+    assert_eq!(
+        client
+            .get_list_tasks(GetListTasksRequest {
+                user_id: test_user_id.clone(),
+                page_size: 3,
+                page_token: first_page.get_ref().next_page_token.clone(),
+                sort_key: TaskSortKey::Priority.into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::InvalidArgument
+    );
+
+    // フィルタのテスト
+    let filtered = client
+        .get_list_tasks(GetListTasksRequest {
+            user_id: test_user_id.clone(),
+            filter: Some(TaskFilter {
+                text: Some("TITLE3".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(filtered.get_ref().tasks.len(), 1);
+    assert_eq!(filtered.get_ref().tasks[0].title, "test_title3");
+
+    let filtered = client
+        .get_list_tasks(GetListTasksRequest {
+            user_id: test_user_id.clone(),
+            filter: Some(TaskFilter {
+                priority_min: Some(2),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(filtered.get_ref().tasks.is_empty());
+
     // UpdateTaskのテスト
     let update_task_request = TaskUpdate {
         title: Some("updated_title".to_string()),
@@ -227,10 +485,31 @@ async fn test_task() {
     let update_request = tonic::Request::new(UpdateTaskRequest {
         task_id: create_task_response.get_ref().task_id.clone(),
         task_update: Some(update_task_request.clone()),
+        expected_version: 1,
+        update_mask: None,
     });
 
     let update_task_response = client.update_task(update_request).await.unwrap();
     println!("UPDATE RESPONSE={:?}", update_task_response);
+    assert_eq!(update_task_response.get_ref().version, 2);
+
+    // 古いバージョンを指定した更新は上書きせずにAbortedを返す
+    assert_eq!(
+        client
+            .update_task(UpdateTaskRequest {
+                task_id: create_task_response.get_ref().task_id.clone(),
+                task_update: Some(TaskUpdate {
+                    title: Some("stale_title".to_string()),
+                    ..Default::default()
+                }),
+                expected_version: 1,
+                update_mask: None,
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::Aborted
+    );
 
     // 更新後のタスクを取得して検証
     let updated_task_response = client
@@ -241,6 +520,15 @@ async fn test_task() {
         .unwrap();
 
     // update_taskのassert
+    assert_eq!(
+        updated_task_response
+            .get_ref()
+            .task
+            .as_ref()
+            .unwrap()
+            .version,
+        2
+    );
     assert_eq!(
         updated_task_response.get_ref().task.as_ref().unwrap().title,
         update_task_request.title.unwrap()
@@ -291,18 +579,625 @@ async fn test_task() {
         update_task_request.user_id.unwrap()
     );
 
-    // テスト後にデータベースをクリーンアップ
-    let cleanup_stmt = Statement::from_sql_and_values(
-        sea_orm::DatabaseBackend::Postgres,
-        r#"DELETE FROM tasks WHERE user_id = $1"#,
-        vec![test_user_id.clone().into()],
+    // update_maskに含めたフィールドだけを書き込み、値の無いdescriptionは消す
+    client
+        .update_task(UpdateTaskRequest {
+            task_id: create_task_response.get_ref().task_id.clone(),
+            task_update: Some(TaskUpdate {
+                title: Some("ignored_title".to_string()),
+                ..Default::default()
+            }),
+            expected_version: 2,
+            update_mask: Some(prost_types::FieldMask {
+                paths: vec!["description".to_string()],
+            }),
+        })
+        .await
+        .unwrap();
+    let masked_task = client
+        .get_task(GetTaskRequest {
+            task_id: create_task_response.get_ref().task_id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .task
+        .unwrap();
+    assert_eq!(
+        masked_task.title,
+        updated_task_response.get_ref().task.as_ref().unwrap().title
     );
-    db_for_cleanup.execute(cleanup_stmt).await.unwrap();
+    assert_eq!(masked_task.description, Some(String::new()));
+    assert_eq!(masked_task.version, 3);
 
-    let cleanup_user_stmt = Statement::from_sql_and_values(
-        sea_orm::DatabaseBackend::Postgres,
-        r#"DELETE FROM users WHERE user_id = $1"#,
-        vec![test_user_id.clone().into()],
+    // 作成直後のタスクは未着手
+    assert_eq!(
+        updated_task_response
+            .get_ref()
+            .task
+            .as_ref()
+            .unwrap()
+            .status(),
+        TaskStatus::Todo
+    );
+
+    // CompleteTaskのテスト
+    let complete_task_response = client
+        .complete_task(CompleteTaskRequest {
+            task_id: create_task_response.get_ref().task_id.clone(),
+        })
+        .await
+        .unwrap();
+    let completed_task = complete_task_response.get_ref().task.as_ref().unwrap();
+    assert_eq!(completed_task.status(), TaskStatus::Done);
+    assert!(completed_task.completed_at.is_some());
+
+    // 完了済みのタスクは再度完了できない
+    assert_eq!(
+        client
+            .complete_task(CompleteTaskRequest {
+                task_id: create_task_response.get_ref().task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::FailedPrecondition
+    );
+
+    // ReopenTaskのテスト
+    let reopen_task_response = client
+        .reopen_task(ReopenTaskRequest {
+            task_id: create_task_response.get_ref().task_id.clone(),
+        })
+        .await
+        .unwrap();
+    let reopened_task = reopen_task_response.get_ref().task.as_ref().unwrap();
+    assert_eq!(reopened_task.status(), TaskStatus::Todo);
+    assert!(reopened_task.completed_at.is_none());
+
+    // DeleteTaskのテスト
+    let deleted_task_id = create_task_response.get_ref().task_id.clone();
+    client
+        .delete_task(DeleteTaskRequest {
+            task_id: deleted_task_id.clone(),
+        })
+        .await
+        .unwrap();
+
+    // 論理削除したタスクは取得できない
+    assert_eq!(
+        client
+            .get_task(GetTaskRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::NotFound
+    );
+    let get_list_tasks_response = client
+        .get_list_tasks(GetListTasksRequest {
+            user_id: test_user_id.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(get_list_tasks_response.get_ref().tasks.len(), 3);
+
+    // RestoreTaskのテスト
+    client
+        .restore_task(RestoreTaskRequest {
+            task_id: deleted_task_id.clone(),
+        })
+        .await
+        .unwrap();
+    let restored_task_response = client
+        .get_task(GetTaskRequest {
+            task_id: deleted_task_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        restored_task_response
+            .get_ref()
+            .task
+            .as_ref()
+            .unwrap()
+            .title,
+        "updated_title"
+    );
+
+    // GetTaskHistoryのテスト
+    let history = client
+        .get_task_history(GetTaskHistoryRequest {
+            task_id: deleted_task_id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .entries;
+    assert_eq!(
+        history.iter().map(|e| e.action()).collect::<Vec<_>>(),
+        vec![
+            TaskHistoryAction::Created,
+            TaskHistoryAction::Updated,
+            TaskHistoryAction::Updated,
+            TaskHistoryAction::Updated,
+            TaskHistoryAction::Updated,
+            TaskHistoryAction::Deleted,
+            TaskHistoryAction::Restored,
+        ]
+    );
+    assert!(history.iter().all(|e| e.actor_id == test_user_id));
+    let title_change = history[1]
+        .changes
+        .iter()
+        .find(|c| c.field == "title")
+        .unwrap();
+    assert_eq!(title_change.after.as_deref(), Some("updated_title"));
+
+    // 他のユーザーのタスクは閲覧・削除できない
+    let other_token =
+        issue_token(TEST_AUTH_SECRET, &other_user_id, Duration::from_secs(600)).unwrap();
+    let mut other_client = TaskServiceClient::with_interceptor(
+        channel.clone(),
+        BearerToken(format!("Bearer {}", other_token).parse().unwrap()),
+    );
+    assert_eq!(
+        other_client
+            .get_task(GetTaskRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::PermissionDenied
+    );
+
+    // 共有されたタスクは閲覧できるが削除はできない
+    client
+        .share_task(ShareTaskRequest {
+            task_id: deleted_task_id.clone(),
+            user_id: other_user_id.clone(),
+        })
+        .await
+        .unwrap();
+    let shared_task_response = other_client
+        .get_task(GetTaskRequest {
+            task_id: deleted_task_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        shared_task_response
+            .get_ref()
+            .task
+            .as_ref()
+            .unwrap()
+            .user_id,
+        test_user_id
+    );
+    assert_eq!(
+        other_client
+            .delete_task(DeleteTaskRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::PermissionDenied
+    );
+
+    // 共有を解除すると再び閲覧できなくなる
+    client
+        .unshare_task(UnshareTaskRequest {
+            task_id: deleted_task_id.clone(),
+            user_id: other_user_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        other_client
+            .get_task(GetTaskRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::PermissionDenied
+    );
+
+    // サブタスクのテスト
+    let subtask_response = client
+        .create_task(CreateTaskRequest {
+            task_request: Some(TaskRequest {
+                title: "subtask".to_string(),
+                description: None,
+                due_date: Some(prost_types::Timestamp::default()),
+                priority: 1,
+                weight: 3,
+                user_id: String::new(),
+                parent_id: Some(deleted_task_id.clone()),
+                recurrence: None,
+            }),
+        })
+        .await
+        .unwrap();
+    let subtask_id = subtask_response.get_ref().task_id.clone();
+
+    // 依存関係のテスト
+    client
+        .add_task_dependency(AddTaskDependencyRequest {
+            task_id: deleted_task_id.clone(),
+            depends_on_task_id: subtask_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        client
+            .add_task_dependency(AddTaskDependencyRequest {
+                task_id: subtask_id.clone(),
+                depends_on_task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::FailedPrecondition
+    );
+    assert_eq!(
+        client
+            .complete_task(CompleteTaskRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::FailedPrecondition
+    );
+    let order_response = client
+        .get_tasks_in_dependency_order(GetTasksInDependencyOrderRequest {})
+        .await
+        .unwrap();
+    let order: Vec<String> = order_response
+        .get_ref()
+        .tasks
+        .iter()
+        .map(|t| t.id.clone())
+        .collect();
+    let position = |id: &String| order.iter().position(|t| t == id).unwrap();
+    assert!(position(&subtask_id) < position(&deleted_task_id));
+    client
+        .remove_task_dependency(RemoveTaskDependencyRequest {
+            task_id: deleted_task_id.clone(),
+            depends_on_task_id: subtask_id.clone(),
+        })
+        .await
+        .unwrap();
+
+    client
+        .complete_task(CompleteTaskRequest {
+            task_id: subtask_id.clone(),
+        })
+        .await
+        .unwrap();
+    let subtree_response = client
+        .get_task_subtree(GetTaskSubtreeRequest {
+            task_id: deleted_task_id.clone(),
+        })
+        .await
+        .unwrap();
+    let root = subtree_response.get_ref().root.as_ref().unwrap();
+    assert_eq!(root.children.len(), 1);
+    assert_eq!(
+        root.children[0].task.as_ref().unwrap().parent_id,
+        Some(deleted_task_id.clone())
+    );
+    assert_eq!(root.total_weight, 3);
+    assert_eq!(root.completed_weight, 3);
+    assert_eq!(root.progress, 1.0);
+
+    // GeneratePlanのテスト
+    let plan_response = client
+        .generate_plan(GeneratePlanRequest {
+            hours_per_day: 8,
+            days: 7,
+            start_date: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(plan_response.get_ref().days.len(), 7);
+    assert_eq!(
+        client
+            .generate_plan(GeneratePlanRequest {
+                hours_per_day: 0,
+                days: 7,
+                start_date: None,
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::InvalidArgument
+    );
+
+    // GetNextTasksのテスト
+    let next_tasks_response = client
+        .get_next_tasks(GetNextTasksRequest {
+            limit: 2,
+            weights: None,
+        })
+        .await
+        .unwrap();
+    let next_tasks = &next_tasks_response.get_ref().tasks;
+    assert_eq!(next_tasks.len(), 2);
+    assert!(next_tasks[0].score >= next_tasks[1].score);
+
+    // PurgeTaskのテスト。所有者でも管理者でなければ完全に削除できない
+    assert_eq!(
+        client
+            .purge_task(PurgeTaskRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::PermissionDenied
+    );
+    let admin_token = issue_token_with_role(
+        TEST_AUTH_SECRET,
+        &format!("test_admin_{}", Uuid::new_v4()),
+        Role::Admin,
+        Duration::from_secs(600),
+    )
+    .unwrap();
+    let mut admin_client = TaskServiceClient::with_interceptor(
+        channel,
+        BearerToken(format!("Bearer {}", admin_token).parse().unwrap()),
+    );
+    // 子タスクが残っている間は完全に削除できない
+    assert_eq!(
+        admin_client
+            .purge_task(PurgeTaskRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::FailedPrecondition
+    );
+    admin_client
+        .purge_task(PurgeTaskRequest {
+            task_id: subtask_id.clone(),
+        })
+        .await
+        .unwrap();
+    admin_client
+        .purge_task(PurgeTaskRequest {
+            task_id: deleted_task_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        client
+            .restore_task(RestoreTaskRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::NotFound
+    );
+    // 完全に削除したタスクの履歴も所有者は閲覧できる
+    let history = client
+        .get_task_history(GetTaskHistoryRequest {
+            task_id: deleted_task_id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .entries;
+    assert_eq!(history.len(), 8);
+    assert_eq!(history[7].action(), TaskHistoryAction::Purged);
+    assert_eq!(
+        other_client
+            .get_task_history(GetTaskHistoryRequest {
+                task_id: deleted_task_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::PermissionDenied
+    );
+
+    // 不正なIDはInvalidArgumentになる
+    assert_eq!(
+        client
+            .get_task(GetTaskRequest {
+                task_id: "not-a-uuid".to_string(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::InvalidArgument
+    );
+
+    // 繰り返しタスクのテスト
+    let recurring_response = client
+        .create_task(CreateTaskRequest {
+            task_request: Some(TaskRequest {
+                title: "recurring".to_string(),
+                description: None,
+                due_date: Some(prost_types::Timestamp::default()),
+                priority: 1,
+                weight: 1,
+                user_id: String::new(),
+                parent_id: None,
+                recurrence: Some("FREQ=DAILY;COUNT=2".to_string()),
+            }),
+        })
+        .await
+        .unwrap();
+    let completed = client
+        .complete_task(CompleteTaskRequest {
+            task_id: recurring_response.get_ref().task_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(completed.get_ref().task.as_ref().unwrap().recurrence, None);
+    let recurring_list = client
+        .get_list_tasks(GetListTasksRequest {
+            filter: Some(TaskFilter {
+                text: Some("recurring".to_string()),
+                statuses: vec![TaskStatus::Todo.into()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let next_task = &recurring_list.get_ref().tasks[0];
+    assert_eq!(recurring_list.get_ref().tasks.len(), 1);
+    assert_eq!(next_task.recurrence.as_deref(), Some("FREQ=DAILY;COUNT=1"));
+    assert_eq!(next_task.due_date.as_ref().unwrap().seconds, 86_400);
+
+    // 一括処理のテスト
+    let batch_task_request = |title: &str| CreateTaskRequest {
+        task_request: Some(TaskRequest {
+            title: title.to_string(),
+            description: None,
+            due_date: Some(prost_types::Timestamp::default()),
+            priority: 1,
+            weight: 1,
+            user_id: String::new(),
+            parent_id: None,
+            recurrence: None,
+        }),
+    };
+    let batch_ids = client
+        .batch_create_tasks(BatchCreateTasksRequest {
+            requests: vec![batch_task_request("batch_a"), batch_task_request("batch_b")],
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .task_ids;
+    assert_eq!(batch_ids.len(), 2);
+    let batch_update_request =
+        |task_id: &str, title: &str, expected_version: i32| UpdateTaskRequest {
+            task_id: task_id.to_string(),
+            task_update: Some(TaskUpdate {
+                title: Some(title.to_string()),
+                ..Default::default()
+            }),
+            expected_version,
+            update_mask: None,
+        };
+    // 1件でも失敗した場合は他の要素も書き込まない
+    let batch_error = client
+        .batch_update_tasks(BatchUpdateTasksRequest {
+            requests: vec![
+                batch_update_request(&batch_ids[0], "batch_a2", 1),
+                batch_update_request(&batch_ids[1], "batch_b2", 5),
+            ],
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(batch_error.code(), tonic::Code::Aborted);
+    assert_eq!(
+        client
+            .get_task(GetTaskRequest {
+                task_id: batch_ids[0].clone(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .task
+            .unwrap()
+            .title,
+        "batch_a"
+    );
+    let batch_updated = client
+        .batch_update_tasks(BatchUpdateTasksRequest {
+            requests: vec![
+                batch_update_request(&batch_ids[0], "batch_a2", 1),
+                batch_update_request(&batch_ids[1], "batch_b2", 1),
+            ],
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .responses;
+    assert!(batch_updated.iter().all(|r| r.version == 2));
+    assert_eq!(
+        client
+            .batch_delete_tasks(BatchDeleteTasksRequest {
+                task_ids: vec![batch_ids[0].clone(), batch_ids[0].clone()],
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::InvalidArgument
+    );
+    client
+        .batch_delete_tasks(BatchDeleteTasksRequest {
+            task_ids: batch_ids.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        client
+            .get_task(GetTaskRequest {
+                task_id: batch_ids[1].clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        tonic::Code::NotFound
+    );
+
+    // WatchTasksのテスト
+    let mut events = client
+        .watch_tasks(WatchTasksRequest {})
+        .await
+        .unwrap()
+        .into_inner();
+    let mut other_events = other_client
+        .watch_tasks(WatchTasksRequest {})
+        .await
+        .unwrap()
+        .into_inner();
+    let watched_task_id = client
+        .create_task(CreateTaskRequest {
+            task_request: Some(TaskRequest {
+                title: "watched".to_string(),
+                description: None,
+                due_date: Some(prost_types::Timestamp::default()),
+                priority: 1,
+                weight: 1,
+                user_id: String::new(),
+                parent_id: None,
+                recurrence: None,
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .task_id;
+    client
+        .delete_task(DeleteTaskRequest {
+            task_id: watched_task_id.clone(),
+        })
+        .await
+        .unwrap();
+    let created_event = events.message().await.unwrap().unwrap();
+    assert_eq!(created_event.r#type(), TaskEventType::Created);
+    assert_eq!(created_event.task.unwrap().id, watched_task_id);
+    let deleted_event = events.message().await.unwrap().unwrap();
+    assert_eq!(deleted_event.r#type(), TaskEventType::Deleted);
+    assert_eq!(deleted_event.task.unwrap().id, watched_task_id);
+    // 共有されていないタスクのイベントは他のユーザーに届かない
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), other_events.message())
+            .await
+            .is_err()
     );
-    db_for_cleanup.execute(cleanup_user_stmt).await.unwrap();
 }
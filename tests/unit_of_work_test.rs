@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use dotenv::dotenv;
+use sea_orm::{ConnectionTrait, Database, Statement};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use gakusai2024_backend::{
+    domain::{
+        repository::{
+            task::TaskRepositoryTrait,
+            unit_of_work::{TransactionScopeTrait, UnitOfWorkTrait},
+            user::UserRepositoryTrait,
+        },
+        task::{Task, TaskStatus},
+        user::User,
+    },
+    error::CustomError,
+    infrastructure::{
+        db::{task::TaskPersistence, unit_of_work::UnitOfWork, user::UserPersistence},
+        memory::{
+            task::InMemoryTaskPersistence, unit_of_work::InMemoryUnitOfWork,
+            user::InMemoryUserPersistence, InMemoryStore,
+        },
+    },
+};
+
+fn create_user(id: &str) -> User {
+    User {
+        id: id.to_string(),
+        username: "Test User".to_string(),
+        email: "test@example.com".to_string(),
+        created_at: OffsetDateTime::now_utc(),
+        updated_at: OffsetDateTime::now_utc(),
+    }
+}
+
+fn create_task(user_id: &str) -> Task {
+    Task {
+        id: Uuid::new_v4(),
+        title: "starter".to_string(),
+        description: "starter task".to_string(),
+        due_date: OffsetDateTime::now_utc(),
+        priority: 1,
+        weight: 1,
+        created_at: OffsetDateTime::now_utc(),
+        updated_at: OffsetDateTime::now_utc(),
+        deleted_at: None,
+        status: TaskStatus::Todo,
+        completed_at: None,
+        parent_id: None,
+        recurrence: None,
+        version: 1,
+        user_id: user_id.to_string(),
+    }
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_unit_of_work() {
+    dotenv().ok();
+    let db_url = std::env::var("DATABASE_URL").unwrap();
+    let conn = Arc::new(Database::connect(db_url).await.unwrap());
+
+    let committed_user_id = run_unit_of_work_scenario(
+        UnitOfWork::new(conn.clone()),
+        UserPersistence::new(conn.clone()),
+        TaskPersistence::new(conn.clone()),
+    )
+    .await;
+
+    // テスト後にデータベースをクリーンアップ
+    conn.execute(Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM tasks WHERE user_id = $1"#,
+        vec![committed_user_id.clone().into()],
+    ))
+    .await
+    .unwrap();
+    conn.execute(Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM users WHERE user_id = $1"#,
+        vec![committed_user_id.into()],
+    ))
+    .await
+    .unwrap();
+}
+
+// DBを使わずに同じシナリオを実行する
+#[tokio::test]
+async fn test_unit_of_work_in_memory() {
+    let store = InMemoryStore::new();
+    let unit_of_work = InMemoryUnitOfWork::new(store.clone());
+    let users = InMemoryUserPersistence::new(store.clone());
+    let tasks = InMemoryTaskPersistence::new(store.clone());
+
+    let committed_user_id = run_unit_of_work_scenario(
+        InMemoryUnitOfWork::new(store.clone()),
+        users,
+        InMemoryTaskPersistence::new(store.clone()),
+    )
+    .await;
+
+    // begin以降に同じ行への他の書き込みがあった場合はcommitできない
+    let task = create_task(&committed_user_id);
+    tasks.insert(task.clone(), vec![]).await.unwrap();
+    let txn = unit_of_work.begin().await.unwrap();
+    txn.tasks()
+        .update(
+            Task {
+                title: "from transaction".to_string(),
+                ..task.clone()
+            },
+            vec![],
+        )
+        .await
+        .unwrap();
+    tasks
+        .update(
+            Task {
+                title: "from outside".to_string(),
+                ..task.clone()
+            },
+            vec![],
+        )
+        .await
+        .unwrap();
+    assert!(matches!(
+        txn.commit().await,
+        Err(CustomError::TransactionConflict(_))
+    ));
+    assert_eq!(tasks.find(task.id).await.unwrap().title, "from outside");
+}
+
+// commitしたユーザーのIDを返す
+async fn run_unit_of_work_scenario<UW, UR, TR>(unit_of_work: UW, users: UR, tasks: TR) -> String
+where
+    UW: UnitOfWorkTrait,
+    UR: UserRepositoryTrait,
+    TR: TaskRepositoryTrait,
+{
+    // commitするとユーザーとタスクの両方が書き込まれる
+    let committed_user_id = format!("test_user_{}", Uuid::new_v4());
+    let committed_task = create_task(&committed_user_id);
+    let txn = unit_of_work.begin().await.unwrap();
+    txn.users()
+        .insert(create_user(&committed_user_id))
+        .await
+        .unwrap();
+    txn.tasks()
+        .insert(committed_task.clone(), vec![])
+        .await
+        .unwrap();
+    // commitするまでは他の接続からは見えない
+    assert!(users.find(committed_user_id.clone()).await.is_err());
+    txn.commit().await.unwrap();
+    assert_eq!(
+        users.find(committed_user_id.clone()).await.unwrap().id,
+        committed_user_id
+    );
+    assert_eq!(
+        tasks.find(committed_task.id).await.unwrap().user_id,
+        committed_user_id
+    );
+
+    // commitせずに破棄すると何も書き込まれない
+    let rolled_back_user_id = format!("test_user_{}", Uuid::new_v4());
+    let rolled_back_task = create_task(&rolled_back_user_id);
+    {
+        let txn = unit_of_work.begin().await.unwrap();
+        txn.users()
+            .insert(create_user(&rolled_back_user_id))
+            .await
+            .unwrap();
+        txn.tasks()
+            .insert(rolled_back_task.clone(), vec![])
+            .await
+            .unwrap();
+    }
+    assert!(users.find(rolled_back_user_id).await.is_err());
+    assert!(tasks.find(rolled_back_task.id).await.is_err());
+
+    // 別々のタスクを更新するトランザクションは、他の書き込みがあっても両方commitできる
+    let first_task = create_task(&committed_user_id);
+    let second_task = create_task(&committed_user_id);
+    tasks.insert(first_task.clone(), vec![]).await.unwrap();
+    tasks.insert(second_task.clone(), vec![]).await.unwrap();
+    let first = unit_of_work.begin().await.unwrap();
+    let second = unit_of_work.begin().await.unwrap();
+    first
+        .tasks()
+        .update(
+            Task {
+                title: "first".to_string(),
+                ..first_task.clone()
+            },
+            vec![],
+        )
+        .await
+        .unwrap();
+    second
+        .tasks()
+        .update(
+            Task {
+                title: "second".to_string(),
+                ..second_task.clone()
+            },
+            vec![],
+        )
+        .await
+        .unwrap();
+    first.commit().await.unwrap();
+    tasks
+        .insert(create_task(&committed_user_id), vec![])
+        .await
+        .unwrap();
+    second.commit().await.unwrap();
+    assert_eq!(tasks.find(first_task.id).await.unwrap().title, "first");
+    assert_eq!(tasks.find(second_task.id).await.unwrap().title, "second");
+
+    committed_user_id
+}
@@ -0,0 +1,303 @@
+use std::{sync::Arc, time::Duration};
+
+use dotenv::dotenv;
+use gakusai2024_proto::api::{
+    user_service_client::UserServiceClient, user_service_server::UserServiceServer,
+    CreateUserRequest, GetListUsersRequest, GetUserRequest, UpdateUserRequest, UserRequest,
+    UserUpdate,
+};
+use hyper_util::rt::TokioIo;
+#[cfg(feature = "sqlite")]
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectionTrait, Database, Statement};
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::Interceptor,
+    transport::{Endpoint, Server, Uri},
+    Code, Status,
+};
+use tower::service_fn;
+use uuid::Uuid;
+
+use gakusai2024_backend::{
+    domain::repository::user::UserRepositoryTrait,
+    infrastructure::{
+        self,
+        memory::{user::InMemoryUserPersistence, InMemoryStore},
+    },
+    interface::{
+        self,
+        auth::{issue_token, AuthInterceptor},
+        handler::user::UserHandlerTrait,
+    },
+    usecase::{self, user::UserUsecaseTrait},
+};
+
+const TEST_AUTH_SECRET: &[u8] = b"test-secret";
+
+// クライアント側でAuthorizationヘッダを付与する
+#[derive(Clone)]
+struct BearerToken(MetadataValue<Ascii>);
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert("authorization", self.0.clone());
+        Ok(request)
+    }
+}
+
+fn bearer_token(user_id: &str) -> BearerToken {
+    let token = issue_token(TEST_AUTH_SECRET, user_id, Duration::from_secs(600)).unwrap();
+    BearerToken(format!("Bearer {}", token).parse().unwrap())
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_user() {
+    dotenv().ok();
+    let db_url = std::env::var("DATABASE_URL").unwrap();
+
+    let db_for_cleanup = Database::connect(db_url.clone()).await.unwrap();
+    let db = Database::connect(db_url).await.unwrap();
+
+    // テスト用のユーザーIDを生成
+    let test_user_id = format!("test_user_{}", Uuid::new_v4());
+
+    let user_persistence = infrastructure::db::user::UserPersistence::new(Arc::new(db));
+    run_user_scenario(user_persistence, test_user_id.clone()).await;
+
+    // テスト後にデータベースをクリーンアップ
+    let cleanup_user_stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        r#"DELETE FROM users WHERE user_id = $1"#,
+        vec![test_user_id.clone().into()],
+    );
+    db_for_cleanup.execute(cleanup_user_stmt).await.unwrap();
+}
+
+// DBを使わずに同じシナリオを実行する
+#[tokio::test]
+async fn test_user_in_memory() {
+    let user_persistence = InMemoryUserPersistence::new(InMemoryStore::new());
+    run_user_scenario(user_persistence, format!("test_user_{}", Uuid::new_v4())).await;
+}
+
+// SQLiteのインメモリDBにマイグレーションを適用し、同じシナリオを実行する
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_user_sqlite() {
+    let db = infrastructure::db::DbConfig::new("sqlite::memory:".to_string())
+        .connect()
+        .await
+        .unwrap();
+    Migrator::up(db.as_ref(), None).await.unwrap();
+    run_user_scenario(
+        infrastructure::db::user::UserPersistence::new(db),
+        format!("test_user_{}", Uuid::new_v4()),
+    )
+    .await;
+}
+
+async fn run_user_scenario<UR>(user_persistence: UR, test_user_id: String)
+where
+    UR: UserRepositoryTrait + Send + Sync + 'static,
+{
+    let (client, server) = tokio::io::duplex(1024);
+    let user_usecase = usecase::user::UserUsecase::new(Box::new(user_persistence));
+    let user_handler = interface::handler::user::UserHandler::new(Box::new(user_usecase));
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(UserServiceServer::with_interceptor(
+                user_handler,
+                AuthInterceptor::new(TEST_AUTH_SECRET),
+            ))
+            .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
+            .await
+    });
+
+    // Move client to an option so we can _move_ the inner value
+    // on the first attempt to connect. All other attempts will fail.
+    let mut client = Some(client);
+    let channel = Endpoint::try_from("http://[::]:50051")
+        .unwrap()
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let client = client.take();
+
+            async move {
+                if let Some(client) = client {
+                    Ok(TokioIo::new(client))
+                } else {
+                    Err(std::io::Error::other("Client already taken"))
+                }
+            }
+        }))
+        .await
+        .unwrap();
+
+    // トークンが無いリクエストは拒否される
+    assert_eq!(
+        UserServiceClient::new(channel.clone())
+            .get_list_users(GetListUsersRequest {})
+            .await
+            .unwrap_err()
+            .code(),
+        Code::Unauthenticated
+    );
+
+    let mut client =
+        UserServiceClient::with_interceptor(channel.clone(), bearer_token(&test_user_id));
+
+    // トークンと異なるidでは登録できない
+    assert_eq!(
+        client
+            .create_user(CreateUserRequest {
+                user_request: Some(UserRequest {
+                    id: format!("spoofed_{}", Uuid::new_v4()),
+                    username: "Test User".to_string(),
+                    email: "test@example.com".to_string(),
+                }),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        Code::PermissionDenied
+    );
+    // 空のユーザー名や不正なメールアドレスは登録できない
+    assert_eq!(
+        client
+            .create_user(CreateUserRequest {
+                user_request: Some(UserRequest {
+                    id: String::new(),
+                    username: String::new(),
+                    email: "invalid".to_string(),
+                }),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        Code::InvalidArgument
+    );
+
+    // idを省略した場合はトークンのユーザーとして登録される
+    let user_request = UserRequest {
+        id: String::new(),
+        username: "Test User".to_string(),
+        email: "test@example.com".to_string(),
+    };
+
+    let create_user_response = client
+        .create_user(tonic::Request::new(CreateUserRequest {
+            user_request: Some(user_request.clone()),
+        }))
+        .await
+        .unwrap();
+
+    println!("RESPONSE={:?}", create_user_response);
+
+    assert_eq!(create_user_response.get_ref().user_id, test_user_id);
+
+    let get_user_response = client
+        .get_user(GetUserRequest {
+            user_id: test_user_id.clone(),
+        })
+        .await
+        .unwrap();
+
+    println!("RESPONSE={:?}", get_user_response);
+
+    // get_userのassert
+    let user = get_user_response.get_ref().user.as_ref().unwrap();
+    assert_eq!(user.id, test_user_id);
+    assert_eq!(user.username, user_request.username);
+    assert_eq!(user.email, user_request.email);
+
+    // get_list_usersのassert。自分のユーザーだけが返される
+    let get_list_users_response = client.get_list_users(GetListUsersRequest {}).await.unwrap();
+    let users = &get_list_users_response.get_ref().users;
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].id, test_user_id);
+
+    // UpdateUserのテスト
+    let user_update = UserUpdate {
+        username: Some("Updated User".to_string()),
+        email: None,
+    };
+
+    let update_user_response = client
+        .update_user(tonic::Request::new(UpdateUserRequest {
+            user_id: test_user_id.clone(),
+            user_update: Some(user_update.clone()),
+        }))
+        .await
+        .unwrap();
+    println!("UPDATE RESPONSE={:?}", update_user_response);
+
+    let updated_user_response = client
+        .get_user(GetUserRequest {
+            user_id: test_user_id.clone(),
+        })
+        .await
+        .unwrap();
+
+    // update_userのassert
+    let updated_user = updated_user_response.get_ref().user.as_ref().unwrap();
+    assert_eq!(updated_user.username, user_update.username.unwrap());
+    // 指定しなかったフィールドは変更されない
+    assert_eq!(updated_user.email, user_request.email);
+
+    // 他のユーザーは参照・更新できない
+    let mut other_client = UserServiceClient::with_interceptor(
+        channel,
+        bearer_token(&format!("other_{}", Uuid::new_v4())),
+    );
+    assert_eq!(
+        other_client
+            .get_user(GetUserRequest {
+                user_id: test_user_id.clone(),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        Code::PermissionDenied
+    );
+    assert_eq!(
+        other_client
+            .update_user(UpdateUserRequest {
+                user_id: test_user_id.clone(),
+                user_update: Some(UserUpdate {
+                    username: None,
+                    email: Some("attacker@example.com".to_string()),
+                }),
+            })
+            .await
+            .unwrap_err()
+            .code(),
+        Code::PermissionDenied
+    );
+    assert_eq!(
+        client
+            .get_user(GetUserRequest {
+                user_id: test_user_id.clone(),
+            })
+            .await
+            .unwrap()
+            .get_ref()
+            .user
+            .as_ref()
+            .unwrap()
+            .email,
+        user_request.email
+    );
+
+    // 登録していないユーザーの一覧は空になる
+    assert!(other_client
+        .get_list_users(GetListUsersRequest {})
+        .await
+        .unwrap()
+        .get_ref()
+        .users
+        .is_empty());
+}